@@ -169,6 +169,15 @@ fn memchr_benches(c: &mut Criterion) {
                 });
             },
         );
+
+        #[cfg(feature = "std-simd")]
+        fwd.bench_with_input(BenchmarkId::new("std_simd", &case.label), &len, |b, &n| {
+            b.iter(|| unsafe {
+                let r =
+                    faststrings::stdsimd::memchr(black_box(ptr), black_box(n), black_box(needle));
+                black_box(r);
+            });
+        });
     }
     fwd.finish();
 
@@ -161,6 +161,15 @@ fn memcmp_benches(c: &mut Criterion) {
                 black_box(r);
             });
         });
+
+        #[cfg(feature = "std-simd")]
+        group.bench_with_input(BenchmarkId::new("std_simd", &case.label), &len, |b, &n| {
+            b.iter(|| unsafe {
+                let r =
+                    faststrings::stdsimd::memcmp(black_box(s1_ptr), black_box(s2_ptr), black_box(n));
+                black_box(r);
+            });
+        });
     }
 
     group.finish();
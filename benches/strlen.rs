@@ -97,6 +97,22 @@ fn strlen_benches(c: &mut Criterion) {
                 });
             },
         );
+
+        group.bench_with_input(BenchmarkId::new("raw", &case.label), &len, |b, _| {
+            b.iter(|| unsafe {
+                black_box(faststrings::raw::strlen(black_box(s.as_ptr() as *const c_char)));
+            });
+        });
+
+        #[cfg(feature = "std-simd")]
+        group.bench_with_input(BenchmarkId::new("std_simd", &case.label), &len, |b, _| {
+            b.iter(|| unsafe {
+                black_box(faststrings::stdsimd::strlen(
+                    black_box(s.as_ptr()),
+                    black_box(s.len()),
+                ));
+            });
+        });
     }
     group.finish();
 }
@@ -308,6 +308,17 @@ fn strspn_benches(c: &mut Criterion) {
                     });
                 },
             );
+
+            // The bare kernel, without the NUL scans of both arguments.
+            #[cfg(feature = "std-simd")]
+            group.bench_with_input(BenchmarkId::new("std_simd", &case_label), &len, |b, _| {
+                b.iter(|| {
+                    black_box(faststrings::stdsimd::strspn_small_set(
+                        black_box(&s[..len]),
+                        black_box(&accept[..3]),
+                    ));
+                });
+            });
         }
     }
     group.finish();
@@ -349,6 +360,17 @@ fn strcspn_benches(c: &mut Criterion) {
                     });
                 },
             );
+
+            // The bare kernel, without the NUL scans of both arguments.
+            #[cfg(feature = "std-simd")]
+            group.bench_with_input(BenchmarkId::new("std_simd", &case_label), &len, |b, _| {
+                b.iter(|| {
+                    black_box(faststrings::stdsimd::find_first_any_small_set(
+                        black_box(&s[..len]),
+                        black_box(&reject[..1]),
+                    ));
+                });
+            });
         }
     }
     group.finish();
@@ -1,5 +1,6 @@
 use core::ffi::{c_char, c_void};
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use faststrings::arena::Arena;
 use faststrings::strdup::strdup as fast_strdup;
 use faststrings::strndup::strndup as fast_strndup;
 use faststrings::strtok::strtok as fast_strtok;
@@ -83,6 +84,22 @@ fn strdup_benches(c: &mut Criterion) {
                 black_box(unsafe { core::ptr::read_volatile(out.as_ptr()) });
             });
         });
+
+        // Bulk duplication: the arena is reset every 1024 copies.
+        group.bench_with_input(BenchmarkId::new("arena", &label), &len, |b, _| {
+            let mut arena = Arena::new();
+            let mut live = 0usize;
+            b.iter(|| {
+                if live == 1024 {
+                    arena.reset();
+                    live = 0;
+                }
+                live += 1;
+                let out = arena.strdup(black_box(&src));
+                black_box(out.len());
+                black_box(unsafe { core::ptr::read_volatile(out.as_ptr()) });
+            });
+        });
     }
     group.finish();
 }
@@ -0,0 +1,166 @@
+/* faststrings: libc-compatible string and memory routines.
+ *
+ * Generated from src/capi.rs by
+ *     FASTSTRINGS_BLESS=1 cargo test --features capi capi::
+ * Do not edit by hand.
+ */
+#ifndef FASTSTRINGS_H
+#define FASTSTRINGS_H
+
+#include <stddef.h>
+#include <string.h>
+#include <wchar.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Receives the name of the _chk routine that caught an overflow. */
+typedef void (*faststrings_chk_fail_hook)(const char *routine);
+
+void *memcpy(void *dest, const void *src, size_t n);
+void *memmove(void *dest, const void *src, size_t n);
+void *memset(void *s, int c, size_t n);
+int memcmp(const void *s1, const void *s2, size_t n);
+#if !defined(__cplusplus)
+void *memchr(const void *s, int c, size_t n);
+#endif
+#if !defined(__cplusplus)
+void *memrchr(const void *s, int c, size_t n);
+#endif
+void *memccpy(void *dest, const void *src, int c, size_t n);
+void *memmem(const void *haystack, size_t haystacklen, const void *needle, size_t needlelen);
+void explicit_bzero(void *s, size_t n);
+void *memset_explicit(void *s, int c, size_t n);
+void bzero(void *s, size_t n);
+int bcmp(const void *s1, const void *s2, size_t n);
+int timingsafe_bcmp(const void *b1, const void *b2, size_t n);
+int timingsafe_memcmp(const void *b1, const void *b2, size_t len);
+void bcopy(const void *src, void *dest, size_t n);
+int ffs(int i);
+size_t strlen(const char *s);
+size_t strnlen(const char *s, size_t maxlen);
+int strverscmp(const char *s1, const char *s2);
+char *strcpy(char *dest, const char *src);
+char *strncpy(char *dest, const char *src, size_t n);
+char *stpcpy(char *dest, const char *src);
+char *stpncpy(char *dest, const char *src, size_t n);
+char *strcat(char *dest, const char *src);
+char *strncat(char *dest, const char *src, size_t n);
+int strcmp(const char *s1, const char *s2);
+int strncmp(const char *s1, const char *s2, size_t n);
+int strcoll(const char *s1, const char *s2);
+int strcasecmp(const char *s1, const char *s2);
+int strncasecmp(const char *s1, const char *s2, size_t n);
+size_t strlcpy(char *dest, const char *src, size_t size);
+size_t strlcat(char *dest, const char *src, size_t size);
+#if !defined(__cplusplus)
+char *strchr(const char *s, int c);
+#endif
+#if !defined(__cplusplus)
+char *strchrnul(const char *s, int c);
+#endif
+#if !defined(__cplusplus)
+char *strrchr(const char *s, int c);
+#endif
+#if !defined(__cplusplus)
+char *strstr(const char *haystack, const char *needle);
+#endif
+#if !defined(__cplusplus)
+char *strcasestr(const char *haystack, const char *needle);
+#endif
+size_t strspn(const char *s, const char *accept);
+size_t strcspn(const char *s, const char *reject);
+#if !defined(__cplusplus)
+char *strpbrk(const char *s, const char *accept);
+#endif
+#if !defined(__cplusplus)
+char *index(const char *s, int c);
+#endif
+#if !defined(__cplusplus)
+char *rindex(const char *s, int c);
+#endif
+char *strtok(char *s, const char *delim);
+char *strtok_r(char *s, const char *delim, char **saveptr);
+size_t strxfrm(char *dest, const char *src, size_t n);
+char *strdup(const char *s);
+char *strndup(const char *s, size_t n);
+char *strerror(int errnum);
+#if !defined(__GLIBC__)
+int strerror_r(int errnum, char *buf, size_t buflen);
+#endif
+size_t wcslen(const wchar_t *s);
+size_t wcsnlen(const wchar_t *s, size_t maxlen);
+wchar_t *wcscpy(wchar_t *dest, const wchar_t *src);
+wchar_t *wcsncpy(wchar_t *dest, const wchar_t *src, size_t n);
+wchar_t *wcpcpy(wchar_t *dest, const wchar_t *src);
+wchar_t *wcpncpy(wchar_t *dest, const wchar_t *src, size_t n);
+wchar_t *wcscat(wchar_t *dest, const wchar_t *src);
+wchar_t *wcsncat(wchar_t *dest, const wchar_t *src, size_t n);
+int wcscmp(const wchar_t *s1, const wchar_t *s2);
+int wcsncmp(const wchar_t *s1, const wchar_t *s2, size_t n);
+int wcscoll(const wchar_t *s1, const wchar_t *s2);
+#if !defined(__cplusplus)
+wchar_t *wcschr(const wchar_t *s, wchar_t c);
+#endif
+#if !defined(__cplusplus)
+wchar_t *wcsrchr(const wchar_t *s, wchar_t c);
+#endif
+#if !defined(__cplusplus)
+wchar_t *wcsstr(const wchar_t *haystack, const wchar_t *needle);
+#endif
+size_t wcsspn(const wchar_t *s, const wchar_t *accept);
+size_t wcscspn(const wchar_t *s, const wchar_t *reject);
+#if !defined(__cplusplus)
+wchar_t *wcspbrk(const wchar_t *s, const wchar_t *accept);
+#endif
+int wcscasecmp(const wchar_t *s1, const wchar_t *s2);
+int wcsncasecmp(const wchar_t *s1, const wchar_t *s2, size_t n);
+wchar_t *wcschrnul(const wchar_t *s, wchar_t c);
+size_t wcslcpy(wchar_t *dest, const wchar_t *src, size_t size);
+size_t wcslcat(wchar_t *dest, const wchar_t *src, size_t size);
+wchar_t *wcstok(wchar_t *s, const wchar_t *delim, wchar_t **saveptr);
+size_t wcsxfrm(wchar_t *dest, const wchar_t *src, size_t n);
+wchar_t *wcsdup(const wchar_t *s);
+wchar_t *wmemcpy(wchar_t *dest, const wchar_t *src, size_t n);
+wchar_t *wmempcpy(wchar_t *dest, const wchar_t *src, size_t n);
+wchar_t *wmemmove(wchar_t *dest, const wchar_t *src, size_t n);
+wchar_t *wmemset(wchar_t *s, wchar_t c, size_t n);
+int wmemcmp(const wchar_t *s1, const wchar_t *s2, size_t n);
+#if !defined(__cplusplus)
+wchar_t *wmemchr(const wchar_t *s, wchar_t c, size_t n);
+#endif
+wchar_t *wmemrchr(const wchar_t *s, wchar_t c, size_t n);
+faststrings_chk_fail_hook faststrings_set_chk_fail_hook(faststrings_chk_fail_hook hook);
+void __chk_fail(void);
+void *__memcpy_chk(void *dest, const void *src, size_t len, size_t destlen);
+void *__memmove_chk(void *dest, const void *src, size_t len, size_t destlen);
+void *__mempcpy_chk(void *dest, const void *src, size_t len, size_t destlen);
+void *__memset_chk(void *dest, int c, size_t len, size_t destlen);
+void __explicit_bzero_chk(void *s, size_t len, size_t destlen);
+char *__strcpy_chk(char *dest, const char *src, size_t destlen);
+char *__stpcpy_chk(char *dest, const char *src, size_t destlen);
+char *__strncpy_chk(char *dest, const char *src, size_t n, size_t destlen);
+char *__stpncpy_chk(char *dest, const char *src, size_t n, size_t destlen);
+char *__strcat_chk(char *dest, const char *src, size_t destlen);
+char *__strncat_chk(char *dest, const char *src, size_t n, size_t destlen);
+size_t __strlcpy_chk(char *dest, const char *src, size_t size, size_t destlen);
+size_t __strlcat_chk(char *dest, const char *src, size_t size, size_t destlen);
+wchar_t *__wcscpy_chk(wchar_t *dest, const wchar_t *src, size_t n);
+wchar_t *__wcpcpy_chk(wchar_t *dest, const wchar_t *src, size_t destlen);
+wchar_t *__wcsncpy_chk(wchar_t *dest, const wchar_t *src, size_t n, size_t destlen);
+wchar_t *__wcpncpy_chk(wchar_t *dest, const wchar_t *src, size_t n, size_t destlen);
+wchar_t *__wcscat_chk(wchar_t *dest, const wchar_t *src, size_t destlen);
+wchar_t *__wcsncat_chk(wchar_t *dest, const wchar_t *src, size_t n, size_t destlen);
+size_t __wcslcpy_chk(wchar_t *dest, const wchar_t *src, size_t n, size_t destlen);
+size_t __wcslcat_chk(wchar_t *dest, const wchar_t *src, size_t n, size_t destlen);
+wchar_t *__wmemcpy_chk(wchar_t *s1, const wchar_t *s2, size_t n, size_t ns1);
+wchar_t *__wmemmove_chk(wchar_t *s1, const wchar_t *s2, size_t n, size_t ns1);
+wchar_t *__wmempcpy_chk(wchar_t *s1, const wchar_t *s2, size_t n, size_t ns1);
+wchar_t *__wmemset_chk(wchar_t *s, wchar_t c, size_t n, size_t dstlen);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* FASTSTRINGS_H */
@@ -0,0 +1,346 @@
+//! Bump arena for bulk duplication of short strings.
+//!
+//! Duplicating many short strings with `strdup` spends most of its time in
+//! the allocator. An [`Arena`] instead carves each copy out of a large chunk
+//! and frees everything at once when it is dropped or [`reset`](Arena::reset):
+//!
+//! ```
+//! use faststrings::arena::Arena;
+//!
+//! let arena = Arena::new();
+//! let words: Vec<&[u8]> = ["alpha", "beta", "gamma"]
+//!     .iter()
+//!     .map(|w| &*arena.strdup(w.as_bytes()))
+//!     .collect();
+//! assert_eq!(words[1], b"beta\0");
+//! ```
+//!
+//! `&Arena` is also an [`Allocator`], so the `_in` routines such as
+//! [`strdup_in`](crate::strdup::strdup_in) can allocate from it. Freeing an
+//! arena allocation does nothing; the memory comes back with the arena.
+#![allow(unsafe_code)]
+
+use alloc::alloc::{alloc, dealloc, handle_alloc_error};
+use alloc::vec::Vec;
+use allocator_api2::alloc::{AllocError, Allocator};
+use core::alloc::Layout;
+use core::cell::{Cell, RefCell};
+use core::ptr::{self, NonNull};
+
+use crate::str::strlen;
+use crate::strdup::write_terminated;
+use crate::strndup;
+use crate::types::wchar_t;
+use crate::wide::wcslen;
+
+/// Size of the first chunk.
+const FIRST_CHUNK: usize = 4096;
+
+/// Chunks double in size up to this; larger requests get a chunk of their own.
+const MAX_CHUNK: usize = 1 << 20;
+
+/// Alignment of every chunk.
+const CHUNK_ALIGN: usize = 16;
+
+/// A bump allocator that frees all of its allocations together.
+pub struct Arena {
+    /// Every chunk, the current one last.
+    chunks: RefCell<Vec<Chunk>>,
+    /// Next free byte of the current chunk.
+    cursor: Cell<*mut u8>,
+    /// End of the current chunk.
+    end: Cell<*mut u8>,
+}
+
+struct Chunk {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+// Every allocation is disjoint and handed out once, so `&self` methods may
+// return it as `&mut`.
+#[allow(clippy::mut_from_ref)]
+impl Arena {
+    /// An empty arena; the first allocation reserves the first chunk.
+    pub const fn new() -> Self {
+        Self {
+            chunks: RefCell::new(Vec::new()),
+            cursor: Cell::new(ptr::null_mut()),
+            end: Cell::new(ptr::null_mut()),
+        }
+    }
+
+    /// An arena whose first chunk holds at least `bytes` bytes.
+    pub fn with_capacity(bytes: usize) -> Self {
+        let arena = Self::new();
+        if bytes > 0 {
+            let layout = Layout::from_size_align(bytes, 1).unwrap_or_else(|_| capacity_overflow());
+            if arena.new_chunk(layout).is_err() {
+                handle_alloc_error(layout);
+            }
+        }
+        arena
+    }
+
+    /// Total size of the chunks currently reserved.
+    pub fn allocated_bytes(&self) -> usize {
+        self.chunks.borrow().iter().map(|c| c.layout.size()).sum()
+    }
+
+    /// Frees every allocation, keeping the most recent chunk for reuse.
+    pub fn reset(&mut self) {
+        let chunks = self.chunks.get_mut();
+        let Some(last) = chunks.pop() else {
+            return;
+        };
+        for chunk in chunks.drain(..) {
+            chunk.free();
+        }
+        self.cursor.set(last.ptr.as_ptr());
+        // SAFETY: The chunk spans `layout.size()` bytes.
+        self.end
+            .set(unsafe { last.ptr.as_ptr().add(last.layout.size()) });
+        chunks.push(last);
+    }
+
+    /// Copies the C string in `src` into the arena, like
+    /// [`strdup`](crate::strdup::strdup).
+    ///
+    /// Aborts if the allocation fails.
+    pub fn strdup(&self, src: &[u8]) -> &mut [u8] {
+        self.dup(&src[..strlen(src).min(src.len())], 0)
+    }
+
+    /// Copies at most `n` bytes of the C string in `src` into the arena, like
+    /// [`strndup`](crate::strndup::strndup).
+    ///
+    /// Aborts if the allocation fails.
+    pub fn strndup(&self, src: &[u8], n: usize) -> &mut [u8] {
+        self.dup(strndup::prefix(src, n), 0)
+    }
+
+    /// Copies the wide C string in `src` into the arena, like
+    /// [`wcsdup`](crate::wcsdup::wcsdup).
+    ///
+    /// Aborts if the allocation fails.
+    pub fn wcsdup(&self, src: &[wchar_t]) -> &mut [wchar_t] {
+        self.dup(&src[..wcslen(src).min(src.len())], 0)
+    }
+
+    /// Like [`strdup`](Self::strdup), but returns an error instead of
+    /// aborting when the allocation fails.
+    pub fn try_strdup(&self, src: &[u8]) -> Result<&mut [u8], AllocError> {
+        self.try_dup(&src[..strlen(src).min(src.len())], 0)
+    }
+
+    /// Like [`strndup`](Self::strndup), but returns an error instead of
+    /// aborting when the allocation fails.
+    pub fn try_strndup(&self, src: &[u8], n: usize) -> Result<&mut [u8], AllocError> {
+        self.try_dup(strndup::prefix(src, n), 0)
+    }
+
+    /// Like [`wcsdup`](Self::wcsdup), but returns an error instead of
+    /// aborting when the allocation fails.
+    pub fn try_wcsdup(&self, src: &[wchar_t]) -> Result<&mut [wchar_t], AllocError> {
+        self.try_dup(&src[..wcslen(src).min(src.len())], 0)
+    }
+
+    fn dup<T: Copy>(&self, s: &[T], nul: T) -> &mut [T] {
+        match self.try_dup(s, nul) {
+            Ok(out) => out,
+            Err(_) => handle_alloc_error(
+                Layout::array::<T>(s.len() + 1).unwrap_or_else(|_| capacity_overflow()),
+            ),
+        }
+    }
+
+    /// Copies `s` and a terminating `nul` into a fresh arena allocation.
+    fn try_dup<T: Copy>(&self, s: &[T], nul: T) -> Result<&mut [T], AllocError> {
+        let layout = Layout::array::<T>(s.len() + 1).map_err(|_| AllocError)?;
+        let out = self.bump(layout)?.cast::<T>().as_ptr();
+        // SAFETY: The allocation is aligned for `T`, holds `s.len() + 1`
+        // elements, is not handed out again and cannot overlap `s`.
+        unsafe {
+            write_terminated(out, s, nul);
+            Ok(core::slice::from_raw_parts_mut(out, s.len() + 1))
+        }
+    }
+
+    /// Reserves `layout` from the current chunk, starting a new one when it
+    /// does not fit.
+    fn bump(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        if layout.size() == 0 {
+            return Ok(dangling(layout));
+        }
+        if let Some(p) = self.bump_in_current(layout) {
+            return Ok(p);
+        }
+        self.new_chunk(layout)?;
+        Ok(self
+            .bump_in_current(layout)
+            .expect("a new chunk fits the allocation"))
+    }
+
+    fn bump_in_current(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let cursor = self.cursor.get();
+        let pad = cursor.addr().wrapping_neg() & (layout.align() - 1);
+        let needed = pad.checked_add(layout.size())?;
+        if needed > self.end.get().addr() - cursor.addr() {
+            return None;
+        }
+        // SAFETY: `pad + size` bytes past the cursor are within the chunk.
+        unsafe {
+            let start = cursor.add(pad);
+            self.cursor.set(start.add(layout.size()));
+            Some(NonNull::new_unchecked(start))
+        }
+    }
+
+    /// Starts a chunk large enough for `layout` and makes it current.
+    fn new_chunk(&self, layout: Layout) -> Result<(), AllocError> {
+        let mut chunks = self.chunks.borrow_mut();
+        let grown = match chunks.last() {
+            Some(last) => (last.layout.size() * 2).min(MAX_CHUNK),
+            None => FIRST_CHUNK,
+        };
+        let size = layout
+            .size()
+            .checked_add(layout.align())
+            .ok_or(AllocError)?
+            .max(grown);
+        let chunk_layout = Layout::from_size_align(size, layout.align().max(CHUNK_ALIGN))
+            .map_err(|_| AllocError)?;
+        chunks.try_reserve(1).map_err(|_| AllocError)?;
+        // SAFETY: `chunk_layout` has a non-zero size.
+        let ptr = NonNull::new(unsafe { alloc(chunk_layout) }).ok_or(AllocError)?;
+        chunks.push(Chunk {
+            ptr,
+            layout: chunk_layout,
+        });
+        self.cursor.set(ptr.as_ptr());
+        // SAFETY: The chunk spans `size` bytes.
+        self.end.set(unsafe { ptr.as_ptr().add(size) });
+        Ok(())
+    }
+}
+
+// SAFETY: The arena owns its chunks; nothing else points into them while it
+// can be moved.
+unsafe impl Send for Arena {}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        for chunk in self.chunks.get_mut().drain(..) {
+            chunk.free();
+        }
+    }
+}
+
+impl Chunk {
+    fn free(self) {
+        // SAFETY: `ptr` came from `alloc(layout)` and is freed only here.
+        unsafe { dealloc(self.ptr.as_ptr(), self.layout) }
+    }
+}
+
+// SAFETY: Allocations are disjoint, aligned and live until the arena is reset
+// or dropped, which needs `&mut Arena` and so outlives every `&Arena`.
+unsafe impl Allocator for &Arena {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = self.bump(layout)?;
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+}
+
+fn dangling(layout: Layout) -> NonNull<u8> {
+    // SAFETY: Alignments are non-zero.
+    unsafe { NonNull::new_unchecked(ptr::without_provenance_mut(layout.align())) }
+}
+
+#[cold]
+fn capacity_overflow() -> ! {
+    panic!("capacity overflow")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strdup::strdup_in;
+    use std::vec;
+
+    #[test]
+    fn test_arena_dups_match_global_dups() {
+        let arena = Arena::new();
+        let mut copies = Vec::new();
+        for len in 0..300 {
+            let src: Vec<u8> = (0..len).map(|i| b'a' + (i % 26) as u8).collect();
+            copies.push((arena.strdup(&src), crate::strdup::strdup(&src)));
+        }
+        for (ours, expected) in &copies {
+            assert_eq!(&ours[..], &expected[..]);
+        }
+        assert_eq!(arena.strndup(b"abcdef\0", 3), b"abc\0");
+        assert_eq!(arena.strndup(b"ab\0cd", 5), b"ab\0");
+        assert!(arena.allocated_bytes() >= 300 * 301 / 2);
+    }
+
+    #[test]
+    fn test_arena_wcsdup_is_aligned() {
+        let arena = Arena::new();
+        let src = [b'x' as wchar_t, b'y' as wchar_t, 0];
+        for odd in 1..8 {
+            arena.strdup(&vec![b'a'; odd]);
+            let out = arena.wcsdup(&src);
+            assert!(out.as_ptr().is_aligned());
+            assert_eq!(out, &src[..]);
+        }
+        assert_eq!(arena.try_wcsdup(&src[..2]).unwrap(), &src[..]);
+    }
+
+    #[test]
+    fn test_arena_large_allocations_get_their_own_chunk() {
+        let arena = Arena::with_capacity(64);
+        let big = vec![b'z'; 3 * MAX_CHUNK];
+        let out = arena.try_strdup(&big).unwrap();
+        assert_eq!(out.len(), big.len() + 1);
+        assert_eq!(out[big.len()], 0);
+        assert_eq!(arena.strdup(b"small\0"), b"small\0");
+    }
+
+    #[test]
+    fn test_arena_reset_reuses_last_chunk() {
+        let mut arena = Arena::new();
+        for _ in 0..2000 {
+            arena.strdup(b"0123456789\0");
+        }
+        let before = arena.chunks.borrow().len();
+        assert!(before > 1);
+        arena.reset();
+        assert_eq!(arena.chunks.borrow().len(), 1);
+        let kept = arena.allocated_bytes();
+        assert_eq!(arena.strdup(b"again\0"), b"again\0");
+        assert_eq!(arena.allocated_bytes(), kept);
+    }
+
+    #[test]
+    fn test_arena_as_allocator() {
+        let arena = Arena::new();
+        let a = strdup_in(b"left\0", &arena);
+        let b = strdup_in(b"right", &arena);
+        assert_eq!(a.as_slice(), b"left\0");
+        assert_eq!(b.as_slice(), b"right\0");
+        drop(a);
+        let mut grown = crate::strdup::try_strdup_in(b"abc", &arena).unwrap();
+        grown.extend_from_slice(&[b'd'; 100]);
+        assert_eq!(grown.len(), 104);
+        assert_eq!(&b[..], b"right\0");
+    }
+}
@@ -0,0 +1,281 @@
+//! Fixed-capacity C string buffer that never allocates.
+//!
+//! [`ArrayCStr<N>`] holds up to `N - 1` bytes and a terminator inline, and
+//! implements [`fmt::Write`], so `write!` formats straight into it. Like
+//! `snprintf`, output that does not fit is cut off while the buffer stays a
+//! valid C string, and [`wanted_len`](ArrayCStr::wanted_len) reports how long
+//! the untruncated string would have been.
+//!
+//! ```
+//! use core::fmt::Write;
+//! use faststrings::arraycstr::ArrayCStr;
+//!
+//! let mut msg = ArrayCStr::<17>::new();
+//! write!(msg, "errno {}: ", 2).unwrap();
+//! msg.append(c"No such file or directory");
+//! assert_eq!(msg.as_c_str(), c"errno 2: No such");
+//! assert_eq!(msg.wanted_len(), 34);
+//! assert!(msg.is_truncated());
+//! ```
+#![allow(unsafe_code)]
+
+use core::fmt;
+use core::ops::Deref;
+
+use crate::cstr::CStrLike;
+use crate::memcpy::optimized_memcpy_unified;
+use crate::nulstr::NulStr;
+
+/// A C string of at most `N - 1` bytes stored inline.
+///
+/// `N` counts the terminator and must be at least 1. Bytes are added up to
+/// the first NUL of each piece, so the buffer never holds an interior NUL.
+/// It dereferences to [`NulStr`] for the pointer and slice views.
+#[derive(Clone, Copy)]
+pub struct ArrayCStr<const N: usize> {
+    /// The string, its terminator at `len`, then unused bytes.
+    buf: [u8; N],
+    /// Bytes stored, without the terminator.
+    len: usize,
+    /// Bytes every append so far asked for, without the terminator.
+    wanted: usize,
+}
+
+impl<const N: usize> ArrayCStr<N> {
+    /// Bytes the buffer holds besides the terminator.
+    pub const CAPACITY: usize = N - 1;
+
+    /// An empty string.
+    pub const fn new() -> Self {
+        const { assert!(N > 0, "an ArrayCStr needs room for its terminator") };
+        Self {
+            buf: [0; N],
+            len: 0,
+            wanted: 0,
+        }
+    }
+
+    /// Formats `args` into a new buffer, truncating like `snprintf`.
+    ///
+    /// # Examples
+    /// ```
+    /// use faststrings::arraycstr::ArrayCStr;
+    /// let s = ArrayCStr::<8>::from_fmt(format_args!("{}-{}", 12, 345));
+    /// assert_eq!(s.as_bytes(), b"12-345");
+    /// ```
+    pub fn from_fmt(args: fmt::Arguments<'_>) -> Self {
+        let mut s = Self::new();
+        // `write_str` never fails.
+        let _ = fmt::Write::write_fmt(&mut s, args);
+        s
+    }
+
+    /// Length the string would have without truncation: the sum of
+    /// everything appended since it was created or cleared.
+    ///
+    /// This is what `snprintf` and `strlcat` return.
+    #[inline]
+    pub const fn wanted_len(&self) -> usize {
+        self.wanted
+    }
+
+    /// Whether any appended bytes were dropped for lack of room.
+    #[inline]
+    pub const fn is_truncated(&self) -> bool {
+        self.wanted > self.len
+    }
+
+    /// Bytes still free, not counting the terminator.
+    #[inline]
+    pub const fn remaining(&self) -> usize {
+        Self::CAPACITY - self.len
+    }
+
+    /// Appends `s` up to its terminator, keeping what fits, and returns
+    /// [`wanted_len`](Self::wanted_len), like `strlcat`.
+    pub fn append<S: CStrLike + ?Sized>(&mut self, s: &S) -> usize {
+        self.append_bytes(s.str_bytes());
+        self.wanted
+    }
+
+    /// Appends the byte `b`, if it fits, and returns
+    /// [`wanted_len`](Self::wanted_len).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `b` is NUL.
+    pub fn push(&mut self, b: u8) -> usize {
+        assert!(b != 0, "cannot push a NUL into an ArrayCStr");
+        self.append_bytes(&[b]);
+        self.wanted
+    }
+
+    /// Shortens the string to `len` bytes; does nothing if it is not longer.
+    ///
+    /// [`wanted_len`](Self::wanted_len) becomes the new length.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            self.len = len;
+            self.buf[len] = 0;
+        }
+        self.wanted = self.len;
+    }
+
+    /// Empties the string.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// The string as a borrowed [`NulStr`].
+    #[inline]
+    pub fn as_nul_str(&self) -> &NulStr {
+        // SAFETY: `buf[len]` is the only NUL in `buf[..=len]`.
+        unsafe { NulStr::from_bytes_with_nul_unchecked(&self.buf[..=self.len]) }
+    }
+
+    /// Copies as much of `s`, which holds no NUL, as fits.
+    fn append_bytes(&mut self, s: &[u8]) {
+        self.wanted = self.wanted.saturating_add(s.len());
+        let n = s.len().min(self.remaining());
+        // SAFETY: `len + n < N`, so the copy and the terminator stay inside
+        // `buf`, and `s` cannot alias `buf` while `self` is borrowed mutably.
+        unsafe {
+            let dst = self.buf.as_mut_ptr().add(self.len);
+            optimized_memcpy_unified(dst, s.as_ptr(), n);
+            dst.add(n).write(0);
+        }
+        self.len += n;
+    }
+}
+
+impl<const N: usize> Default for ArrayCStr<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Deref for ArrayCStr<N> {
+    type Target = NulStr;
+
+    #[inline]
+    fn deref(&self) -> &NulStr {
+        self.as_nul_str()
+    }
+}
+
+impl<const N: usize> CStrLike for ArrayCStr<N> {
+    #[inline]
+    fn str_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+/// Appends like [`append`](ArrayCStr::append) and never fails; check
+/// [`is_truncated`](ArrayCStr::is_truncated) afterwards.
+impl<const N: usize> fmt::Write for ArrayCStr<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.append(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Wipes the whole buffer and leaves the string empty.
+impl<const N: usize> crate::zeroize::Zeroize for ArrayCStr<N> {
+    fn zeroize(&mut self) {
+        self.buf.zeroize();
+        self.len = 0;
+        self.wanted = 0;
+    }
+}
+
+impl<const N: usize> PartialEq for ArrayCStr<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.str_bytes() == other.str_bytes()
+    }
+}
+
+impl<const N: usize> Eq for ArrayCStr<N> {}
+
+impl<const N: usize> fmt::Debug for ArrayCStr<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_nul_str(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+
+    fn check<const N: usize>(s: &ArrayCStr<N>, expect: &[u8], wanted: usize) {
+        assert_eq!(s.as_bytes(), expect);
+        assert_eq!(s.as_bytes_with_nul()[expect.len()], 0);
+        assert_eq!(crate::str::strlen(s.as_bytes_with_nul()), expect.len());
+        assert_eq!(s.wanted_len(), wanted);
+        assert_eq!(s.is_truncated(), wanted > expect.len());
+    }
+
+    #[test]
+    fn test_write_fits() {
+        let mut s = ArrayCStr::<32>::new();
+        check(&s, b"", 0);
+        write!(s, "id:{:04x}", 0xbeef).unwrap();
+        check(&s, b"id:beef", 7);
+        assert_eq!(s.remaining(), 24);
+    }
+
+    #[test]
+    fn test_write_truncates_like_snprintf() {
+        let mut s = ArrayCStr::<6>::new();
+        write!(s, "{}", 1234).unwrap();
+        write!(s, "{}", 5678).unwrap();
+        check(&s, b"12345", 8);
+        assert_eq!(s.remaining(), 0);
+        // Further output is only counted.
+        assert_eq!(s.append(c"xyz"), 11);
+        check(&s, b"12345", 11);
+    }
+
+    #[test]
+    fn test_append_stops_at_nul() {
+        let mut s = ArrayCStr::<16>::new();
+        assert_eq!(s.append(&b"ab\0cd"[..]), 2);
+        assert_eq!(s.push(b'c'), 3);
+        s.write_str("d\0ignored").unwrap();
+        check(&s, b"abcd", 4);
+    }
+
+    #[test]
+    fn test_single_byte_buffer() {
+        let mut s = ArrayCStr::<1>::new();
+        assert_eq!(ArrayCStr::<1>::CAPACITY, 0);
+        assert_eq!(s.append(c"abc"), 3);
+        check(&s, b"", 3);
+    }
+
+    #[test]
+    fn test_long_copies_and_reset() {
+        let mut s = ArrayCStr::<300>::new();
+        let chunk = [b'q'; 120];
+        for _ in 0..3 {
+            s.append(&chunk);
+        }
+        check(&s, &[b'q'; 299], 360);
+        s.truncate(10);
+        check(&s, &[b'q'; 10], 10);
+        s.clear();
+        check(&s, b"", 0);
+        assert_eq!(s.append(c"ok"), 2);
+        assert_eq!(s.as_c_str(), c"ok");
+    }
+
+    #[test]
+    fn test_from_fmt_and_compare() {
+        let a = ArrayCStr::<4>::from_fmt(format_args!("{}", "abcdef"));
+        check(&a, b"abc", 6);
+        let mut b = ArrayCStr::<4>::new();
+        b.append(c"abc");
+        assert_eq!(a, b);
+        assert_eq!(crate::cstr::strcmp(&a, c"abd"), -1);
+    }
+}
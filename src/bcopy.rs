@@ -31,15 +31,15 @@ mod tests {
     }
 
     #[test]
-    fn test_bcopy_overlap() {
+    fn test_bcopy_backing_memmove_overlap() {
         let mut buf = *b"abcdef";
+        // `bcopy` takes a shared and a mutable slice, which cannot alias, so
+        // drive the overlap through the memmove it delegates to. Both pointers
+        // come from one raw pointer so neither invalidates the other.
         // src = "abcd", dest starts at index 2
-        let (src, dst) = unsafe {
-            let src = core::slice::from_raw_parts(buf.as_ptr(), 4);
-            let dst = core::slice::from_raw_parts_mut(buf.as_mut_ptr().add(2), 4);
-            (src, dst)
-        };
-        bcopy(src, dst);
+        let p = buf.as_mut_ptr();
+        unsafe { crate::memmove::optimized_memmove_unified(p.add(2), p, 4) };
         assert_eq!(&buf, b"ababcd");
+        assert_eq!(bcopy(&buf[..4], &mut [0u8; 4]), 4);
     }
 }
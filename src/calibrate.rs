@@ -0,0 +1,653 @@
+//! Opt-in host calibration of per-size-class kernels.
+//!
+//! [`calibrate`] times every kernel the running CPU supports for memcpy,
+//! memset, memmove and memchr at one representative size per [`SizeClass`]
+//! and records the fastest in a [`Profile`]. Profiles have a small text form,
+//! so a deployment can calibrate once, save the result and install it on
+//! later runs without paying for the benchmarks again:
+//!
+//! ```no_run
+//! use faststrings::calibrate::{self, Profile};
+//!
+//! let path = "/var/cache/faststrings.profile";
+//! let profile = Profile::load(path).unwrap_or_else(|_| {
+//!     let profile = calibrate::calibrate();
+//!     let _ = profile.save(path);
+//!     profile
+//! });
+//! // Fails if the profile names kernels this CPU cannot run.
+//! profile.install().unwrap();
+//! ```
+//!
+//! Nothing changes until [`Profile::install`] is called. Installing replaces
+//! each routine's dispatched kernel with a per-size-class lookup, so like
+//! `cpu::set_max_isa_level` it is meant to run once at startup.
+#![allow(unsafe_code)]
+
+use core::fmt;
+use core::hint::black_box;
+use std::io;
+use std::path::Path;
+use std::string::ToString;
+use std::time::{Duration, Instant};
+use std::vec;
+use std::vec::Vec;
+
+use crate::cpu::{self, IsaLevel};
+use crate::dispatch::FnSlot;
+use crate::memchr::{self, ScanFn};
+use crate::memcpy::{self, CopyFn};
+use crate::memmove::{self, MoveFn};
+use crate::memset::{self, SetFn};
+use crate::simd;
+
+/// A routine whose kernels can be calibrated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Routine {
+    Memcpy,
+    Memset,
+    Memmove,
+    Memchr,
+}
+
+impl Routine {
+    /// Every calibrated routine.
+    pub const ALL: [Routine; 4] = [
+        Routine::Memcpy,
+        Routine::Memset,
+        Routine::Memmove,
+        Routine::Memchr,
+    ];
+
+    /// Lowercase name, as used in the profile text.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Routine::Memcpy => "memcpy",
+            Routine::Memset => "memset",
+            Routine::Memmove => "memmove",
+            Routine::Memchr => "memchr",
+        }
+    }
+}
+
+/// Call sizes that share a kernel choice.
+///
+/// Sizes below 64 bytes never reach the dispatched kernels (each routine
+/// handles them inline), so they fall in `Small` without being timed there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SizeClass {
+    /// Below 256 bytes.
+    Small,
+    /// 256 to 1024 bytes inclusive.
+    Medium,
+    /// 1025 bytes to 256 KiB inclusive.
+    Large,
+    /// Above 256 KiB.
+    Huge,
+}
+
+impl SizeClass {
+    /// Number of classes.
+    pub const COUNT: usize = 4;
+
+    /// Every class, smallest first.
+    pub const ALL: [SizeClass; SizeClass::COUNT] = [
+        SizeClass::Small,
+        SizeClass::Medium,
+        SizeClass::Large,
+        SizeClass::Huge,
+    ];
+
+    /// Returns the class of an `n`-byte call.
+    #[inline(always)]
+    pub const fn of(n: usize) -> SizeClass {
+        if n < 256 {
+            SizeClass::Small
+        } else if n <= 1024 {
+            SizeClass::Medium
+        } else if n <= 256 * 1024 {
+            SizeClass::Large
+        } else {
+            SizeClass::Huge
+        }
+    }
+
+    /// The size timed for this class.
+    pub const fn sample_size(self) -> usize {
+        match self {
+            SizeClass::Small => 160,
+            SizeClass::Medium => 640,
+            SizeClass::Large => 32 * 1024,
+            SizeClass::Huge => 1024 * 1024,
+        }
+    }
+
+    /// Lowercase name, as used in the profile text.
+    pub const fn name(self) -> &'static str {
+        match self {
+            SizeClass::Small => "small",
+            SizeClass::Medium => "medium",
+            SizeClass::Large => "large",
+            SizeClass::Huge => "huge",
+        }
+    }
+}
+
+/// A candidate kernel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Variant {
+    /// The kernel normal dispatch picks at this ISA level.
+    Isa(IsaLevel),
+    /// `simd::memcpy_unified` (memcpy only).
+    SimdUnified,
+    /// `simd::memcpy_unified_refined` (memcpy only).
+    SimdRefined,
+    /// `simd::memcpy_unified_no_medium` (memcpy, 256 bytes and up).
+    SimdNoMedium,
+    /// `simd::memcpy_unified_no_medium_refined` (memcpy, 256 bytes and up).
+    SimdNoMediumRefined,
+    /// `simd::memcpy_avx2_unaligned_256_1024` (memcpy, `Medium` only).
+    SimdUnaligned256To1024,
+}
+
+impl Variant {
+    const SIMD: [Variant; 5] = [
+        Variant::SimdUnified,
+        Variant::SimdRefined,
+        Variant::SimdNoMedium,
+        Variant::SimdNoMediumRefined,
+        Variant::SimdUnaligned256To1024,
+    ];
+
+    /// Name used in the profile text: an ISA level name such as `"avx2"`, or
+    /// the `simd` function name without its `memcpy_` prefix.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Variant::Isa(level) => level.name(),
+            Variant::SimdUnified => "unified",
+            Variant::SimdRefined => "unified_refined",
+            Variant::SimdNoMedium => "unified_no_medium",
+            Variant::SimdNoMediumRefined => "unified_no_medium_refined",
+            Variant::SimdUnaligned256To1024 => "avx2_unaligned_256_1024",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Variant> {
+        IsaLevel::ALL
+            .into_iter()
+            .map(Variant::Isa)
+            .chain(Variant::SIMD)
+            .find(|v| v.name() == name)
+    }
+
+    /// Whether this kernel exists for `routine` and handles every size in
+    /// `class`.
+    pub fn fits(self, routine: Routine, class: SizeClass) -> bool {
+        match self {
+            Variant::Isa(_) => true,
+            Variant::SimdUnified | Variant::SimdRefined => routine == Routine::Memcpy,
+            // These go straight to the large loop, which needs 128+ bytes.
+            Variant::SimdNoMedium | Variant::SimdNoMediumRefined => {
+                routine == Routine::Memcpy && class != SizeClass::Small
+            }
+            Variant::SimdUnaligned256To1024 => {
+                routine == Routine::Memcpy && class == SizeClass::Medium
+            }
+        }
+    }
+
+    /// Whether the running CPU can execute this kernel.
+    pub fn is_supported(self) -> bool {
+        match self {
+            Variant::Isa(level) => level <= cpu::isa_level(),
+            _ => cpu::has_avx2(),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Kernel {
+    Copy(CopyFn),
+    Set(SetFn),
+    Move(MoveFn),
+    Scan(ScanFn),
+}
+
+impl Kernel {
+    fn addr(self) -> usize {
+        match self {
+            Kernel::Copy(f) => f as usize,
+            Kernel::Set(f) => f as usize,
+            Kernel::Move(f) => f as usize,
+            Kernel::Scan(f) => f as usize,
+        }
+    }
+}
+
+fn kernel(routine: Routine, variant: Variant) -> Kernel {
+    match (routine, variant) {
+        (Routine::Memcpy, Variant::Isa(level)) => Kernel::Copy(memcpy::select_memcpy_large(level)),
+        (Routine::Memset, Variant::Isa(level)) => Kernel::Set(memset::select_memset_large(level)),
+        (Routine::Memmove, Variant::Isa(level)) => {
+            Kernel::Move(memmove::select_memmove_large(level))
+        }
+        (Routine::Memchr, Variant::Isa(level)) => Kernel::Scan(memchr::select_memchr_scan(level)),
+        (_, Variant::SimdUnified) => Kernel::Copy(simd::memcpy_unified),
+        (_, Variant::SimdRefined) => Kernel::Copy(simd::memcpy_unified_refined),
+        (_, Variant::SimdNoMedium) => Kernel::Copy(simd::memcpy_unified_no_medium),
+        (_, Variant::SimdNoMediumRefined) => Kernel::Copy(simd::memcpy_unified_no_medium_refined),
+        (_, Variant::SimdUnaligned256To1024) => Kernel::Copy(simd::memcpy_avx2_unaligned_256_1024),
+    }
+}
+
+/// Why a profile could not be parsed or installed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProfileError {
+    /// Line `line` (1-based) of the profile text is malformed.
+    Parse { line: usize, reason: &'static str },
+    /// The profile names a kernel that does not fit its slot or that the
+    /// running CPU cannot execute.
+    Unsupported {
+        routine: Routine,
+        class: SizeClass,
+        variant: Variant,
+    },
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileError::Parse { line, reason } => write!(f, "line {line}: {reason}"),
+            ProfileError::Unsupported {
+                routine,
+                class,
+                variant,
+            } => write!(
+                f,
+                "{} {}: kernel `{}` is not usable here",
+                routine.name(),
+                class.name(),
+                variant.name()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+/// The chosen kernel for every routine and size class.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Profile {
+    choices: [[Variant; SizeClass::COUNT]; 4],
+}
+
+impl Default for Profile {
+    /// The choices normal dispatch makes on this CPU.
+    fn default() -> Self {
+        Profile {
+            choices: [[Variant::Isa(cpu::isa_level()); SizeClass::COUNT]; 4],
+        }
+    }
+}
+
+impl Profile {
+    /// Returns the kernel chosen for `routine` calls in `class`.
+    pub fn get(&self, routine: Routine, class: SizeClass) -> Variant {
+        self.choices[routine as usize][class as usize]
+    }
+
+    /// Chooses `variant` for `routine` calls in `class`.
+    pub fn set(&mut self, routine: Routine, class: SizeClass, variant: Variant) {
+        self.choices[routine as usize][class as usize] = variant;
+    }
+
+    /// Checks that every choice fits its slot and runs on this CPU.
+    pub fn validate(&self) -> Result<(), ProfileError> {
+        for routine in Routine::ALL {
+            for class in SizeClass::ALL {
+                let variant = self.get(routine, class);
+                if !variant.fits(routine, class) || !variant.is_supported() {
+                    return Err(ProfileError::Unsupported {
+                        routine,
+                        class,
+                        variant,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Routes every calibrated routine through the kernels in this profile.
+    ///
+    /// Nothing is installed unless the whole profile validates. Call this
+    /// before other threads use the crate; a routine resolving its default
+    /// kernel concurrently may overwrite the installed lookup.
+    pub fn install(&self) -> Result<(), ProfileError> {
+        self.validate()?;
+        for routine in Routine::ALL {
+            for class in SizeClass::ALL {
+                match kernel(routine, self.get(routine, class)) {
+                    // Validation leaves copy kernels on memcpy only.
+                    Kernel::Copy(f) => MEMCPY_BY_CLASS[class as usize].set(f),
+                    Kernel::Set(f) => MEMSET_BY_CLASS[class as usize].set(f),
+                    Kernel::Move(f) => MEMMOVE_BY_CLASS[class as usize].set(f),
+                    Kernel::Scan(f) => MEMCHR_BY_CLASS[class as usize].set(f),
+                }
+            }
+        }
+        memcpy::MEMCPY_LARGE.set(memcpy_by_class);
+        memset::MEMSET_LARGE.set(memset_by_class);
+        memmove::MEMMOVE_LARGE.set(memmove_by_class);
+        memchr::MEMCHR_SCAN.set(memchr_by_class);
+        Ok(())
+    }
+
+    /// Parses the text form produced by `Display`.
+    ///
+    /// Each non-empty line that is not a `#` comment reads
+    /// `<routine> <class> <kernel>`, e.g. `memcpy huge avx512`. Entries that
+    /// are not listed keep their [`Default`] choice.
+    pub fn parse(text: &str) -> Result<Profile, ProfileError> {
+        let mut profile = Profile::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |reason| ProfileError::Parse {
+                line: i + 1,
+                reason,
+            };
+            let mut fields = line.split_whitespace();
+            let (Some(routine), Some(class), Some(variant), None) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                return Err(err("expected `<routine> <class> <kernel>`"));
+            };
+            let routine = Routine::ALL
+                .into_iter()
+                .find(|r| r.name() == routine)
+                .ok_or(err("unknown routine"))?;
+            let class = SizeClass::ALL
+                .into_iter()
+                .find(|c| c.name() == class)
+                .ok_or(err("unknown size class"))?;
+            let variant = Variant::from_name(variant).ok_or(err("unknown kernel"))?;
+            if !variant.fits(routine, class) {
+                return Err(err("kernel does not fit this routine and class"));
+            }
+            profile.set(routine, class, variant);
+        }
+        Ok(profile)
+    }
+
+    /// Writes the text form to `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    /// Reads a profile written by [`Profile::save`].
+    ///
+    /// The result is not validated against this CPU; [`Profile::install`]
+    /// does that.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Profile> {
+        let text = std::fs::read_to_string(path)?;
+        Profile::parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# faststrings calibration profile")?;
+        for routine in Routine::ALL {
+            for class in SizeClass::ALL {
+                writeln!(
+                    f,
+                    "{} {} {}",
+                    routine.name(),
+                    class.name(),
+                    self.get(routine, class).name()
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// =============================================================================
+// INSTALLED LOOKUP: per-class kernels behind each routine's dispatch slot
+// =============================================================================
+
+// Every slot is set before the routine's dispatch slot points here, so the
+// placeholders (the public entry points) are never called through these.
+static MEMCPY_BY_CLASS: [FnSlot<CopyFn>; SizeClass::COUNT] =
+    [const { FnSlot::new(memcpy::optimized_memcpy_unified as *mut ()) }; SizeClass::COUNT];
+static MEMSET_BY_CLASS: [FnSlot<SetFn>; SizeClass::COUNT] =
+    [const { FnSlot::new(memset::optimized_memset_unified as *mut ()) }; SizeClass::COUNT];
+static MEMMOVE_BY_CLASS: [FnSlot<MoveFn>; SizeClass::COUNT] =
+    [const { FnSlot::new(memmove::optimized_memmove_unified as *mut ()) }; SizeClass::COUNT];
+static MEMCHR_BY_CLASS: [FnSlot<ScanFn>; SizeClass::COUNT] =
+    [const { FnSlot::new(memchr::optimized_memchr_unified as *mut ()) }; SizeClass::COUNT];
+
+unsafe fn memcpy_by_class(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    // SAFETY: Forwarded caller contract; installed kernels fit their class.
+    unsafe { (MEMCPY_BY_CLASS[SizeClass::of(n) as usize].get())(dest, src, n) }
+}
+
+unsafe fn memset_by_class(dest: *mut u8, value: u8, n: usize) {
+    // SAFETY: Forwarded caller contract; installed kernels fit their class.
+    unsafe { (MEMSET_BY_CLASS[SizeClass::of(n) as usize].get())(dest, value, n) }
+}
+
+unsafe fn memmove_by_class(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    // SAFETY: Forwarded caller contract; installed kernels fit their class.
+    unsafe { (MEMMOVE_BY_CLASS[SizeClass::of(n) as usize].get())(dest, src, n) }
+}
+
+unsafe fn memchr_by_class(s: *const u8, n: usize, needle: u8) -> Option<usize> {
+    // SAFETY: Forwarded caller contract; installed kernels fit their class.
+    unsafe { (MEMCHR_BY_CLASS[SizeClass::of(n) as usize].get())(s, n, needle) }
+}
+
+// =============================================================================
+// MEASUREMENT
+// =============================================================================
+
+// Bytes processed per timed round, and rounds kept after one warm-up round.
+const BYTES_PER_ROUND: usize = 1024 * 1024;
+const ROUNDS: usize = 3;
+
+/// Benchmarks every supported kernel and returns the fastest per routine and
+/// size class.
+///
+/// This takes a fraction of a second in release builds and does not install
+/// the result; see [`Profile::install`].
+pub fn calibrate() -> Profile {
+    let mut profile = Profile::default();
+    let max_len = SizeClass::Huge.sample_size() + 128;
+    let mut src = vec![0u8; max_len];
+    let mut dst = vec![0u8; max_len];
+
+    for routine in Routine::ALL {
+        for class in SizeClass::ALL {
+            let n = class.sample_size();
+            let mut seen: Vec<usize> = Vec::new();
+            let mut best = (Duration::MAX, profile.get(routine, class));
+
+            let variants = IsaLevel::ALL
+                .into_iter()
+                .map(Variant::Isa)
+                .chain(Variant::SIMD)
+                .filter(|v| v.fits(routine, class) && v.is_supported());
+            for variant in variants {
+                let k = kernel(routine, variant);
+                // Levels that share a kernel are timed once, under the lowest.
+                if seen.contains(&k.addr()) {
+                    continue;
+                }
+                seen.push(k.addr());
+                let elapsed = time_kernel(k, n, &mut src, &mut dst);
+                if elapsed < best.0 {
+                    best = (elapsed, variant);
+                }
+            }
+            profile.set(routine, class, best.1);
+        }
+    }
+
+    profile
+}
+
+fn time_kernel(kernel: Kernel, n: usize, src: &mut [u8], dst: &mut [u8]) -> Duration {
+    let iters = (BYTES_PER_ROUND / n).max(2);
+    let mut best = Duration::MAX;
+
+    for round in 0..=ROUNDS {
+        let start = Instant::now();
+        for _ in 0..iters {
+            // SAFETY: `src`/`dst` hold at least `n + 128` bytes and are
+            // distinct, so every access below stays in bounds; memmove uses
+            // overlapping ranges within `dst` on purpose.
+            unsafe {
+                let s = black_box(src.as_ptr().add(32));
+                let d = black_box(dst.as_mut_ptr().add(32));
+                match kernel {
+                    Kernel::Copy(f) => {
+                        black_box(f(d, s, n));
+                    }
+                    Kernel::Set(f) => f(d, black_box(0x5a), n),
+                    Kernel::Move(f) => {
+                        // One move each way, like a ring buffer compaction.
+                        black_box(f(d.add(16), d, n));
+                        black_box(f(d, d.add(16), n));
+                    }
+                    Kernel::Scan(f) => {
+                        black_box(f(s, n, black_box(0xff)));
+                    }
+                }
+            }
+        }
+        // The first round only warms caches and predictors.
+        if round > 0 {
+            best = best.min(start.elapsed());
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_class_boundaries() {
+        assert_eq!(SizeClass::of(0), SizeClass::Small);
+        assert_eq!(SizeClass::of(255), SizeClass::Small);
+        assert_eq!(SizeClass::of(256), SizeClass::Medium);
+        assert_eq!(SizeClass::of(1024), SizeClass::Medium);
+        assert_eq!(SizeClass::of(1025), SizeClass::Large);
+        assert_eq!(SizeClass::of(256 * 1024), SizeClass::Large);
+        assert_eq!(SizeClass::of(256 * 1024 + 1), SizeClass::Huge);
+        for class in SizeClass::ALL {
+            assert_eq!(SizeClass::of(class.sample_size()), class);
+        }
+    }
+
+    #[test]
+    fn test_profile_text_roundtrip() {
+        let mut profile = Profile::default();
+        profile.set(
+            Routine::Memcpy,
+            SizeClass::Medium,
+            Variant::SimdUnaligned256To1024,
+        );
+        profile.set(
+            Routine::Memset,
+            SizeClass::Huge,
+            Variant::Isa(IsaLevel::Scalar),
+        );
+        assert_eq!(Profile::parse(&profile.to_string()), Ok(profile));
+    }
+
+    #[test]
+    fn test_profile_parse_errors() {
+        let parse_err = |text: &str| match Profile::parse(text) {
+            Err(ProfileError::Parse { line, .. }) => line,
+            other => panic!("expected a parse error, got {other:?}"),
+        };
+        assert_eq!(parse_err("# header\nmemcpy small"), 2);
+        assert_eq!(parse_err("strcpy small avx2"), 1);
+        assert_eq!(parse_err("memcpy tiny avx2"), 1);
+        assert_eq!(parse_err("memcpy small avx1024"), 1);
+        assert_eq!(parse_err("memset small unified"), 1);
+        assert_eq!(parse_err("memcpy small unified_no_medium"), 1);
+        assert_eq!(parse_err("memcpy small avx2 extra"), 1);
+    }
+
+    // Installing rewrites the global dispatch slots under every other test,
+    // so the install runs alone in a child copy of the test binary.
+    #[test]
+    #[cfg_attr(miri, ignore = "spawns a process")]
+    fn test_calibrated_profile_installs_and_stays_correct() {
+        if std::env::var_os("FASTSTRINGS_CALIBRATE_CHILD").is_some() {
+            install_and_check();
+            return;
+        }
+
+        let out = std::process::Command::new(std::env::current_exe().unwrap())
+            .args([
+                "--exact",
+                "calibrate::tests::test_calibrated_profile_installs_and_stays_correct",
+                "--nocapture",
+            ])
+            .env("FASTSTRINGS_CALIBRATE_CHILD", "1")
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "{out:?}");
+        assert!(std::string::String::from_utf8_lossy(&out.stdout).contains("1 passed"));
+    }
+
+    fn install_and_check() {
+        let profile = calibrate();
+        assert_eq!(profile.validate(), Ok(()));
+        profile.install().unwrap();
+
+        let src: Vec<u8> = (0..300_000).map(|i| (i * 31 + 7) as u8).collect();
+        for n in [
+            0usize, 63, 64, 65, 200, 255, 256, 700, 1024, 1025, 9000, 262_145,
+        ] {
+            let mut dst = vec![0u8; n];
+            unsafe { memcpy::optimized_memcpy_unified(dst.as_mut_ptr(), src.as_ptr(), n) };
+            assert_eq!(dst, src[..n], "memcpy n={n}");
+
+            unsafe { memset::optimized_memset_unified(dst.as_mut_ptr(), 0xa5, n) };
+            assert!(dst.iter().all(|&b| b == 0xa5), "memset n={n}");
+
+            let mut buf = src[..n + 8].to_vec();
+            unsafe { memmove::optimized_memmove_unified(buf.as_mut_ptr().add(8), buf.as_ptr(), n) };
+            assert_eq!(buf[8..], src[..n], "memmove n={n}");
+
+            let mut hay = vec![0u8; n];
+            if n > 0 {
+                hay[n - 1] = 1;
+            }
+            let found = unsafe { memchr::optimized_memchr_unified(hay.as_ptr(), n, 1) };
+            assert_eq!(found, n.checked_sub(1), "memchr n={n}");
+        }
+    }
+
+    #[test]
+    fn test_install_rejects_unsupported_profile() {
+        let mut profile = Profile::default();
+        profile.choices[Routine::Memset as usize][0] = Variant::SimdUnified;
+        assert!(matches!(
+            profile.install(),
+            Err(ProfileError::Unsupported {
+                routine: Routine::Memset,
+                ..
+            })
+        ));
+    }
+}
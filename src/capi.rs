@@ -0,0 +1,1480 @@
+//! C ABI exports with libc names and semantics.
+//!
+//! Built with the `capi` feature, the crate exports every routine below under
+//! its libc name, taking raw NUL-terminated pointers and returning exactly what
+//! glibc returns (comparisons return the difference of the first mismatching
+//! `unsigned char`s, the wide ones `-1`/`1`). Each export has the contract of
+//! the libc function of the same name. Linking the library ahead of libc routes
+//! a C or C++ program's string calls here.
+//!
+//! The crate itself is an `rlib`, so a `no_std` embedder supplies its own
+//! panic handler; build the C libraries explicitly:
+//!
+//! ```text
+//! cargo rustc --release --features capi --crate-type cdylib
+//! cargo rustc --release --features capi --crate-type staticlib
+//! ```
+//!
+//! `include/faststrings.h` declares the exports. It is generated from
+//! [`PROTOTYPES`] by [`header`]; after adding an export, regenerate it with
+//!
+//! ```text
+//! FASTSTRINGS_BLESS=1 cargo test --features capi capi::
+//! ```
+//!
+//! The `_chk` exports are the `_FORTIFY_SOURCE` entry points: each compares
+//! the size of the write with the destination size the compiler passed. On
+//! overflow they run the hook installed with [`set_chk_fail_hook`] (or
+//! `faststrings_set_chk_fail_hook` from C) and abort.
+//!
+//! `memcpy`, `memmove`, `memset`, `memcmp` and `memchr` are not counted by
+//! the `instrument` feature: the Rust runtime calls them while setting up
+//! the per-thread histograms, and recording those calls would recurse.
+//!
+//! With the `preload` feature each export can instead forward to the next
+//! definition of its symbol, see [`crate::preload`].
+//!
+//! `strerror_r` follows POSIX (it returns an error number, not a pointer),
+//! except on glibc targets, which export the GNU flavour under that name and
+//! the POSIX one as `__xpg_strerror_r`, as glibc itself does. `strcoll`,
+//! `strxfrm`, `wcscoll` and `wcsxfrm` implement the C locale.
+#![allow(unsafe_code)]
+#![allow(clippy::missing_safety_doc)]
+
+use core::ffi::{CStr, c_char, c_int, c_void};
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+use std::eprintln;
+use std::format;
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+use crate::memchr::{memchr_dispatched, optimized_memrchr_unified};
+use crate::memcmp::memcmp_dispatched;
+use crate::memcpy::memcpy_dispatched;
+use crate::memmove::memmove_dispatched;
+use crate::memset::{memset_dispatched, optimized_memset_explicit};
+use crate::timingsafe::{optimized_timingsafe_bcmp, optimized_timingsafe_memcmp};
+use crate::types::wchar_t;
+
+unsafe extern "C" {
+    #[link_name = "malloc"]
+    fn libc_malloc(size: usize) -> *mut c_void;
+}
+
+/// C declaration of one export, as recorded by `c_exports!`.
+#[derive(Clone, Copy, Debug)]
+pub struct Prototype {
+    /// Symbol name.
+    pub name: &'static str,
+    /// Rust spelling of the return type, `()` for `void`.
+    pub ret: &'static str,
+    /// Parameter names and the Rust spelling of their types.
+    pub params: &'static [(&'static str, &'static str)],
+}
+
+impl Prototype {
+    /// Renders the C declaration, without the trailing semicolon.
+    pub fn to_c(&self) -> String {
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|&(name, ty)| c_declarator(ty, name))
+            .collect();
+        let params = if params.is_empty() {
+            "void".to_string()
+        } else {
+            params.join(", ")
+        };
+        format!("{}({params})", c_declarator(self.ret, self.name))
+    }
+}
+
+/// Spells `name` declared with the C equivalent of the Rust type `ty`.
+fn c_declarator(ty: &str, name: &str) -> String {
+    let c = match ty {
+        "()" => "void",
+        "c_int" => "int",
+        "usize" => "size_t",
+        "wchar_t" => "wchar_t",
+        "*const c_void" => "const void *",
+        "*mut c_void" => "void *",
+        "*const c_char" => "const char *",
+        "*mut c_char" => "char *",
+        "*mut *mut c_char" => "char **",
+        "*const wchar_t" => "const wchar_t *",
+        "*mut wchar_t" => "wchar_t *",
+        "*mut *mut wchar_t" => "wchar_t **",
+        "Option<ChkFailHook>" => "faststrings_chk_fail_hook",
+        other => panic!("no C spelling for `{other}`"),
+    };
+    if c.ends_with('*') {
+        format!("{c}{name}")
+    } else {
+        format!("{c} {name}")
+    }
+}
+
+/// Exports that `faststrings.h` declares only under a preprocessor condition,
+/// because the system headers it includes may declare them differently.
+const HEADER_GUARDS: &[(&str, &[&str])] = &[
+    // C++ overloads these on the constness of the argument; <string.h> and
+    // <wchar.h> provide those declarations.
+    (
+        "!defined(__cplusplus)",
+        &[
+            "memchr",
+            "memrchr",
+            "strchr",
+            "strchrnul",
+            "strrchr",
+            "strstr",
+            "strcasestr",
+            "strpbrk",
+            "index",
+            "rindex",
+            "wcschr",
+            "wcsrchr",
+            "wcsstr",
+            "wcspbrk",
+            "wmemchr",
+        ],
+    ),
+    // glibc's <string.h> picks the GNU or POSIX flavour from the feature
+    // macros.
+    ("!defined(__GLIBC__)", &["strerror_r"]),
+];
+
+/// Renders `faststrings.h` from [`PROTOTYPES`].
+pub fn header() -> String {
+    let mut out = String::from(
+        "/* faststrings: libc-compatible string and memory routines.\n \
+         *\n \
+         * Generated from src/capi.rs by\n \
+         *     FASTSTRINGS_BLESS=1 cargo test --features capi capi::\n \
+         * Do not edit by hand.\n \
+         */\n\
+         #ifndef FASTSTRINGS_H\n\
+         #define FASTSTRINGS_H\n\
+         \n\
+         #include <stddef.h>\n\
+         #include <string.h>\n\
+         #include <wchar.h>\n\
+         \n\
+         #ifdef __cplusplus\n\
+         extern \"C\" {\n\
+         #endif\n\
+         \n\
+         /* Receives the name of the _chk routine that caught an overflow. */\n\
+         typedef void (*faststrings_chk_fail_hook)(const char *routine);\n\
+         \n",
+    );
+    for proto in PROTOTYPES {
+        let guard = HEADER_GUARDS
+            .iter()
+            .find(|(_, names)| names.contains(&proto.name))
+            .map(|&(cond, _)| cond);
+        if let Some(cond) = guard {
+            out.push_str(&format!("#if {cond}\n"));
+        }
+        out.push_str(&proto.to_c());
+        out.push_str(";\n");
+        if guard.is_some() {
+            out.push_str("#endif\n");
+        }
+    }
+    out.push_str(
+        "\n\
+         #ifdef __cplusplus\n\
+         }\n\
+         #endif\n\
+         \n\
+         #endif /* FASTSTRINGS_H */\n",
+    );
+    out
+}
+
+/// With the `preload` feature, returns from the enclosing export through the
+/// next definition of its symbol when `FASTSTRINGS_PRELOAD` disables it.
+macro_rules! forward_if_disabled {
+    ($name:ident($($arg:ident: $ty:ty),*) $(-> $ret:ty)?) => {
+        #[cfg(feature = "preload")]
+        {
+            static ROUTE: $crate::preload::Route =
+                $crate::preload::Route::new(concat!(stringify!($name), "\0"));
+            if let Some(next) = ROUTE.next() {
+                // SAFETY: `next` is another definition of this libc symbol,
+                // so it has this signature.
+                let next: unsafe extern "C" fn($($ty),*) $(-> $ret)? =
+                    unsafe { core::mem::transmute(next) };
+                // SAFETY: The caller upholds the contract of the symbol.
+                return unsafe { next($($arg),*) };
+            }
+        }
+    };
+}
+
+/// Defines each function as an unmangled `extern "C"` export and records its
+/// signature in `PROTOTYPES`.
+macro_rules! c_exports {
+    ($(
+        $(#[$attr:meta])*
+        pub unsafe extern "C" fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)? $body:block
+    )*) => {
+        $(
+            $(#[$attr])*
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn $name($($arg: $ty),*) $(-> $ret)? {
+                forward_if_disabled!($name($($arg: $ty),*) $(-> $ret)?);
+                $body
+            }
+        )*
+
+        /// Signatures of every export, in declaration order.
+        pub const PROTOTYPES: &[Prototype] = &[$(
+            Prototype {
+                name: stringify!($name),
+                ret: c_exports!(@ret $($ret)?),
+                params: &[$((stringify!($arg), stringify!($ty))),*],
+            },
+        )*];
+    };
+    (@ret) => { "()" };
+    (@ret $ret:ty) => { stringify!($ret) };
+}
+
+unsafe fn c_strnlen(s: *const u8, maxlen: usize) -> usize {
+    // SAFETY: Forwarded caller contract.
+    unsafe { crate::raw::strnlen(s as *const c_char, maxlen) }
+}
+
+unsafe fn c_strlen(s: *const u8) -> usize {
+    // SAFETY: Forwarded caller contract.
+    unsafe { crate::raw::strlen(s as *const c_char) }
+}
+
+unsafe fn c_wcsnlen(s: *const wchar_t, maxlen: usize) -> usize {
+    let mut i = 0usize;
+    // SAFETY: Caller guarantees `s` is readable up to its terminator or
+    // `maxlen` elements.
+    while i < maxlen && unsafe { *s.add(i) } != 0 {
+        i += 1;
+    }
+    i
+}
+
+unsafe fn c_wcslen(s: *const wchar_t) -> usize {
+    // SAFETY: Forwarded caller contract.
+    unsafe { c_wcsnlen(s, usize::MAX) }
+}
+
+/// `n` elements at `p`; `p` may be null when `n` is 0.
+unsafe fn slice<'a, T>(p: *const T, n: usize) -> &'a [T] {
+    if n == 0 {
+        return &[];
+    }
+    // SAFETY: Caller guarantees `p` is readable for `n` elements.
+    unsafe { core::slice::from_raw_parts(p, n) }
+}
+
+/// `n` elements at `p`; `p` may be null when `n` is 0.
+unsafe fn slice_mut<'a, T>(p: *mut T, n: usize) -> &'a mut [T] {
+    if n == 0 {
+        return &mut [];
+    }
+    // SAFETY: Caller guarantees `p` is writable for `n` elements.
+    unsafe { core::slice::from_raw_parts_mut(p, n) }
+}
+
+/// The string at `s`, including its terminator.
+unsafe fn cstr<'a>(s: *const c_char) -> &'a [u8] {
+    // SAFETY: Caller guarantees `s` is NUL-terminated.
+    unsafe { slice(s as *const u8, c_strlen(s as *const u8) + 1) }
+}
+
+/// The wide string at `s`, including its terminator.
+unsafe fn wcs<'a>(s: *const wchar_t) -> &'a [wchar_t] {
+    // SAFETY: Caller guarantees `s` is NUL-terminated.
+    unsafe { slice(s, c_wcslen(s) + 1) }
+}
+
+/// `base + idx`, or null for `None`.
+fn at<T>(base: *const T, idx: Option<usize>) -> *mut T {
+    match idx {
+        Some(i) => base.wrapping_add(i) as *mut T,
+        None => ptr::null_mut(),
+    }
+}
+
+/// Copies `n` bytes between non-overlapping buffers.
+unsafe fn copy(dest: *mut u8, src: *const u8, n: usize) {
+    // SAFETY: Forwarded caller contract.
+    unsafe { memcpy_dispatched(dest, src, n) };
+}
+
+unsafe fn fill(dest: *mut u8, value: u8, n: usize) {
+    // SAFETY: Forwarded caller contract.
+    unsafe { memset_dispatched(dest, value, n) };
+}
+
+/// `strcmp`/`strncmp` over the first `n` bytes, both readable through the
+/// shorter string's terminator or `n`.
+unsafe fn compare_bytes(s1: *const u8, s2: *const u8, n: usize) -> c_int {
+    // SAFETY: Forwarded caller contract.
+    unsafe { memcmp_dispatched(s1, s2, n) }
+}
+
+unsafe fn casecmp(s1: *const u8, s2: *const u8, n: usize) -> c_int {
+    for i in 0..n {
+        // SAFETY: Caller guarantees both strings are readable until their
+        // terminators or `n` bytes; the loop stops at the first NUL.
+        let (a, b) = unsafe { (*s1.add(i), *s2.add(i)) };
+        let (a, b) = (a.to_ascii_lowercase(), b.to_ascii_lowercase());
+        if a != b || a == 0 {
+            return a as c_int - b as c_int;
+        }
+    }
+    0
+}
+
+unsafe fn malloc_copy<T: Copy>(src: *const T, len: usize) -> *mut T {
+    let size = (len + 1) * core::mem::size_of::<T>();
+    // SAFETY: `malloc` has no preconditions.
+    let dest = unsafe { libc_malloc(size) } as *mut T;
+    if dest.is_null() {
+        return dest;
+    }
+    // SAFETY: `dest` holds `len + 1` elements, `src` is readable for `len`;
+    // zeroing the last element writes the terminator.
+    unsafe {
+        copy(
+            dest as *mut u8,
+            src as *const u8,
+            len * core::mem::size_of::<T>(),
+        );
+        fill(dest.add(len) as *mut u8, 0, core::mem::size_of::<T>());
+    }
+    dest
+}
+
+unsafe fn posix_strerror_r(errnum: c_int, buf: *mut c_char, buflen: usize) -> c_int {
+    // SAFETY: Caller guarantees `buf` is writable for `buflen` bytes.
+    crate::strerror_r::strerror_r(errnum, unsafe { slice_mut(buf as *mut u8, buflen) })
+}
+
+/// Receives the name of the `_chk` routine that caught an overflow, as a
+/// NUL-terminated string. The process aborts if the hook returns.
+pub type ChkFailHook = unsafe extern "C" fn(routine: *const c_char);
+
+static CHK_FAIL_HOOK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Installs `hook` to run when a `_chk` export detects an overflow and
+/// returns the previous one. `None` restores the default, which reports the
+/// overflow on stderr like glibc's `__chk_fail`.
+pub fn set_chk_fail_hook(hook: Option<ChkFailHook>) -> Option<ChkFailHook> {
+    let new = hook.map_or(ptr::null_mut(), |hook| hook as *mut ());
+    let old = CHK_FAIL_HOOK.swap(new, Ordering::AcqRel);
+    // SAFETY: Only `ChkFailHook`s are stored.
+    (!old.is_null()).then(|| unsafe { core::mem::transmute::<*mut (), ChkFailHook>(old) })
+}
+
+#[cold]
+#[inline(never)]
+fn chk_fail(routine: &CStr) -> ! {
+    let hook = CHK_FAIL_HOOK.load(Ordering::Acquire);
+    if hook.is_null() {
+        eprintln!(
+            "*** buffer overflow detected ***: {}: terminated",
+            routine.to_string_lossy()
+        );
+    } else {
+        // SAFETY: Only `ChkFailHook`s are stored, and `routine` is
+        // NUL-terminated.
+        unsafe { core::mem::transmute::<*mut (), ChkFailHook>(hook)(routine.as_ptr()) };
+    }
+    std::process::abort()
+}
+
+/// Fails `routine` unless `needed` fits in the destination's `available`.
+#[inline(always)]
+fn check(routine: &CStr, needed: usize, available: usize) {
+    if needed > available {
+        chk_fail(routine);
+    }
+}
+
+static STRTOK_SAVE: AtomicPtr<c_char> = AtomicPtr::new(ptr::null_mut());
+
+c_exports! {
+    pub unsafe extern "C" fn memcpy(dest: *mut c_void, src: *const c_void, n: usize) -> *mut c_void {
+        // SAFETY: libc contract.
+        unsafe { copy(dest as *mut u8, src as *const u8, n) };
+        dest
+    }
+
+    pub unsafe extern "C" fn memmove(dest: *mut c_void, src: *const c_void, n: usize) -> *mut c_void {
+        // SAFETY: libc contract.
+        unsafe { memmove_dispatched(dest as *mut u8, src as *const u8, n) };
+        dest
+    }
+
+    pub unsafe extern "C" fn memset(s: *mut c_void, c: c_int, n: usize) -> *mut c_void {
+        // SAFETY: libc contract.
+        unsafe { fill(s as *mut u8, c as u8, n) };
+        s
+    }
+
+    pub unsafe extern "C" fn memcmp(s1: *const c_void, s2: *const c_void, n: usize) -> c_int {
+        // SAFETY: libc contract.
+        unsafe { memcmp_dispatched(s1 as *const u8, s2 as *const u8, n) }
+    }
+
+    pub unsafe extern "C" fn memchr(s: *const c_void, c: c_int, n: usize) -> *mut c_void {
+        // SAFETY: libc contract.
+        at(s, unsafe { memchr_dispatched(s as *const u8, n, c as u8) })
+    }
+
+    pub unsafe extern "C" fn memrchr(s: *const c_void, c: c_int, n: usize) -> *mut c_void {
+        // SAFETY: libc contract.
+        at(s, unsafe { optimized_memrchr_unified(s as *const u8, n, c as u8) })
+    }
+
+    pub unsafe extern "C" fn memccpy(dest: *mut c_void, src: *const c_void, c: c_int, n: usize) -> *mut c_void {
+        // SAFETY: libc contract.
+        unsafe {
+            let stop = memchr_dispatched(src as *const u8, n, c as u8);
+            copy(dest as *mut u8, src as *const u8, stop.map_or(n, |i| i + 1));
+            at(dest, stop.map(|i| i + 1))
+        }
+    }
+
+    pub unsafe extern "C" fn memmem(
+        haystack: *const c_void,
+        haystacklen: usize,
+        needle: *const c_void,
+        needlelen: usize,
+    ) -> *mut c_void {
+        // SAFETY: libc contract.
+        let (h, n) = unsafe {
+            (
+                slice(haystack as *const u8, haystacklen),
+                slice(needle as *const u8, needlelen),
+            )
+        };
+        at(haystack, crate::mem::memmem(h, n))
+    }
+
+    pub unsafe extern "C" fn explicit_bzero(s: *mut c_void, n: usize) {
+        // SAFETY: libc contract.
+        unsafe { optimized_memset_explicit(s as *mut u8, 0, n) };
+    }
+
+    pub unsafe extern "C" fn memset_explicit(s: *mut c_void, c: c_int, n: usize) -> *mut c_void {
+        // SAFETY: libc contract.
+        unsafe { optimized_memset_explicit(s as *mut u8, c as u8, n) };
+        s
+    }
+
+    pub unsafe extern "C" fn bzero(s: *mut c_void, n: usize) {
+        // SAFETY: libc contract.
+        unsafe { fill(s as *mut u8, 0, n) };
+    }
+
+    pub unsafe extern "C" fn bcmp(s1: *const c_void, s2: *const c_void, n: usize) -> c_int {
+        // SAFETY: libc contract.
+        unsafe { memcmp_dispatched(s1 as *const u8, s2 as *const u8, n) }
+    }
+
+    pub unsafe extern "C" fn timingsafe_bcmp(b1: *const c_void, b2: *const c_void, n: usize) -> c_int {
+        // SAFETY: libc contract.
+        unsafe { optimized_timingsafe_bcmp(b1 as *const u8, b2 as *const u8, n) }
+    }
+
+    pub unsafe extern "C" fn timingsafe_memcmp(b1: *const c_void, b2: *const c_void, len: usize) -> c_int {
+        // SAFETY: libc contract.
+        unsafe { optimized_timingsafe_memcmp(b1 as *const u8, b2 as *const u8, len) }
+    }
+
+    pub unsafe extern "C" fn bcopy(src: *const c_void, dest: *mut c_void, n: usize) {
+        // SAFETY: libc contract.
+        unsafe { memmove_dispatched(dest as *mut u8, src as *const u8, n) };
+    }
+
+    pub unsafe extern "C" fn ffs(i: c_int) -> c_int {
+        crate::ffs::ffs(i)
+    }
+
+    pub unsafe extern "C" fn strlen(s: *const c_char) -> usize {
+        // SAFETY: libc contract.
+        unsafe { c_strlen(s as *const u8) }
+    }
+
+    pub unsafe extern "C" fn strnlen(s: *const c_char, maxlen: usize) -> usize {
+        // SAFETY: libc contract.
+        unsafe { c_strnlen(s as *const u8, maxlen) }
+    }
+
+    pub unsafe extern "C" fn strverscmp(s1: *const c_char, s2: *const c_char) -> c_int {
+        // SAFETY: libc contract.
+        unsafe { crate::str::strverscmp(cstr(s1), cstr(s2)) }
+    }
+
+    pub unsafe extern "C" fn strcpy(dest: *mut c_char, src: *const c_char) -> *mut c_char {
+        // SAFETY: libc contract.
+        unsafe { copy(dest as *mut u8, src as *const u8, c_strlen(src as *const u8) + 1) };
+        dest
+    }
+
+    pub unsafe extern "C" fn strncpy(dest: *mut c_char, src: *const c_char, n: usize) -> *mut c_char {
+        // SAFETY: libc contract.
+        unsafe { stpncpy(dest, src, n) };
+        dest
+    }
+
+    pub unsafe extern "C" fn stpcpy(dest: *mut c_char, src: *const c_char) -> *mut c_char {
+        // SAFETY: libc contract.
+        unsafe {
+            let len = c_strlen(src as *const u8);
+            copy(dest as *mut u8, src as *const u8, len + 1);
+            dest.add(len)
+        }
+    }
+
+    pub unsafe extern "C" fn stpncpy(dest: *mut c_char, src: *const c_char, n: usize) -> *mut c_char {
+        // SAFETY: libc contract.
+        unsafe {
+            let len = c_strnlen(src as *const u8, n);
+            copy(dest as *mut u8, src as *const u8, len);
+            fill(dest.add(len) as *mut u8, 0, n - len);
+            dest.add(len)
+        }
+    }
+
+    pub unsafe extern "C" fn strcat(dest: *mut c_char, src: *const c_char) -> *mut c_char {
+        // SAFETY: libc contract.
+        unsafe { strcpy(dest.add(c_strlen(dest as *const u8)), src) };
+        dest
+    }
+
+    pub unsafe extern "C" fn strncat(dest: *mut c_char, src: *const c_char, n: usize) -> *mut c_char {
+        // SAFETY: libc contract.
+        unsafe {
+            let end = dest.add(c_strlen(dest as *const u8));
+            let len = c_strnlen(src as *const u8, n);
+            copy(end as *mut u8, src as *const u8, len);
+            *end.add(len) = 0;
+        }
+        dest
+    }
+
+    pub unsafe extern "C" fn strcmp(s1: *const c_char, s2: *const c_char) -> c_int {
+        // SAFETY: libc contract.
+        unsafe { crate::raw::strcmp(s1, s2) }
+    }
+
+    pub unsafe extern "C" fn strncmp(s1: *const c_char, s2: *const c_char, n: usize) -> c_int {
+        // SAFETY: libc contract.
+        unsafe {
+            let len = c_strnlen(s1 as *const u8, n).min(c_strnlen(s2 as *const u8, n));
+            compare_bytes(s1 as *const u8, s2 as *const u8, (len + 1).min(n))
+        }
+    }
+
+    pub unsafe extern "C" fn strcoll(s1: *const c_char, s2: *const c_char) -> c_int {
+        // SAFETY: libc contract.
+        unsafe { strcmp(s1, s2) }
+    }
+
+    pub unsafe extern "C" fn strcasecmp(s1: *const c_char, s2: *const c_char) -> c_int {
+        // SAFETY: libc contract.
+        unsafe { casecmp(s1 as *const u8, s2 as *const u8, usize::MAX) }
+    }
+
+    pub unsafe extern "C" fn strncasecmp(s1: *const c_char, s2: *const c_char, n: usize) -> c_int {
+        // SAFETY: libc contract.
+        unsafe { casecmp(s1 as *const u8, s2 as *const u8, n) }
+    }
+
+    pub unsafe extern "C" fn strlcpy(dest: *mut c_char, src: *const c_char, size: usize) -> usize {
+        // SAFETY: libc contract.
+        unsafe { crate::str::strlcpy(slice_mut(dest as *mut u8, size), cstr(src)) }
+    }
+
+    pub unsafe extern "C" fn strlcat(dest: *mut c_char, src: *const c_char, size: usize) -> usize {
+        // SAFETY: libc contract.
+        unsafe { crate::str::strlcat(slice_mut(dest as *mut u8, size), cstr(src)) }
+    }
+
+    pub unsafe extern "C" fn strchr(s: *const c_char, c: c_int) -> *mut c_char {
+        // SAFETY: libc contract.
+        at(s, unsafe { crate::raw::strchr(s, c as u8) })
+    }
+
+    pub unsafe extern "C" fn strchrnul(s: *const c_char, c: c_int) -> *mut c_char {
+        // SAFETY: libc contract.
+        unsafe {
+            let len = c_strlen(s as *const u8);
+            let hit = memchr_dispatched(s as *const u8, len, c as u8);
+            s.add(hit.unwrap_or(len)) as *mut c_char
+        }
+    }
+
+    pub unsafe extern "C" fn strrchr(s: *const c_char, c: c_int) -> *mut c_char {
+        // SAFETY: libc contract.
+        at(s, unsafe { crate::raw::strrchr(s, c as u8) })
+    }
+
+    pub unsafe extern "C" fn strstr(haystack: *const c_char, needle: *const c_char) -> *mut c_char {
+        // SAFETY: libc contract.
+        at(haystack, unsafe { crate::search::strstr(cstr(haystack), cstr(needle)) })
+    }
+
+    pub unsafe extern "C" fn strcasestr(haystack: *const c_char, needle: *const c_char) -> *mut c_char {
+        // SAFETY: libc contract.
+        at(haystack, unsafe { crate::search::strcasestr(cstr(haystack), cstr(needle)) })
+    }
+
+    pub unsafe extern "C" fn strspn(s: *const c_char, accept: *const c_char) -> usize {
+        // SAFETY: libc contract.
+        unsafe { crate::search::strspn(cstr(s), cstr(accept)) }
+    }
+
+    pub unsafe extern "C" fn strcspn(s: *const c_char, reject: *const c_char) -> usize {
+        // SAFETY: libc contract.
+        unsafe { crate::raw::strcspn(s, reject) }
+    }
+
+    pub unsafe extern "C" fn strpbrk(s: *const c_char, accept: *const c_char) -> *mut c_char {
+        // SAFETY: libc contract.
+        at(s, unsafe { crate::search::strpbrk(cstr(s), cstr(accept)) })
+    }
+
+    pub unsafe extern "C" fn index(s: *const c_char, c: c_int) -> *mut c_char {
+        // SAFETY: libc contract.
+        unsafe { strchr(s, c) }
+    }
+
+    pub unsafe extern "C" fn rindex(s: *const c_char, c: c_int) -> *mut c_char {
+        // SAFETY: libc contract.
+        unsafe { strrchr(s, c) }
+    }
+
+    pub unsafe extern "C" fn strtok(s: *mut c_char, delim: *const c_char) -> *mut c_char {
+        let mut save = STRTOK_SAVE.load(Ordering::Relaxed);
+        // SAFETY: libc contract.
+        let token = unsafe { strtok_r(s, delim, &mut save) };
+        STRTOK_SAVE.store(save, Ordering::Relaxed);
+        token
+    }
+
+    pub unsafe extern "C" fn strtok_r(s: *mut c_char, delim: *const c_char, saveptr: *mut *mut c_char) -> *mut c_char {
+        // SAFETY: libc contract. `s` (or the saved position) is a writable
+        // NUL-terminated string and every offset stays within it.
+        unsafe {
+            let s = if s.is_null() { *saveptr } else { s };
+            if s.is_null() {
+                return s;
+            }
+            let delim = cstr(delim);
+            let start = s.add(crate::search::strspn(cstr(s), delim));
+            if *start == 0 {
+                *saveptr = start;
+                return ptr::null_mut();
+            }
+            let end = start.add(crate::search::strcspn(cstr(start), delim));
+            if *end == 0 {
+                *saveptr = end;
+            } else {
+                *end = 0;
+                *saveptr = end.add(1);
+            }
+            start
+        }
+    }
+
+    pub unsafe extern "C" fn strxfrm(dest: *mut c_char, src: *const c_char, n: usize) -> usize {
+        // SAFETY: libc contract.
+        unsafe { crate::strxfrm::strxfrm(slice_mut(dest as *mut u8, n), cstr(src)) }
+    }
+
+    pub unsafe extern "C" fn strdup(s: *const c_char) -> *mut c_char {
+        // SAFETY: libc contract.
+        unsafe { malloc_copy(s, c_strlen(s as *const u8)) }
+    }
+
+    pub unsafe extern "C" fn strndup(s: *const c_char, n: usize) -> *mut c_char {
+        // SAFETY: libc contract.
+        unsafe { malloc_copy(s, c_strnlen(s as *const u8, n)) }
+    }
+
+    pub unsafe extern "C" fn strerror(errnum: c_int) -> *mut c_char {
+        crate::strerror::strerror(errnum).as_ptr() as *mut c_char
+    }
+
+    #[cfg(not(target_env = "gnu"))]
+    pub unsafe extern "C" fn strerror_r(errnum: c_int, buf: *mut c_char, buflen: usize) -> c_int {
+        // SAFETY: libc contract.
+        unsafe { posix_strerror_r(errnum, buf, buflen) }
+    }
+
+    pub unsafe extern "C" fn wcslen(s: *const wchar_t) -> usize {
+        // SAFETY: libc contract.
+        unsafe { c_wcslen(s) }
+    }
+
+    pub unsafe extern "C" fn wcsnlen(s: *const wchar_t, maxlen: usize) -> usize {
+        // SAFETY: libc contract.
+        unsafe { c_wcsnlen(s, maxlen) }
+    }
+
+    pub unsafe extern "C" fn wcscpy(dest: *mut wchar_t, src: *const wchar_t) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        unsafe { wcpcpy(dest, src) };
+        dest
+    }
+
+    pub unsafe extern "C" fn wcsncpy(dest: *mut wchar_t, src: *const wchar_t, n: usize) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        unsafe { wcpncpy(dest, src, n) };
+        dest
+    }
+
+    pub unsafe extern "C" fn wcpcpy(dest: *mut wchar_t, src: *const wchar_t) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        unsafe {
+            let len = c_wcslen(src);
+            wmemcpy(dest, src, len + 1);
+            dest.add(len)
+        }
+    }
+
+    pub unsafe extern "C" fn wcpncpy(dest: *mut wchar_t, src: *const wchar_t, n: usize) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        unsafe {
+            let len = c_wcsnlen(src, n);
+            wmemcpy(dest, src, len);
+            wmemset(dest.add(len), 0, n - len);
+            dest.add(len)
+        }
+    }
+
+    pub unsafe extern "C" fn wcscat(dest: *mut wchar_t, src: *const wchar_t) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        unsafe { wcpcpy(dest.add(c_wcslen(dest)), src) };
+        dest
+    }
+
+    pub unsafe extern "C" fn wcsncat(dest: *mut wchar_t, src: *const wchar_t, n: usize) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        unsafe {
+            let end = dest.add(c_wcslen(dest));
+            let len = c_wcsnlen(src, n);
+            wmemcpy(end, src, len);
+            *end.add(len) = 0;
+        }
+        dest
+    }
+
+    pub unsafe extern "C" fn wcscmp(s1: *const wchar_t, s2: *const wchar_t) -> c_int {
+        // SAFETY: libc contract.
+        unsafe { crate::wide::wcscmp(wcs(s1), wcs(s2)) }
+    }
+
+    pub unsafe extern "C" fn wcsncmp(s1: *const wchar_t, s2: *const wchar_t, n: usize) -> c_int {
+        // SAFETY: libc contract.
+        unsafe {
+            let (a, b) = (slice(s1, c_wcsnlen(s1, n)), slice(s2, c_wcsnlen(s2, n)));
+            crate::wide::wcsncmp(a, b, n)
+        }
+    }
+
+    pub unsafe extern "C" fn wcscoll(s1: *const wchar_t, s2: *const wchar_t) -> c_int {
+        // SAFETY: libc contract.
+        unsafe { wcscmp(s1, s2) }
+    }
+
+    pub unsafe extern "C" fn wcschr(s: *const wchar_t, c: wchar_t) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        at(s, unsafe { crate::wide::wcschr(wcs(s), c) })
+    }
+
+    pub unsafe extern "C" fn wcsrchr(s: *const wchar_t, c: wchar_t) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        at(s, unsafe { crate::wide::wcsrchr(wcs(s), c) })
+    }
+
+    pub unsafe extern "C" fn wcsstr(haystack: *const wchar_t, needle: *const wchar_t) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        at(haystack, unsafe { crate::wide::wcsstr(wcs(haystack), wcs(needle)) })
+    }
+
+    pub unsafe extern "C" fn wcsspn(s: *const wchar_t, accept: *const wchar_t) -> usize {
+        // SAFETY: libc contract.
+        unsafe { crate::wide::wcsspn(wcs(s), wcs(accept)) }
+    }
+
+    pub unsafe extern "C" fn wcscspn(s: *const wchar_t, reject: *const wchar_t) -> usize {
+        // SAFETY: libc contract.
+        unsafe { crate::wide::wcscspn(wcs(s), wcs(reject)) }
+    }
+
+    pub unsafe extern "C" fn wcspbrk(s: *const wchar_t, accept: *const wchar_t) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        at(s, unsafe { crate::wide::wcspbrk(wcs(s), wcs(accept)) })
+    }
+
+    pub unsafe extern "C" fn wcscasecmp(s1: *const wchar_t, s2: *const wchar_t) -> c_int {
+        // SAFETY: libc contract.
+        unsafe { crate::wide::wcscasecmp(wcs(s1), wcs(s2)) }
+    }
+
+    pub unsafe extern "C" fn wcsncasecmp(s1: *const wchar_t, s2: *const wchar_t, n: usize) -> c_int {
+        // SAFETY: libc contract.
+        unsafe {
+            let (a, b) = (slice(s1, c_wcsnlen(s1, n)), slice(s2, c_wcsnlen(s2, n)));
+            crate::wide::wcsncasecmp(a, b, n)
+        }
+    }
+
+    pub unsafe extern "C" fn wcschrnul(s: *const wchar_t, c: wchar_t) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        unsafe { s.add(crate::wide::wcschrnul(wcs(s), c)) as *mut wchar_t }
+    }
+
+    pub unsafe extern "C" fn wcslcpy(dest: *mut wchar_t, src: *const wchar_t, size: usize) -> usize {
+        // SAFETY: libc contract.
+        unsafe { crate::wide::wcslcpy(slice_mut(dest, size), wcs(src)) }
+    }
+
+    pub unsafe extern "C" fn wcslcat(dest: *mut wchar_t, src: *const wchar_t, size: usize) -> usize {
+        // SAFETY: libc contract.
+        unsafe { crate::wide::wcslcat(slice_mut(dest, size), wcs(src)) }
+    }
+
+    pub unsafe extern "C" fn wcstok(s: *mut wchar_t, delim: *const wchar_t, saveptr: *mut *mut wchar_t) -> *mut wchar_t {
+        // SAFETY: libc contract. `s` (or the saved position) is a writable
+        // NUL-terminated wide string and every offset stays within it.
+        unsafe {
+            let s = if s.is_null() { *saveptr } else { s };
+            if s.is_null() {
+                return s;
+            }
+            let delim = wcs(delim);
+            let start = s.add(crate::wide::wcsspn(wcs(s), delim));
+            if *start == 0 {
+                *saveptr = start;
+                return ptr::null_mut();
+            }
+            let end = start.add(crate::wide::wcscspn(wcs(start), delim));
+            if *end == 0 {
+                *saveptr = end;
+            } else {
+                *end = 0;
+                *saveptr = end.add(1);
+            }
+            start
+        }
+    }
+
+    pub unsafe extern "C" fn wcsxfrm(dest: *mut wchar_t, src: *const wchar_t, n: usize) -> usize {
+        // SAFETY: libc contract.
+        unsafe { crate::wcsxfrm::wcsxfrm(slice_mut(dest, n), wcs(src)) }
+    }
+
+    pub unsafe extern "C" fn wcsdup(s: *const wchar_t) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        unsafe { malloc_copy(s, c_wcslen(s)) }
+    }
+
+    pub unsafe extern "C" fn wmemcpy(dest: *mut wchar_t, src: *const wchar_t, n: usize) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        unsafe { copy(dest as *mut u8, src as *const u8, n * core::mem::size_of::<wchar_t>()) };
+        dest
+    }
+
+    pub unsafe extern "C" fn wmempcpy(dest: *mut wchar_t, src: *const wchar_t, n: usize) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        unsafe { wmemcpy(dest, src, n).add(n) }
+    }
+
+    pub unsafe extern "C" fn wmemmove(dest: *mut wchar_t, src: *const wchar_t, n: usize) -> *mut wchar_t {
+        let bytes = n * core::mem::size_of::<wchar_t>();
+        // SAFETY: libc contract.
+        unsafe { memmove_dispatched(dest as *mut u8, src as *const u8, bytes) };
+        dest
+    }
+
+    pub unsafe extern "C" fn wmemset(s: *mut wchar_t, c: wchar_t, n: usize) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        crate::wmem::wmemset(unsafe { slice_mut(s, n) }, c);
+        s
+    }
+
+    pub unsafe extern "C" fn wmemcmp(s1: *const wchar_t, s2: *const wchar_t, n: usize) -> c_int {
+        // SAFETY: libc contract.
+        unsafe { crate::wmem::wmemcmp(slice(s1, n), slice(s2, n)) }
+    }
+
+    pub unsafe extern "C" fn wmemchr(s: *const wchar_t, c: wchar_t, n: usize) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        at(s, crate::wmem::wmemchr(unsafe { slice(s, n) }, c))
+    }
+
+    pub unsafe extern "C" fn wmemrchr(s: *const wchar_t, c: wchar_t, n: usize) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        at(s, crate::wmem::wmemrchr(unsafe { slice(s, n) }, c))
+    }
+
+    pub unsafe extern "C" fn faststrings_set_chk_fail_hook(hook: Option<ChkFailHook>) -> Option<ChkFailHook> {
+        set_chk_fail_hook(hook)
+    }
+
+    pub unsafe extern "C" fn __chk_fail() {
+        chk_fail(c"__chk_fail")
+    }
+
+    pub unsafe extern "C" fn __memcpy_chk(dest: *mut c_void, src: *const c_void, len: usize, destlen: usize) -> *mut c_void {
+        check(c"__memcpy_chk", len, destlen);
+        // SAFETY: libc contract.
+        unsafe { memcpy(dest, src, len) }
+    }
+
+    pub unsafe extern "C" fn __memmove_chk(dest: *mut c_void, src: *const c_void, len: usize, destlen: usize) -> *mut c_void {
+        check(c"__memmove_chk", len, destlen);
+        // SAFETY: libc contract.
+        unsafe { memmove(dest, src, len) }
+    }
+
+    pub unsafe extern "C" fn __mempcpy_chk(dest: *mut c_void, src: *const c_void, len: usize, destlen: usize) -> *mut c_void {
+        check(c"__mempcpy_chk", len, destlen);
+        // SAFETY: libc contract.
+        unsafe { (memcpy(dest, src, len) as *mut u8).add(len) as *mut c_void }
+    }
+
+    pub unsafe extern "C" fn __memset_chk(dest: *mut c_void, c: c_int, len: usize, destlen: usize) -> *mut c_void {
+        check(c"__memset_chk", len, destlen);
+        // SAFETY: libc contract.
+        unsafe { memset(dest, c, len) }
+    }
+
+    pub unsafe extern "C" fn __explicit_bzero_chk(s: *mut c_void, len: usize, destlen: usize) {
+        check(c"__explicit_bzero_chk", len, destlen);
+        // SAFETY: libc contract.
+        unsafe { explicit_bzero(s, len) }
+    }
+
+    pub unsafe extern "C" fn __strcpy_chk(dest: *mut c_char, src: *const c_char, destlen: usize) -> *mut c_char {
+        // SAFETY: libc contract.
+        unsafe {
+            check(c"__strcpy_chk", c_strlen(src as *const u8) + 1, destlen);
+            strcpy(dest, src)
+        }
+    }
+
+    pub unsafe extern "C" fn __stpcpy_chk(dest: *mut c_char, src: *const c_char, destlen: usize) -> *mut c_char {
+        // SAFETY: libc contract.
+        unsafe {
+            check(c"__stpcpy_chk", c_strlen(src as *const u8) + 1, destlen);
+            stpcpy(dest, src)
+        }
+    }
+
+    pub unsafe extern "C" fn __strncpy_chk(dest: *mut c_char, src: *const c_char, n: usize, destlen: usize) -> *mut c_char {
+        check(c"__strncpy_chk", n, destlen);
+        // SAFETY: libc contract.
+        unsafe { strncpy(dest, src, n) }
+    }
+
+    pub unsafe extern "C" fn __stpncpy_chk(dest: *mut c_char, src: *const c_char, n: usize, destlen: usize) -> *mut c_char {
+        check(c"__stpncpy_chk", n, destlen);
+        // SAFETY: libc contract.
+        unsafe { stpncpy(dest, src, n) }
+    }
+
+    pub unsafe extern "C" fn __strcat_chk(dest: *mut c_char, src: *const c_char, destlen: usize) -> *mut c_char {
+        // SAFETY: libc contract.
+        unsafe {
+            let len = c_strlen(dest as *const u8) + c_strlen(src as *const u8);
+            check(c"__strcat_chk", len + 1, destlen);
+            strcat(dest, src)
+        }
+    }
+
+    pub unsafe extern "C" fn __strncat_chk(dest: *mut c_char, src: *const c_char, n: usize, destlen: usize) -> *mut c_char {
+        // SAFETY: libc contract.
+        unsafe {
+            let len = c_strlen(dest as *const u8) + c_strnlen(src as *const u8, n);
+            check(c"__strncat_chk", len + 1, destlen);
+            strncat(dest, src, n)
+        }
+    }
+
+    pub unsafe extern "C" fn __strlcpy_chk(dest: *mut c_char, src: *const c_char, size: usize, destlen: usize) -> usize {
+        check(c"__strlcpy_chk", size, destlen);
+        // SAFETY: libc contract.
+        unsafe { strlcpy(dest, src, size) }
+    }
+
+    pub unsafe extern "C" fn __strlcat_chk(dest: *mut c_char, src: *const c_char, size: usize, destlen: usize) -> usize {
+        check(c"__strlcat_chk", size, destlen);
+        // SAFETY: libc contract.
+        unsafe { strlcat(dest, src, size) }
+    }
+
+    pub unsafe extern "C" fn __wcscpy_chk(dest: *mut wchar_t, src: *const wchar_t, n: usize) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        unsafe {
+            check(c"__wcscpy_chk", c_wcslen(src) + 1, n);
+            wcscpy(dest, src)
+        }
+    }
+
+    pub unsafe extern "C" fn __wcpcpy_chk(dest: *mut wchar_t, src: *const wchar_t, destlen: usize) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        unsafe {
+            check(c"__wcpcpy_chk", c_wcslen(src) + 1, destlen);
+            wcpcpy(dest, src)
+        }
+    }
+
+    pub unsafe extern "C" fn __wcsncpy_chk(dest: *mut wchar_t, src: *const wchar_t, n: usize, destlen: usize) -> *mut wchar_t {
+        check(c"__wcsncpy_chk", n, destlen);
+        // SAFETY: libc contract.
+        unsafe { wcsncpy(dest, src, n) }
+    }
+
+    pub unsafe extern "C" fn __wcpncpy_chk(dest: *mut wchar_t, src: *const wchar_t, n: usize, destlen: usize) -> *mut wchar_t {
+        check(c"__wcpncpy_chk", n, destlen);
+        // SAFETY: libc contract.
+        unsafe { wcpncpy(dest, src, n) }
+    }
+
+    pub unsafe extern "C" fn __wcscat_chk(dest: *mut wchar_t, src: *const wchar_t, destlen: usize) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        unsafe {
+            check(c"__wcscat_chk", c_wcslen(dest) + c_wcslen(src) + 1, destlen);
+            wcscat(dest, src)
+        }
+    }
+
+    pub unsafe extern "C" fn __wcsncat_chk(dest: *mut wchar_t, src: *const wchar_t, n: usize, destlen: usize) -> *mut wchar_t {
+        // SAFETY: libc contract.
+        unsafe {
+            check(c"__wcsncat_chk", c_wcslen(dest) + c_wcsnlen(src, n) + 1, destlen);
+            wcsncat(dest, src, n)
+        }
+    }
+
+    pub unsafe extern "C" fn __wcslcpy_chk(dest: *mut wchar_t, src: *const wchar_t, n: usize, destlen: usize) -> usize {
+        check(c"__wcslcpy_chk", n, destlen);
+        // SAFETY: libc contract.
+        unsafe { wcslcpy(dest, src, n) }
+    }
+
+    pub unsafe extern "C" fn __wcslcat_chk(dest: *mut wchar_t, src: *const wchar_t, n: usize, destlen: usize) -> usize {
+        check(c"__wcslcat_chk", n, destlen);
+        // SAFETY: libc contract.
+        unsafe { wcslcat(dest, src, n) }
+    }
+
+    pub unsafe extern "C" fn __wmemcpy_chk(s1: *mut wchar_t, s2: *const wchar_t, n: usize, ns1: usize) -> *mut wchar_t {
+        check(c"__wmemcpy_chk", n, ns1);
+        // SAFETY: libc contract.
+        unsafe { wmemcpy(s1, s2, n) }
+    }
+
+    pub unsafe extern "C" fn __wmemmove_chk(s1: *mut wchar_t, s2: *const wchar_t, n: usize, ns1: usize) -> *mut wchar_t {
+        check(c"__wmemmove_chk", n, ns1);
+        // SAFETY: libc contract.
+        unsafe { wmemmove(s1, s2, n) }
+    }
+
+    pub unsafe extern "C" fn __wmempcpy_chk(s1: *mut wchar_t, s2: *const wchar_t, n: usize, ns1: usize) -> *mut wchar_t {
+        check(c"__wmempcpy_chk", n, ns1);
+        // SAFETY: libc contract.
+        unsafe { wmempcpy(s1, s2, n) }
+    }
+
+    pub unsafe extern "C" fn __wmemset_chk(s: *mut wchar_t, c: wchar_t, n: usize, dstlen: usize) -> *mut wchar_t {
+        check(c"__wmemset_chk", n, dstlen);
+        // SAFETY: libc contract.
+        unsafe { wmemset(s, c, n) }
+    }
+}
+
+/// GNU `strerror_r`, the flavour glibc's `<string.h>` declares by default. It
+/// returns the message itself, and every message here is static, so `buf` is
+/// never written.
+#[cfg(target_env = "gnu")]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn strerror_r(
+    errnum: c_int,
+    _buf: *mut c_char,
+    _buflen: usize,
+) -> *mut c_char {
+    // SAFETY: libc contract.
+    unsafe { strerror(errnum) }
+}
+
+/// POSIX `strerror_r`, which glibc's `<string.h>` binds `strerror_r` to when
+/// `_GNU_SOURCE` is not defined.
+#[cfg(target_env = "gnu")]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn __xpg_strerror_r(errnum: c_int, buf: *mut c_char, buflen: usize) -> c_int {
+    forward_if_disabled!(__xpg_strerror_r(errnum: c_int, buf: *mut c_char, buflen: usize) -> c_int);
+    // SAFETY: libc contract.
+    unsafe { posix_strerror_r(errnum, buf, buflen) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wide(s: &str) -> Vec<wchar_t> {
+        s.chars().map(|c| c as wchar_t).chain([0]).collect()
+    }
+
+    fn offset<T>(base: *const T, p: *mut T) -> Option<usize> {
+        (!p.is_null()).then(|| (p as usize - base as usize) / core::mem::size_of::<T>())
+    }
+
+    // The first instrumented call on a thread allocates its histograms, and
+    // that allocation calls the exported `memcpy`.
+    #[cfg(feature = "instrument")]
+    #[test]
+    fn test_mem_exports_are_not_instrumented() {
+        use crate::instrument::{Routine, thread_snapshot};
+
+        std::thread::spawn(|| {
+            let src = [7u8; 100];
+            let mut dst = [0u8; 100];
+            unsafe {
+                crate::memcpy::optimized_memcpy_unified(dst.as_mut_ptr(), src.as_ptr(), 100);
+                memcpy(dst.as_mut_ptr().cast(), src.as_ptr().cast(), 100);
+                memset(dst.as_mut_ptr().cast(), 1, 100);
+            }
+            let snap = thread_snapshot();
+            assert_eq!(snap.get(Routine::Memcpy).calls(), 1);
+            assert_eq!(snap.get(Routine::Memset).calls(), 0);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_header_is_up_to_date() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/include/faststrings.h");
+        let generated = header();
+        if std::env::var_os("FASTSTRINGS_BLESS").is_some() {
+            std::fs::write(path, &generated).unwrap();
+        }
+        let on_disk = std::fs::read_to_string(path).unwrap_or_default();
+        assert!(
+            on_disk == generated,
+            "include/faststrings.h is stale; rerun with FASTSTRINGS_BLESS=1"
+        );
+    }
+
+    #[test]
+    fn test_prototypes_render_as_c() {
+        let find = |name| PROTOTYPES.iter().find(|p| p.name == name).unwrap().to_c();
+        assert_eq!(
+            find("memcpy"),
+            "void *memcpy(void *dest, const void *src, size_t n)"
+        );
+        assert_eq!(
+            find("strtok_r"),
+            "char *strtok_r(char *s, const char *delim, char **saveptr)"
+        );
+        assert_eq!(find("bzero"), "void bzero(void *s, size_t n)");
+        assert_eq!(
+            find("wcschr"),
+            "wchar_t *wcschr(const wchar_t *s, wchar_t c)"
+        );
+    }
+
+    #[test]
+    fn test_strlen_every_alignment() {
+        let mut buf = [b'x'; 96];
+        for start in 0..16 {
+            for len in 0..64 {
+                buf.fill(b'x');
+                buf[start + len] = 0;
+                let s = buf[start..].as_ptr() as *const c_char;
+                unsafe {
+                    assert_eq!(strlen(s), len);
+                    assert_eq!(strnlen(s, len / 2), len / 2);
+                    assert_eq!(strnlen(s, usize::MAX), len);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_comparisons_return_byte_differences() {
+        unsafe {
+            let c = |s: &'static [u8]| s.as_ptr() as *const c_char;
+            assert_eq!(strcmp(c(b"abc\0"), c(b"abc\0")), 0);
+            assert_eq!(strcmp(c(b"ab\0"), c(b"abc\0")), -(b'c' as c_int));
+            assert_eq!(strcmp(c(b"ab\xff\0"), c(b"ab\x01\0")), 0xfe);
+            assert_eq!(strncmp(c(b"abcx\0"), c(b"abcy\0"), 3), 0);
+            assert_eq!(strncmp(c(b"abcx\0"), c(b"abcy\0"), 4), -1);
+            assert_eq!(strncmp(c(b"a\0"), c(b"b\0"), 0), 0);
+            assert_eq!(strcasecmp(c(b"HeLLo\0"), c(b"hello\0")), 0);
+            assert_eq!(strcasecmp(c(b"a\0"), c(b"B\0")), -1);
+            assert_eq!(strncasecmp(c(b"ABx\0"), c(b"aby\0"), 2), 0);
+            let (x, y) = ([1u8, 2, 3], [1u8, 2, 9]);
+            assert_eq!(memcmp(x.as_ptr().cast(), y.as_ptr().cast(), 3), -6);
+            assert_eq!(bcmp(x.as_ptr().cast(), y.as_ptr().cast(), 2), 0);
+            // The constant-time pair returns OpenBSD's -1/0/1, not differences.
+            assert_eq!(timingsafe_bcmp(x.as_ptr().cast(), y.as_ptr().cast(), 3), 1);
+            assert_eq!(
+                timingsafe_memcmp(x.as_ptr().cast(), y.as_ptr().cast(), 3),
+                -1
+            );
+            assert_eq!(
+                timingsafe_memcmp(y.as_ptr().cast(), x.as_ptr().cast(), 2),
+                0
+            );
+        }
+    }
+
+    #[test]
+    fn test_copies_and_returned_pointers() {
+        unsafe {
+            let mut buf = [0x55u8; 16];
+            let d = buf.as_mut_ptr() as *mut c_char;
+            let src = c"hello".as_ptr();
+
+            assert_eq!(strcpy(d, src), d);
+            assert_eq!(&buf[..7], b"hello\0\x55");
+            assert_eq!(stpcpy(d, src), d.add(5));
+            assert_eq!(strcat(d, c", you".as_ptr()), d);
+            assert_eq!(&buf[..11], b"hello, you\0");
+
+            buf.fill(0x55);
+            assert_eq!(strncpy(d, src, 8), d);
+            assert_eq!(&buf[..9], b"hello\0\0\0\x55");
+            assert_eq!(stpncpy(d, src, 3), d.add(3));
+            assert_eq!(stpncpy(d, src, 6), d.add(5));
+            assert_eq!(strncat(d, c"worlds".as_ptr(), 5), d);
+            assert_eq!(&buf[..11], b"helloworld\0");
+
+            assert_eq!(strlcpy(d, src, 3), 5);
+            assert_eq!(&buf[..3], b"he\0");
+            assert_eq!(strlcat(d, src, 6), 7);
+            assert_eq!(&buf[..6], b"hehel\0");
+
+            buf.fill(0);
+            let stop = memccpy(d.cast(), b"ab,cd".as_ptr().cast(), b',' as c_int, 5);
+            assert_eq!(stop, d.add(3).cast());
+            assert_eq!(&buf[..4], b"ab,\0");
+            assert!(memccpy(d.cast(), b"abc".as_ptr().cast(), 0, 3).is_null());
+
+            let dup = strndup(src, 3);
+            assert_eq!(cstr(dup), b"hel\0");
+            let dup = strdup(src);
+            assert_eq!(cstr(dup), b"hello\0");
+
+            buf.fill(0x55);
+            assert_eq!(memset_explicit(d.cast(), 0xaa, 3), d.cast());
+            explicit_bzero(d.add(1).cast(), 1);
+            assert_eq!(&buf[..4], b"\xaa\0\xaa\x55");
+        }
+    }
+
+    #[test]
+    fn test_searches() {
+        unsafe {
+            let s = b"a,b;c,\0";
+            let p = s.as_ptr() as *const c_char;
+            let c = |s: &'static [u8]| s.as_ptr() as *const c_char;
+            assert_eq!(offset(p, strchr(p, b',' as c_int)), Some(1));
+            assert_eq!(offset(p, strchr(p, 0)), Some(6));
+            assert_eq!(offset(p, strchr(p, b'x' as c_int)), None);
+            assert_eq!(offset(p, strchrnul(p, b'x' as c_int)), Some(6));
+            assert_eq!(offset(p, strrchr(p, b',' as c_int)), Some(5));
+            assert_eq!(offset(p, rindex(p, 0)), Some(6));
+            assert_eq!(offset(p, strstr(p, c(b";c\0"))), Some(3));
+            assert_eq!(offset(p, strstr(p, c(b"\0"))), Some(0));
+            assert_eq!(offset(p, strcasestr(p, c(b"B;C\0"))), Some(2));
+            assert_eq!(strspn(p, c(b"a,\0")), 2);
+            assert_eq!(strcspn(p, c(b";\0")), 3);
+            assert_eq!(offset(p, strpbrk(p, c(b";c\0"))), Some(3));
+            let v = p as *const c_void;
+            assert_eq!(offset(v, memrchr(v, b'a' as c_int, 6)), Some(0));
+            assert_eq!(offset(v, memmem(v, 6, c(b"b;").cast(), 2)), Some(2));
+            assert_eq!(ffs(0x50), 5);
+        }
+    }
+
+    #[test]
+    fn test_strtok_sequences() {
+        unsafe {
+            let mut buf = *b",,aa,b;;cc\0";
+            let delim = c",;".as_ptr();
+            let mut tokens = Vec::new();
+            let mut t = strtok(buf.as_mut_ptr().cast(), delim);
+            while !t.is_null() {
+                tokens.push(cstr(t).to_vec());
+                t = strtok(ptr::null_mut(), delim);
+            }
+            assert_eq!(tokens, [&b"aa\0"[..], b"b\0", b"cc\0"]);
+            assert!(strtok(ptr::null_mut(), delim).is_null());
+
+            let mut w = wide("x y");
+            let sep = wide(" ");
+            let mut save = ptr::null_mut();
+            let first = wcstok(w.as_mut_ptr(), sep.as_ptr(), &mut save);
+            assert_eq!(wcs(first), &wide("x")[..]);
+            let second = wcstok(ptr::null_mut(), sep.as_ptr(), &mut save);
+            assert_eq!(wcs(second), &wide("y")[..]);
+            assert!(wcstok(ptr::null_mut(), sep.as_ptr(), &mut save).is_null());
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        unsafe {
+            assert_eq!(cstr(strerror(2)), b"No such file or directory\0");
+            let mut buf = [0u8; 8];
+            let r = posix_strerror_r(2, buf.as_mut_ptr().cast(), buf.len());
+            assert_eq!(r, crate::strerror_r::ERANGE);
+            assert_eq!(&buf, b"No such\0");
+            #[cfg(target_env = "gnu")]
+            {
+                assert_eq!(strerror_r(2, buf.as_mut_ptr().cast(), 8), strerror(2));
+                assert_eq!(__xpg_strerror_r(2, buf.as_mut_ptr().cast(), 8), 34);
+            }
+        }
+    }
+
+    #[test]
+    fn test_wide_routines() {
+        unsafe {
+            let hello = wide("hello");
+            let h = hello.as_ptr();
+            assert_eq!(wcslen(h), 5);
+            assert_eq!(wcsnlen(h, 3), 3);
+
+            let mut buf = [7 as wchar_t; 12];
+            let d = buf.as_mut_ptr();
+            assert_eq!(wcpcpy(d, h), d.add(5));
+            assert_eq!(wcscat(d, wide("!").as_ptr()), d);
+            assert_eq!(&buf[..7], &wide("hello!")[..]);
+            assert_eq!(wcpncpy(d, h, 7), d.add(5));
+            assert_eq!(&buf[..8], &[104, 101, 108, 108, 111, 0, 0, 7]);
+            assert_eq!(wcsncat(d, h, 2), d);
+            assert_eq!(&buf[..8], &wide("hellohe")[..]);
+
+            assert_eq!(wcscmp(h, wide("help").as_ptr()), -1);
+            assert_eq!(wcsncmp(h, wide("help").as_ptr(), 3), 0);
+            assert_eq!(wcscasecmp(h, wide("HELLO").as_ptr()), 0);
+            assert_eq!(offset(h, wcschr(h, 'l' as wchar_t)), Some(2));
+            assert_eq!(offset(h, wcsrchr(h, 'l' as wchar_t)), Some(3));
+            assert_eq!(offset(h, wcschr(h, 0)), Some(5));
+            assert_eq!(offset(h, wcschrnul(h, 'z' as wchar_t)), Some(5));
+            assert_eq!(offset(h, wcsstr(h, wide("llo").as_ptr())), Some(2));
+            assert_eq!(wcsspn(h, wide("eh").as_ptr()), 2);
+            assert_eq!(wcscspn(h, wide("o").as_ptr()), 4);
+
+            assert_eq!(wmemset(d, 3, 4), d);
+            assert_eq!(&buf[..5], &[3, 3, 3, 3, 111]);
+            assert_eq!(wmempcpy(d, h, 5), d.add(5));
+            assert_eq!(wmemcmp(d, h, 5), 0);
+            assert_eq!(offset(h, wmemchr(h, 'l' as wchar_t, 5)), Some(2));
+            assert_eq!(offset(h, wmemrchr(h, 'l' as wchar_t, 5)), Some(3));
+
+            let dup = wcsdup(h);
+            assert_eq!(wcs(dup), &hello[..]);
+        }
+    }
+
+    #[test]
+    fn test_chk_routines_within_bounds() {
+        unsafe {
+            let mut buf = [b'x'; 8];
+            let d = buf.as_mut_ptr() as *mut c_char;
+            assert_eq!(__strcpy_chk(d, c"hello".as_ptr(), 6), d);
+            assert_eq!(__strcat_chk(d, c"!!".as_ptr(), 8), d);
+            assert_eq!(&buf, b"hello!!\0");
+            assert_eq!(__stpcpy_chk(d, c"abc".as_ptr(), 4), d.add(3));
+            assert_eq!(__strncat_chk(d, c"defgh".as_ptr(), 2, 6), d);
+            assert_eq!(cstr(d), b"abcde\0");
+            assert_eq!(__stpncpy_chk(d, c"ab".as_ptr(), 4, 4), d.add(2));
+            assert_eq!(&buf[..4], b"ab\0\0");
+            assert_eq!(__strlcpy_chk(d, c"longer".as_ptr(), 4, 8), 6);
+            assert_eq!(cstr(d), b"lon\0");
+
+            let v = buf.as_mut_ptr() as *mut c_void;
+            assert_eq!(__memset_chk(v, b'z' as c_int, 8, 8), v);
+            assert_eq!(
+                __mempcpy_chk(v, b"12".as_ptr().cast(), 2, 8),
+                d.add(2).cast()
+            );
+            assert_eq!(&buf, b"12zzzzzz");
+            // Unknown object sizes arrive as `(size_t)-1`.
+            assert_eq!(__memmove_chk(v, d.add(1).cast(), 7, usize::MAX), v);
+            assert_eq!(&buf, b"2zzzzzzz");
+
+            let hello = wide("hello");
+            let mut wbuf = [7 as wchar_t; 8];
+            let w = wbuf.as_mut_ptr();
+            assert_eq!(__wcscpy_chk(w, hello.as_ptr(), 6), w);
+            assert_eq!(__wcsncat_chk(w, hello.as_ptr(), 2, 8), w);
+            assert_eq!(&wbuf, &wide("hellohe")[..]);
+            assert_eq!(__wmemset_chk(w, 1, 8, 8), w);
+            assert_eq!(__wmempcpy_chk(w, hello.as_ptr(), 3, 3), w.add(3));
+            assert_eq!(&wbuf[..4], &[104, 101, 108, 1]);
+        }
+    }
+
+    unsafe extern "C" fn exit_hook(routine: *const c_char) {
+        // SAFETY: The hook receives a NUL-terminated routine name.
+        let routine = unsafe { CStr::from_ptr(routine) };
+        eprintln!("hook: {}", routine.to_string_lossy());
+        std::process::exit(3);
+    }
+
+    /// Runs this test in a child process with `FASTSTRINGS_CHK_CHILD=mode`.
+    #[cfg(unix)]
+    fn overflow_in_child(mode: &str) -> std::process::Output {
+        std::process::Command::new(std::env::current_exe().unwrap())
+            .args([
+                "--exact",
+                "capi::tests::test_chk_overflow_aborts",
+                "--nocapture",
+            ])
+            .env("FASTSTRINGS_CHK_CHILD", mode)
+            .output()
+            .unwrap()
+    }
+
+    #[test]
+    #[cfg(unix)]
+    #[cfg_attr(miri, ignore = "spawns a process")]
+    fn test_chk_overflow_aborts() {
+        use std::os::unix::process::ExitStatusExt;
+
+        if let Some(mode) = std::env::var_os("FASTSTRINGS_CHK_CHILD") {
+            if mode == "hook" {
+                set_chk_fail_hook(Some(exit_hook));
+            }
+            let mut buf = [0u8; 4];
+            unsafe { __strcpy_chk(buf.as_mut_ptr().cast(), c"four".as_ptr(), buf.len()) };
+            unreachable!("__strcpy_chk returned");
+        }
+
+        let out = overflow_in_child("default");
+        assert_eq!(out.status.signal(), Some(6), "{out:?}");
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        assert!(
+            stderr.contains("*** buffer overflow detected ***: __strcpy_chk: terminated"),
+            "{stderr}"
+        );
+
+        let out = overflow_in_child("hook");
+        assert_eq!(out.status.code(), Some(3), "{out:?}");
+        assert!(String::from_utf8_lossy(&out.stderr).contains("hook: __strcpy_chk"));
+    }
+}
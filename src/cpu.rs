@@ -0,0 +1,545 @@
+//! Runtime CPU feature detection.
+//!
+//! The SIMD kernels in this crate are compiled with `#[target_feature]` so a
+//! single binary carries every code path. Which one runs is decided here: the
+//! host CPU is probed once, the result is cached in an atomic, and the
+//! per-routine dispatch slots (see `dispatch`) consult it the first time each
+//! routine is called.
+//!
+//! In portable mode (the `portable` feature, or any build under Miri) the CPU
+//! is never probed: every routine runs its word-at-a-time kernel, which uses
+//! only ordinary in-bounds loads and stores, so the crate can be checked with
+//! `cargo miri test`.
+//!
+//! The same probe also reads the fast-string flags and cache sizes that decide
+//! where large copies and fills switch to `rep movsb` or non-temporal stores
+//! (see [`Thresholds`]).
+
+use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+/// Instruction-set tier used to select kernels.
+///
+/// Levels are ordered: every level implies support for the ones below it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IsaLevel {
+    /// Portable word-at-a-time (SWAR) kernels; the only level off x86_64.
+    Scalar,
+    /// SSE2 only (the x86_64 baseline).
+    Sse2,
+    /// SSE4.2 string instructions (`pcmpistri`/`pcmpestri`, Nehalem and later).
+    Sse42,
+    /// AVX2 (Haswell and later, Zen and later).
+    Avx2,
+    /// AVX-512 F/BW/VL (Skylake-SP and later, Zen 4 and later).
+    Avx512,
+}
+
+impl IsaLevel {
+    /// Every level, lowest first.
+    pub const ALL: [IsaLevel; 5] = [
+        IsaLevel::Scalar,
+        IsaLevel::Sse2,
+        IsaLevel::Sse42,
+        IsaLevel::Avx2,
+        IsaLevel::Avx512,
+    ];
+
+    /// Short lowercase name, e.g. `"avx2"`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            IsaLevel::Scalar => "scalar",
+            IsaLevel::Sse2 => "sse2",
+            IsaLevel::Sse42 => "sse4.2",
+            IsaLevel::Avx2 => "avx2",
+            IsaLevel::Avx512 => "avx512",
+        }
+    }
+
+    const fn to_bits(self) -> u8 {
+        match self {
+            IsaLevel::Scalar => 1,
+            IsaLevel::Sse2 => 2,
+            IsaLevel::Sse42 => 3,
+            IsaLevel::Avx2 => 4,
+            IsaLevel::Avx512 => 5,
+        }
+    }
+
+    const fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            1 => Some(IsaLevel::Scalar),
+            2 => Some(IsaLevel::Sse2),
+            3 => Some(IsaLevel::Sse42),
+            4 => Some(IsaLevel::Avx2),
+            5 => Some(IsaLevel::Avx512),
+            _ => None,
+        }
+    }
+}
+
+/// `true` in portable mode, where [`isa_level`] is always [`IsaLevel::Scalar`]
+/// and the inline SIMD fast paths for short inputs are skipped.
+pub const PORTABLE: bool = cfg!(any(miri, feature = "portable"));
+
+const LEVEL_UNINIT: u8 = 0;
+
+static LEVEL: AtomicU8 = AtomicU8::new(LEVEL_UNINIT);
+
+/// Returns the highest instruction-set level supported by the running CPU.
+///
+/// Detection runs on the first call; later calls are a single relaxed load.
+#[inline]
+pub fn isa_level() -> IsaLevel {
+    match IsaLevel::from_bits(LEVEL.load(Ordering::Relaxed)) {
+        Some(level) => level,
+        None => {
+            let level = available_isa_level();
+            // Lose gracefully to a concurrent `set_max_isa_level`.
+            match LEVEL.compare_exchange(
+                LEVEL_UNINIT,
+                level.to_bits(),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => level,
+                Err(bits) => IsaLevel::from_bits(bits).unwrap_or(level),
+            }
+        }
+    }
+}
+
+/// Caps the level used for kernel selection at `max` and returns the level
+/// now in effect (never above what the CPU supports).
+///
+/// This is the opt-out for CPUs that drop their clock on wide vector
+/// instructions: `set_max_isa_level(IsaLevel::Avx2)` keeps every routine on
+/// its AVX2 kernel even when AVX-512 is available.
+///
+/// Each routine selects its kernel on first use and keeps it, so call this
+/// before the first call into the crate. Routines that have already run are
+/// not re-dispatched. An `isa` cap from `FASTSTRINGS_TUNABLES` (see
+/// [`crate::tunables`]) still applies.
+pub fn set_max_isa_level(max: IsaLevel) -> IsaLevel {
+    let level = available_isa_level().min(max);
+    LEVEL.store(level.to_bits(), Ordering::Relaxed);
+    level
+}
+
+/// Iterates over every level the running CPU supports, lowest first.
+pub fn supported_levels() -> impl Iterator<Item = IsaLevel> {
+    let max = isa_level();
+    IsaLevel::ALL.into_iter().filter(move |&level| level <= max)
+}
+
+/// Returns `true` when AVX2 kernels may be used.
+#[inline]
+pub fn has_avx2() -> bool {
+    isa_level() >= IsaLevel::Avx2
+}
+
+/// Returns `true` when AVX-512 (F/BW/VL) kernels may be used.
+#[inline]
+pub fn has_avx512() -> bool {
+    isa_level() >= IsaLevel::Avx512
+}
+
+/// The detected level, capped by the `isa` tunable.
+fn available_isa_level() -> IsaLevel {
+    let level = detect_isa_level();
+    #[cfg(feature = "std")]
+    if let Some(cap) = crate::tunables::get().isa {
+        return level.min(cap);
+    }
+    level
+}
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn detect_isa_level() -> IsaLevel {
+    use core::arch::x86_64::{__cpuid, __cpuid_count};
+
+    if PORTABLE {
+        return IsaLevel::Scalar;
+    }
+
+    // Read CPUID directly rather than through `is_x86_feature_detected!`,
+    // which needs `std`. AVX2 is only usable when the OS saves the upper YMM
+    // state (XCR0 bits 1-2; AVX-disabled VMs clear them) and AVX-512 further
+    // needs the opmask and ZMM state (bits 5-7). Each level also requires the
+    // ones below it, which every shipping CPU satisfies but some hypervisors'
+    // feature masks do not.
+    let max_leaf = __cpuid(0).eax;
+    let leaf1 = __cpuid(1);
+    if leaf1.ecx & (1 << 20) == 0 {
+        return IsaLevel::Sse2;
+    }
+
+    let osxsave = leaf1.ecx & (1 << 27) != 0;
+    let avx = leaf1.ecx & (1 << 28) != 0;
+    // SAFETY: OSXSAVE means the OS enabled XGETBV.
+    let xcr0 = if osxsave { unsafe { xcr0() } } else { 0 };
+    let leaf7_ebx = if max_leaf >= 7 {
+        __cpuid_count(7, 0).ebx
+    } else {
+        0
+    };
+
+    let avx2 = avx && xcr0 & 0b110 == 0b110 && leaf7_ebx & (1 << 5) != 0;
+    if !avx2 {
+        return IsaLevel::Sse42;
+    }
+
+    // AVX-512 F (bit 16), BW (bit 30) and VL (bit 31).
+    let avx512_bits = (1 << 16) | (1 << 30) | (1 << 31);
+    if xcr0 & 0xe6 == 0xe6 && leaf7_ebx & avx512_bits == avx512_bits {
+        IsaLevel::Avx512
+    } else {
+        IsaLevel::Avx2
+    }
+}
+
+/// Reads XCR0, the OS-enabled extended state.
+///
+/// # Safety
+///
+/// CPUID must report OSXSAVE.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "xsave")]
+unsafe fn xcr0() -> u64 {
+    // SAFETY: Caller guarantees XGETBV is enabled.
+    unsafe { core::arch::x86_64::_xgetbv(0) }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+pub(crate) fn detect_isa_level() -> IsaLevel {
+    IsaLevel::Scalar
+}
+
+/// Fast-string flags and cache sizes that steer large copies and fills.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryFeatures {
+    /// Enhanced `rep movsb`/`rep stosb` (ERMS).
+    pub erms: bool,
+    /// Fast short `rep movsb` (FSRM, Ice Lake and Zen 3 and later).
+    pub fsrm: bool,
+    /// Per-core L2 data cache size in bytes, or 0 if unknown.
+    pub l2_size: usize,
+    /// L3 size in bytes, or 0 if unknown. This is the whole cache shared by
+    /// the core's die (or CCX on AMD), not a per-core share.
+    pub l3_size: usize,
+}
+
+/// Probes the running CPU's fast-string flags and cache sizes.
+///
+/// This executes `cpuid` on every call; the thresholds derived from it are
+/// cached separately (see [`thresholds`]). Off x86_64 everything is reported
+/// as unknown.
+pub fn memory_features() -> MemoryFeatures {
+    detect_memory_features()
+}
+
+/// Size cutoffs for the large-copy and large-fill strategies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Thresholds {
+    /// Overlapping `memmove` calls of at least this many bytes use
+    /// `rep movsb`; backward ones only from [`REP_MOVSB_BACKWARD_MIN`] up.
+    /// `usize::MAX` disables it.
+    pub rep_movsb: usize,
+    /// `memcpy` calls of at least this many bytes use non-temporal stores.
+    pub memcpy_nt: usize,
+    /// `memset` calls of at least this many bytes use non-temporal stores.
+    pub memset_nt: usize,
+}
+
+impl Thresholds {
+    /// Cutoffs used when the cache sizes cannot be read.
+    pub const DEFAULT: Thresholds = Thresholds {
+        rep_movsb: 1024 * 1024,
+        memcpy_nt: 16 * 1024 * 1024,
+        memset_nt: 2 * 1024 * 1024,
+    };
+
+    /// Derives the cutoffs for a machine with the given features.
+    pub fn from_features(features: &MemoryFeatures) -> Thresholds {
+        let mut t = Thresholds::DEFAULT;
+
+        // On current cores `rep movsb` loses to the vector loops until the
+        // working set spills out of L2, and without ERMS it never wins. FSRM
+        // makes short forward strings fast too, so like glibc switch at
+        // 2112 bytes there. Backward `rep movsb` (DF=1) gets no fast-string
+        // help at all and keeps a floor, see `REP_MOVSB_BACKWARD_MIN`.
+        if !features.erms {
+            t.rep_movsb = usize::MAX;
+        } else if features.fsrm {
+            t.rep_movsb = 2112;
+        } else if features.l2_size != 0 {
+            t.rep_movsb = features.l2_size.clamp(256 * 1024, 4 * 1024 * 1024);
+        }
+
+        // Streaming stores pay off once the destination would evict a large
+        // share of the L3 (glibc uses a quarter of it for memcpy). A fill only
+        // writes, so it evicts twice as fast per byte and streams earlier;
+        // never stream anything that still fits in L2.
+        if features.l3_size != 0 {
+            let floor = features.l2_size.max(256 * 1024);
+            t.memcpy_nt = (features.l3_size / 4).max(floor);
+            t.memset_nt = (features.l3_size / 16).max(floor);
+        }
+
+        t
+    }
+}
+
+/// Smallest overlapping backward move that uses `rep movsb`, whatever
+/// [`Thresholds::rep_movsb`] says: below it the vector loops always win.
+pub const REP_MOVSB_BACKWARD_MIN: usize = 256 * 1024;
+
+// Zero means "not derived yet"; every derived cutoff is non-zero.
+static REP_MOVSB_THRESHOLD: AtomicUsize = AtomicUsize::new(0);
+static MEMCPY_NT_THRESHOLD: AtomicUsize = AtomicUsize::new(0);
+static MEMSET_NT_THRESHOLD: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the cutoffs derived for the running CPU.
+///
+/// They are computed from [`memory_features`] on first use, adjusted by any
+/// `FASTSTRINGS_TUNABLES` overrides (see [`crate::tunables`]), and cached.
+pub fn thresholds() -> Thresholds {
+    Thresholds {
+        rep_movsb: cached_threshold(&REP_MOVSB_THRESHOLD),
+        memcpy_nt: cached_threshold(&MEMCPY_NT_THRESHOLD),
+        memset_nt: cached_threshold(&MEMSET_NT_THRESHOLD),
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline]
+pub(crate) fn rep_movsb_threshold() -> usize {
+    cached_threshold(&REP_MOVSB_THRESHOLD)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline]
+pub(crate) fn memcpy_nt_threshold() -> usize {
+    cached_threshold(&MEMCPY_NT_THRESHOLD)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline]
+pub(crate) fn memset_nt_threshold() -> usize {
+    cached_threshold(&MEMSET_NT_THRESHOLD)
+}
+
+#[inline(always)]
+fn cached_threshold(slot: &AtomicUsize) -> usize {
+    match slot.load(Ordering::Relaxed) {
+        0 => {
+            init_thresholds();
+            slot.load(Ordering::Relaxed)
+        }
+        value => value,
+    }
+}
+
+#[cold]
+fn init_thresholds() {
+    // Racing initializers compute identical values, so plain stores suffice.
+    #[cfg_attr(not(feature = "std"), allow(unused_mut))]
+    let mut t = Thresholds::from_features(&detect_memory_features());
+    #[cfg(feature = "std")]
+    crate::tunables::get().apply(&mut t);
+    REP_MOVSB_THRESHOLD.store(t.rep_movsb, Ordering::Relaxed);
+    MEMCPY_NT_THRESHOLD.store(t.memcpy_nt, Ordering::Relaxed);
+    MEMSET_NT_THRESHOLD.store(t.memset_nt, Ordering::Relaxed);
+}
+
+#[cfg(target_arch = "x86_64")]
+fn detect_memory_features() -> MemoryFeatures {
+    use core::arch::x86_64::{__cpuid, __cpuid_count};
+
+    let mut features = MemoryFeatures::default();
+    if PORTABLE {
+        return features;
+    }
+
+    let leaf0 = __cpuid(0);
+    let max_leaf = leaf0.eax;
+
+    if max_leaf >= 7 {
+        let leaf7 = __cpuid_count(7, 0);
+        features.erms = leaf7.ebx & (1 << 9) != 0;
+        features.fsrm = leaf7.edx & (1 << 4) != 0;
+    }
+
+    let vendor = (leaf0.ebx, leaf0.edx, leaf0.ecx);
+    let amd_like =
+        vendor == vendor_words(b"AuthenticAMD") || vendor == vendor_words(b"HygonGenuine");
+    let max_ext_leaf = __cpuid(0x8000_0000).eax;
+
+    // Intel describes its caches in leaf 4; AMD uses the same layout in
+    // 0x8000_001D when topology extensions are present.
+    let cache_leaf = if amd_like {
+        let topoext = max_ext_leaf >= 0x8000_0001 && __cpuid(0x8000_0001).ecx & (1 << 22) != 0;
+        (topoext && max_ext_leaf >= 0x8000_001D).then_some(0x8000_001D)
+    } else {
+        (max_leaf >= 4).then_some(4)
+    };
+
+    if let Some(leaf) = cache_leaf {
+        for subleaf in 0..16 {
+            let r = __cpuid_count(leaf, subleaf);
+            let kind = r.eax & 0x1f;
+            if kind == 0 {
+                break;
+            }
+            // Skip instruction caches; data (1) and unified (3) both count.
+            if kind == 2 {
+                continue;
+            }
+            let ways = ((r.ebx >> 22) & 0x3ff) as usize + 1;
+            let partitions = ((r.ebx >> 12) & 0x3ff) as usize + 1;
+            let line = (r.ebx & 0xfff) as usize + 1;
+            let sets = r.ecx as usize + 1;
+            let size = ways * partitions * line * sets;
+            match (r.eax >> 5) & 0x7 {
+                2 => features.l2_size = size,
+                3 => features.l3_size = size,
+                _ => {}
+            }
+        }
+    } else if amd_like && max_ext_leaf >= 0x8000_0006 {
+        // Legacy AMD leaf: L2 in KiB, L3 in 512 KiB units.
+        let r = __cpuid(0x8000_0006);
+        features.l2_size = (r.ecx >> 16) as usize * 1024;
+        features.l3_size = (r.edx >> 18) as usize * 512 * 1024;
+    }
+
+    features
+}
+
+#[cfg(target_arch = "x86_64")]
+const fn vendor_words(name: &[u8; 12]) -> (u32, u32, u32) {
+    (
+        u32::from_le_bytes([name[0], name[1], name[2], name[3]]),
+        u32::from_le_bytes([name[4], name[5], name[6], name[7]]),
+        u32::from_le_bytes([name[8], name[9], name[10], name[11]]),
+    )
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn detect_memory_features() -> MemoryFeatures {
+    MemoryFeatures::default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_isa_level_is_cached_and_stable() {
+        let first = isa_level();
+        assert_eq!(isa_level(), first);
+        assert_eq!(has_avx2(), first >= IsaLevel::Avx2);
+        assert_eq!(has_avx512(), first >= IsaLevel::Avx512);
+    }
+
+    #[cfg(all(target_arch = "x86_64", not(miri), not(feature = "portable")))]
+    #[test]
+    fn test_isa_level_matches_std_detection() {
+        assert_eq!(
+            detect_isa_level() >= IsaLevel::Sse42,
+            std::is_x86_feature_detected!("sse4.2")
+        );
+        assert_eq!(
+            detect_isa_level() >= IsaLevel::Avx2,
+            std::is_x86_feature_detected!("avx2")
+        );
+        assert_eq!(
+            detect_isa_level() >= IsaLevel::Avx512,
+            std::is_x86_feature_detected!("avx512bw") && std::is_x86_feature_detected!("avx512vl")
+        );
+    }
+
+    #[test]
+    fn test_supported_levels_end_at_detected_level() {
+        let levels: std::vec::Vec<_> = supported_levels().collect();
+        assert_eq!(levels.first(), Some(&IsaLevel::Scalar));
+        assert_eq!(levels.last(), Some(&isa_level()));
+    }
+
+    #[cfg(any(miri, feature = "portable"))]
+    #[test]
+    fn test_portable_mode_uses_scalar_kernels() {
+        assert_eq!(isa_level(), IsaLevel::Scalar);
+        assert_eq!(memory_features(), MemoryFeatures::default());
+    }
+
+    #[test]
+    fn test_isa_level_bits_roundtrip() {
+        for level in IsaLevel::ALL {
+            assert_eq!(IsaLevel::from_bits(level.to_bits()), Some(level));
+        }
+        assert_eq!(IsaLevel::from_bits(LEVEL_UNINIT), None);
+    }
+
+    #[cfg(all(target_arch = "x86_64", not(miri), not(feature = "portable")))]
+    #[test]
+    fn test_memory_features_match_std_detection() {
+        let features = memory_features();
+        assert_eq!(features.erms, std::is_x86_feature_detected!("ermsb"));
+        if features.l2_size != 0 && features.l3_size != 0 {
+            assert!(features.l3_size >= features.l2_size);
+        }
+    }
+
+    #[test]
+    fn test_thresholds_from_features() {
+        assert_eq!(
+            Thresholds::from_features(&MemoryFeatures {
+                erms: true,
+                ..Default::default()
+            }),
+            Thresholds::DEFAULT
+        );
+
+        let no_erms = Thresholds::from_features(&MemoryFeatures::default());
+        assert_eq!(no_erms.rep_movsb, usize::MAX);
+
+        let caches = MemoryFeatures {
+            erms: true,
+            fsrm: false,
+            l2_size: 1024 * 1024,
+            l3_size: 32 * 1024 * 1024,
+        };
+        assert_eq!(Thresholds::from_features(&caches).rep_movsb, 1024 * 1024);
+
+        // FSRM lowers the cutoff to glibc's, whatever the L2 size.
+        let zen4 = Thresholds::from_features(&MemoryFeatures {
+            fsrm: true,
+            ..caches
+        });
+        assert_eq!(zen4.rep_movsb, 2112);
+        assert_eq!(zen4.memcpy_nt, 8 * 1024 * 1024);
+        assert_eq!(zen4.memset_nt, 2 * 1024 * 1024);
+
+        // A small L3 never drives the cutoffs below L2.
+        let small = Thresholds::from_features(&MemoryFeatures {
+            erms: true,
+            l2_size: 2 * 1024 * 1024,
+            l3_size: 4 * 1024 * 1024,
+            ..Default::default()
+        });
+        assert_eq!(small.memcpy_nt, 2 * 1024 * 1024);
+        assert_eq!(small.memset_nt, 2 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_thresholds_are_cached() {
+        let t = thresholds();
+        #[cfg_attr(not(feature = "std"), allow(unused_mut))]
+        let mut expected = Thresholds::from_features(&memory_features());
+        #[cfg(feature = "std")]
+        crate::tunables::get().apply(&mut expected);
+        assert_eq!(t, expected);
+        assert_eq!(thresholds(), t);
+        assert!(t.rep_movsb > 0 && t.memcpy_nt > 0 && t.memset_nt > 0);
+    }
+}
@@ -0,0 +1,355 @@
+//! String functions over [`CStr`], [`CString`] and byte slices
+//!
+//! The functions in [`str`](crate::str), [`search`](crate::search) and the
+//! tokenizers find each argument's terminator with `strlen` before doing any
+//! work. The ones here take any [`CStrLike`] string instead, so a [`CStr`] or
+//! [`CString`], whose length is already known, is never rescanned. Byte
+//! slices are still accepted and are scanned once, as before.
+//!
+//! # Examples
+//! ```
+//! use faststrings::cstr;
+//!
+//! assert_eq!(cstr::strcmp(c"abc", c"abd"), -1);
+//! assert_eq!(cstr::strstr(c"hello world", b"wor\0"), Some(6));
+//! assert_eq!(cstr::strdup(&b"copy\0tail"[..]).as_c_str(), c"copy");
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::ffi::CString;
+use core::ffi::CStr;
+
+use crate::token::{Splitter, Tokenizer};
+
+/// A NUL-terminated string whose bytes can be borrowed without the
+/// terminator.
+pub trait CStrLike {
+    /// The bytes of the string, up to but not including the terminator.
+    ///
+    /// For [`CStr`] and [`CString`] this is free. A byte slice is cut at its
+    /// first NUL, or used whole if it has none.
+    fn str_bytes(&self) -> &[u8];
+}
+
+impl CStrLike for [u8] {
+    #[inline]
+    fn str_bytes(&self) -> &[u8] {
+        &self[..crate::str::strlen(self)]
+    }
+}
+
+impl<const N: usize> CStrLike for [u8; N] {
+    #[inline]
+    fn str_bytes(&self) -> &[u8] {
+        self.as_slice().str_bytes()
+    }
+}
+
+impl CStrLike for CStr {
+    #[inline]
+    fn str_bytes(&self) -> &[u8] {
+        self.to_bytes()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl CStrLike for CString {
+    #[inline]
+    fn str_bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<T: CStrLike + ?Sized> CStrLike for &T {
+    #[inline]
+    fn str_bytes(&self) -> &[u8] {
+        (**self).str_bytes()
+    }
+}
+
+/// Length of `s`, see [`str::strlen`](crate::str::strlen).
+#[inline]
+pub fn strlen<S: CStrLike + ?Sized>(s: &S) -> usize {
+    s.str_bytes().len()
+}
+
+/// Compare two strings, see [`str::strcmp`](crate::str::strcmp).
+///
+/// # Examples
+/// ```
+/// use faststrings::cstr::strcmp;
+/// assert!(strcmp(c"abc", c"abd") < 0);
+/// assert_eq!(strcmp(c"abc", b"abc\0"), 0);
+/// ```
+pub fn strcmp<A: CStrLike + ?Sized, B: CStrLike + ?Sized>(s1: &A, s2: &B) -> i32 {
+    crate::str::strcmp_bytes(s1.str_bytes(), s2.str_bytes())
+}
+
+/// Compare at most `n` bytes of two strings, see
+/// [`str::strncmp`](crate::str::strncmp).
+pub fn strncmp<A: CStrLike + ?Sized, B: CStrLike + ?Sized>(s1: &A, s2: &B, n: usize) -> i32 {
+    crate::str::strncmp_bytes(s1.str_bytes(), s2.str_bytes(), n)
+}
+
+/// Compare two strings ignoring ASCII case, see
+/// [`str::strcasecmp`](crate::str::strcasecmp).
+pub fn strcasecmp<A: CStrLike + ?Sized, B: CStrLike + ?Sized>(s1: &A, s2: &B) -> i32 {
+    crate::str::strcasecmp_bytes(s1.str_bytes(), s2.str_bytes())
+}
+
+/// Compare at most `n` bytes of two strings ignoring ASCII case, see
+/// [`str::strncasecmp`](crate::str::strncasecmp).
+pub fn strncasecmp<A: CStrLike + ?Sized, B: CStrLike + ?Sized>(s1: &A, s2: &B, n: usize) -> i32 {
+    crate::str::strncasecmp_bytes(s1.str_bytes(), s2.str_bytes(), n)
+}
+
+/// Index of the first `c` in `s`, see [`search::strchr`](crate::search::strchr).
+///
+/// Searching for `0` finds the terminator at `strlen(s)`.
+///
+/// # Examples
+/// ```
+/// use faststrings::cstr::strchr;
+/// assert_eq!(strchr(c"hello", b'l'), Some(2));
+/// assert_eq!(strchr(c"hello", 0), Some(5));
+/// ```
+pub fn strchr<S: CStrLike + ?Sized>(s: &S, c: u8) -> Option<usize> {
+    let s = s.str_bytes();
+    if c == 0 {
+        return Some(s.len());
+    }
+    crate::mem::memchr(s, c)
+}
+
+/// Index of the last `c` in `s`, see [`search::strrchr`](crate::search::strrchr).
+pub fn strrchr<S: CStrLike + ?Sized>(s: &S, c: u8) -> Option<usize> {
+    let s = s.str_bytes();
+    if c == 0 {
+        return Some(s.len());
+    }
+    crate::mem::memrchr(s, c)
+}
+
+/// Index of the first `needle` in `haystack`, see
+/// [`search::strstr`](crate::search::strstr).
+pub fn strstr<H: CStrLike + ?Sized, N: CStrLike + ?Sized>(
+    haystack: &H,
+    needle: &N,
+) -> Option<usize> {
+    crate::search::strstr_bytes(haystack.str_bytes(), needle.str_bytes())
+}
+
+/// Index of the first `needle` in `haystack` ignoring ASCII case, see
+/// [`search::strcasestr`](crate::search::strcasestr).
+pub fn strcasestr<H: CStrLike + ?Sized, N: CStrLike + ?Sized>(
+    haystack: &H,
+    needle: &N,
+) -> Option<usize> {
+    crate::search::strcasestr_bytes(haystack.str_bytes(), needle.str_bytes())
+}
+
+/// Length of the prefix of `s` made of bytes in `accept`, see
+/// [`search::strspn`](crate::search::strspn).
+pub fn strspn<S: CStrLike + ?Sized, A: CStrLike + ?Sized>(s: &S, accept: &A) -> usize {
+    crate::search::strspn_bytes(s.str_bytes(), accept.str_bytes())
+}
+
+/// Length of the prefix of `s` made of bytes not in `reject`, see
+/// [`search::strcspn`](crate::search::strcspn).
+pub fn strcspn<S: CStrLike + ?Sized, R: CStrLike + ?Sized>(s: &S, reject: &R) -> usize {
+    crate::search::strcspn_bytes(s.str_bytes(), reject.str_bytes())
+}
+
+/// Index of the first byte of `s` in `accept`, see
+/// [`search::strpbrk`](crate::search::strpbrk).
+pub fn strpbrk<S: CStrLike + ?Sized, A: CStrLike + ?Sized>(s: &S, accept: &A) -> Option<usize> {
+    crate::search::strpbrk_bytes(s.str_bytes(), accept.str_bytes())
+}
+
+/// Next token of `s`, see [`strtok_r::strtok_r`](crate::strtok_r::strtok_r).
+pub fn strtok_r<'a, S: CStrLike + ?Sized, D: CStrLike + ?Sized>(
+    s: &'a S,
+    delim: &D,
+    saveptr: &mut usize,
+) -> Option<&'a [u8]> {
+    crate::strtok_r::strtok_r_bytes(s.str_bytes(), delim.str_bytes(), saveptr)
+}
+
+/// Iterator over the tokens of `s`, see [`Tokenizer`].
+///
+/// # Examples
+/// ```
+/// use faststrings::cstr::tokens;
+/// let words: Vec<_> = tokens(c"a, b,,c", c", ").collect();
+/// assert_eq!(words, [&b"a"[..], b"b", b"c"]);
+/// ```
+pub fn tokens<'a, S: CStrLike + ?Sized, D: CStrLike + ?Sized>(
+    s: &'a S,
+    delim: &'a D,
+) -> Tokenizer<'a> {
+    Tokenizer::from_slice(s.str_bytes(), delim.str_bytes())
+}
+
+/// Iterator over the fields of `s`, empty ones included, see [`Splitter`].
+pub fn fields<'a, S: CStrLike + ?Sized, D: CStrLike + ?Sized>(
+    s: &'a S,
+    delim: &'a D,
+) -> Splitter<'a> {
+    Splitter::from_slice(s.str_bytes(), delim.str_bytes())
+}
+
+/// Copy `src` and its terminator into `dest`, see
+/// [`str::strcpy`](crate::str::strcpy).
+pub fn strcpy<S: CStrLike + ?Sized>(dest: &mut [u8], src: &S) -> usize {
+    crate::str::strcpy_bytes(dest, src.str_bytes(), true)
+}
+
+/// Append `src` to the string in `dest`, see [`str::strcat`](crate::str::strcat).
+pub fn strcat<S: CStrLike + ?Sized>(dest: &mut [u8], src: &S) -> usize {
+    crate::str::strcat_bytes(dest, src.str_bytes())
+}
+
+/// Copy `src` into `dest`, truncating, see [`str::strlcpy`](crate::str::strlcpy).
+pub fn strlcpy<S: CStrLike + ?Sized>(dest: &mut [u8], src: &S) -> usize {
+    crate::str::strlcpy_bytes(dest, src.str_bytes())
+}
+
+/// Append `src` to the string in `dest`, truncating, see
+/// [`str::strlcat`](crate::str::strlcat).
+pub fn strlcat<S: CStrLike + ?Sized>(dest: &mut [u8], src: &S) -> usize {
+    crate::str::strlcat_bytes(dest, src.str_bytes())
+}
+
+/// Duplicate `src` into a [`CString`].
+#[cfg(feature = "alloc")]
+pub fn strdup<S: CStrLike + ?Sized>(src: &S) -> CString {
+    into_cstring(src.str_bytes())
+}
+
+/// Duplicate at most `n` bytes of `src` into a [`CString`].
+#[cfg(feature = "alloc")]
+pub fn strndup<S: CStrLike + ?Sized>(src: &S, n: usize) -> CString {
+    let s = src.str_bytes();
+    into_cstring(&s[..s.len().min(n)])
+}
+
+/// Copies `s`, which holds no NUL, into an exactly sized [`CString`].
+#[cfg(feature = "alloc")]
+fn into_cstring(s: &[u8]) -> CString {
+    // SAFETY: `str_bytes` stops at the first NUL, so the only NUL in the
+    // duplicate is the terminator `dup` appends.
+    unsafe { CString::from_vec_with_nul_unchecked(crate::strdup::dup(s, 0)) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRINGS: [&[u8]; 8] = [
+        b"\0",
+        b"abc\0",
+        b"abd\0",
+        b"ABC\0",
+        b"abcdef\0",
+        b"hello world\0",
+        b"0123456789abcdefghijklmnopqrstuvwxyz0123456789\0",
+        b"ab\0cd\0",
+    ];
+
+    fn as_cstr(s: &[u8]) -> &CStr {
+        CStr::from_bytes_until_nul(s).unwrap()
+    }
+
+    #[test]
+    fn test_str_bytes_stops_at_terminator() {
+        assert_eq!(b"abc\0def"[..].str_bytes(), b"abc");
+        assert_eq!(b"abc".str_bytes(), b"abc");
+        assert_eq!(c"abc".str_bytes(), b"abc");
+        #[cfg(feature = "alloc")]
+        assert_eq!(CString::from(c"abc").str_bytes(), b"abc");
+    }
+
+    #[test]
+    fn test_compare_matches_slice_versions() {
+        for a in STRINGS {
+            for b in STRINGS {
+                let (ca, cb) = (as_cstr(a), as_cstr(b));
+                assert_eq!(strcmp(ca, cb), crate::str::strcmp(a, b));
+                assert_eq!(strcmp(a, cb), crate::str::strcmp(a, b));
+                assert_eq!(strcasecmp(ca, cb), crate::str::strcasecmp(a, b));
+                for n in [0, 1, 2, 3, 5, 64] {
+                    assert_eq!(strncmp(ca, cb, n), crate::str::strncmp(a, b, n));
+                    assert_eq!(strncasecmp(ca, cb, n), crate::str::strncasecmp(a, b, n));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_matches_slice_versions() {
+        use crate::search;
+
+        for a in STRINGS {
+            for b in STRINGS {
+                let (ca, cb) = (as_cstr(a), as_cstr(b));
+                assert_eq!(strstr(ca, cb), search::strstr(a, b));
+                assert_eq!(strcasestr(ca, cb), search::strcasestr(a, b));
+                assert_eq!(strspn(ca, cb), search::strspn(a, b));
+                assert_eq!(strcspn(ca, cb), search::strcspn(a, b));
+                assert_eq!(strpbrk(ca, cb), search::strpbrk(a, b));
+            }
+            for c in [0, b'a', b'c', b'z', b'9'] {
+                assert_eq!(strchr(as_cstr(a), c), search::strchr(a, c));
+                assert_eq!(strrchr(as_cstr(a), c), search::strrchr(a, c));
+            }
+        }
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let mut save = 0usize;
+        assert_eq!(strtok_r(c"aa,bb,,cc", c",", &mut save), Some(&b"aa"[..]));
+        assert_eq!(strtok_r(c"aa,bb,,cc", c",", &mut save), Some(&b"bb"[..]));
+        assert_eq!(strtok_r(c"aa,bb,,cc", c",", &mut save), Some(&b"cc"[..]));
+        assert_eq!(strtok_r(c"aa,bb,,cc", c",", &mut save), None);
+
+        let mut split = fields(c"a,,b", b",\0");
+        assert_eq!(split.next(), Some(&b"a"[..]));
+        assert_eq!(split.next(), Some(&b""[..]));
+        assert_eq!(split.next(), Some(&b"b"[..]));
+        assert_eq!(split.next(), None);
+    }
+
+    #[test]
+    fn test_copy_into_slices() {
+        let mut dest = [0xFFu8; 8];
+        assert_eq!(strcpy(&mut dest, c"abc"), 4);
+        assert_eq!(&dest[..4], b"abc\0");
+        assert_eq!(strcat(&mut dest, c"de"), 5);
+        assert_eq!(&dest[..6], b"abcde\0");
+        assert_eq!(strlcat(&mut dest, c"fghij"), 10);
+        assert_eq!(&dest, b"abcdefg\0");
+        assert_eq!(strlcpy(&mut dest[..3], c"xyz"), 3);
+        assert_eq!(&dest[..3], b"xy\0");
+
+        // A full destination keeps the copy but drops the terminator, like
+        // `str::strcpy`.
+        let mut small = [0u8; 3];
+        assert_eq!(strcpy(&mut small, c"abc"), 3);
+        assert_eq!(&small, b"abc");
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_dup_to_cstring() {
+        assert_eq!(strdup(c"abc").as_c_str(), c"abc");
+        assert_eq!(strdup(&b"abc\0def"[..]).as_c_str(), c"abc");
+        assert_eq!(strdup(b"abc").as_c_str(), c"abc");
+        assert_eq!(strndup(c"abcdef", 2).as_c_str(), c"ab");
+        assert_eq!(strndup(c"abc", 10).as_c_str(), c"abc");
+
+        let owned = strdup(c"");
+        assert_eq!(owned.as_bytes_with_nul(), b"\0");
+        assert_eq!(strcmp(&owned, c""), 0);
+    }
+}
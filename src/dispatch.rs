@@ -0,0 +1,50 @@
+//! Cached per-routine function pointers.
+//!
+//! Each dispatched routine owns a `FnSlot` that initially points at a
+//! resolver. The resolver asks `cpu` for the host's ISA level, stores the
+//! chosen kernel in the slot and forwards the call, so every later call is a
+//! single relaxed load plus an indirect call.
+
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/// Atomic storage for a function pointer of type `F`.
+///
+/// `F` must be a plain (thin) function pointer type such as
+/// `unsafe fn(*mut u8, *const u8, usize)`.
+pub(crate) struct FnSlot<F: Copy> {
+    ptr: AtomicPtr<()>,
+    _marker: PhantomData<F>,
+}
+
+// SAFETY: The slot only holds a code pointer, which is freely shareable.
+unsafe impl<F: Copy> Sync for FnSlot<F> {}
+
+impl<F: Copy> FnSlot<F> {
+    /// Creates a slot pointing at `resolver`, which must have type `F`.
+    pub(crate) const fn new(resolver: *mut ()) -> Self {
+        Self {
+            ptr: AtomicPtr::new(resolver),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the currently installed function.
+    #[inline(always)]
+    pub(crate) fn get(&self) -> F {
+        const { assert!(core::mem::size_of::<F>() == core::mem::size_of::<*mut ()>()) };
+        let raw = self.ptr.load(Ordering::Relaxed);
+        // SAFETY: Only values of type `F` are ever stored (see `new`/`set`),
+        // and `F` is pointer-sized.
+        unsafe { core::mem::transmute_copy::<*mut (), F>(&raw) }
+    }
+
+    /// Installs `f` for all subsequent calls.
+    #[inline]
+    pub(crate) fn set(&self, f: F) {
+        const { assert!(core::mem::size_of::<F>() == core::mem::size_of::<*mut ()>()) };
+        // SAFETY: `F` is pointer-sized (checked above).
+        let raw = unsafe { core::mem::transmute_copy::<F, *mut ()>(&f) };
+        self.ptr.store(raw, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,455 @@
+//! Method forms of the byte and wide string functions
+//!
+//! [`FastBytesExt`] puts [`mem`](crate::mem), [`search`](crate::search),
+//! [`str`](crate::str) and [`token`](crate::token) on `[u8]`, and so on
+//! `Vec<u8>` and arrays through deref. [`FastWideExt`] does the same for
+//! [`wmem`](crate::wmem) and [`wide`](crate::wide) on `[wchar_t]`.
+//!
+//! Methods named `fast_*` treat the receiver as raw memory; methods ending in
+//! `_nul` treat it, and any string argument, as NUL-terminated, with a slice
+//! lacking a NUL taken whole. Comparisons return [`Ordering`] rather than the
+//! C sign convention.
+//!
+//! # Examples
+//! ```
+//! use core::cmp::Ordering;
+//! use faststrings::ext::FastBytesExt;
+//!
+//! let buf = b"key=value\0".to_vec();
+//! assert_eq!(buf.fast_find(b"val"), Some(4));
+//! assert_eq!(buf.fast_rfind_byte(b'e'), Some(8));
+//! assert_eq!(buf.cmp_nul(b"key\0"), Ordering::Greater);
+//! assert_eq!(buf.tokens(b"=").collect::<Vec<_>>(), [&b"key"[..], b"value"]);
+//! ```
+
+use core::cmp::Ordering;
+
+use crate::token::{Splitter, Tokenizer};
+use crate::types::wchar_t;
+use crate::wcstok::WcsTokens;
+use crate::{mem, search, str, wide, wmem};
+
+/// Converts a C-style comparison result to an [`Ordering`].
+#[inline(always)]
+fn ordering(c: i32) -> Ordering {
+    c.cmp(&0)
+}
+
+/// Byte string and memory routines as methods on `[u8]`.
+pub trait FastBytesExt {
+    /// Index of the first `b`, see [`mem::memchr`].
+    fn fast_find_byte(&self, b: u8) -> Option<usize>;
+
+    /// Index of the last `b`, see [`mem::memrchr`].
+    fn fast_rfind_byte(&self, b: u8) -> Option<usize>;
+
+    /// Index of the first occurrence of `needle`, see [`mem::memmem`].
+    fn fast_find(&self, needle: &[u8]) -> Option<usize>;
+
+    /// Lexicographic comparison with `other`, see [`mem::memcmp`].
+    fn fast_cmp(&self, other: &[u8]) -> Ordering;
+
+    /// Copy the start of `src` over the start of `self`, see [`mem::memcpy`].
+    fn fast_copy_from(&mut self, src: &[u8]) -> usize;
+
+    /// Set every byte to `b`, see [`mem::memset`].
+    fn fast_fill(&mut self, b: u8);
+
+    /// Length of the string, see [`str::strlen`].
+    fn len_nul(&self) -> usize;
+
+    /// The bytes of the string, without the terminator.
+    fn until_nul(&self) -> &[u8];
+
+    /// String comparison with `other`, see [`str::strcmp`].
+    fn cmp_nul(&self, other: &[u8]) -> Ordering;
+
+    /// String comparison with `other` ignoring ASCII case, see
+    /// [`str::strcasecmp`].
+    fn cmp_nul_ignore_ascii_case(&self, other: &[u8]) -> Ordering;
+
+    /// Index of the first `b` in the string, see [`search::strchr`].
+    fn find_byte_nul(&self, b: u8) -> Option<usize>;
+
+    /// Index of the last `b` in the string, see [`search::strrchr`].
+    fn rfind_byte_nul(&self, b: u8) -> Option<usize>;
+
+    /// Index of the string `needle` in the string, see [`search::strstr`].
+    fn find_nul(&self, needle: &[u8]) -> Option<usize>;
+
+    /// Length of the prefix made of bytes in `accept`, see [`search::strspn`].
+    fn span_nul(&self, accept: &[u8]) -> usize;
+
+    /// Length of the prefix made of bytes not in `reject`, see
+    /// [`search::strcspn`].
+    fn cspan_nul(&self, reject: &[u8]) -> usize;
+
+    /// Index of the first byte in `set`, see [`search::strpbrk`].
+    fn find_any_nul(&self, set: &[u8]) -> Option<usize>;
+
+    /// Copy the string `src` in, truncating, see [`str::strlcpy`].
+    fn copy_nul_from(&mut self, src: &[u8]) -> usize;
+
+    /// Append the string `src`, truncating, see [`str::strlcat`].
+    fn append_nul(&mut self, src: &[u8]) -> usize;
+
+    /// Iterator over the tokens of the string, see [`Tokenizer`].
+    fn tokens<'a>(&'a self, delims: &'a [u8]) -> Tokenizer<'a>;
+
+    /// Iterator over the fields of the string, empty ones included, see
+    /// [`Splitter`].
+    fn fields<'a>(&'a self, delims: &'a [u8]) -> Splitter<'a>;
+}
+
+impl FastBytesExt for [u8] {
+    #[inline]
+    fn fast_find_byte(&self, b: u8) -> Option<usize> {
+        mem::memchr(self, b)
+    }
+
+    #[inline]
+    fn fast_rfind_byte(&self, b: u8) -> Option<usize> {
+        mem::memrchr(self, b)
+    }
+
+    #[inline]
+    fn fast_find(&self, needle: &[u8]) -> Option<usize> {
+        mem::memmem(self, needle)
+    }
+
+    #[inline]
+    fn fast_cmp(&self, other: &[u8]) -> Ordering {
+        ordering(mem::memcmp(self, other))
+    }
+
+    #[inline]
+    fn fast_copy_from(&mut self, src: &[u8]) -> usize {
+        mem::memcpy(self, src)
+    }
+
+    #[inline]
+    fn fast_fill(&mut self, b: u8) {
+        mem::memset(self, b);
+    }
+
+    #[inline]
+    fn len_nul(&self) -> usize {
+        str::strlen(self)
+    }
+
+    #[inline]
+    fn until_nul(&self) -> &[u8] {
+        &self[..str::strlen(self)]
+    }
+
+    #[inline]
+    fn cmp_nul(&self, other: &[u8]) -> Ordering {
+        ordering(str::strcmp(self, other))
+    }
+
+    #[inline]
+    fn cmp_nul_ignore_ascii_case(&self, other: &[u8]) -> Ordering {
+        ordering(str::strcasecmp(self, other))
+    }
+
+    #[inline]
+    fn find_byte_nul(&self, b: u8) -> Option<usize> {
+        search::strchr(self, b)
+    }
+
+    #[inline]
+    fn rfind_byte_nul(&self, b: u8) -> Option<usize> {
+        search::strrchr(self, b)
+    }
+
+    #[inline]
+    fn find_nul(&self, needle: &[u8]) -> Option<usize> {
+        search::strstr(self, needle)
+    }
+
+    #[inline]
+    fn span_nul(&self, accept: &[u8]) -> usize {
+        search::strspn(self, accept)
+    }
+
+    #[inline]
+    fn cspan_nul(&self, reject: &[u8]) -> usize {
+        search::strcspn(self, reject)
+    }
+
+    #[inline]
+    fn find_any_nul(&self, set: &[u8]) -> Option<usize> {
+        search::strpbrk(self, set)
+    }
+
+    #[inline]
+    fn copy_nul_from(&mut self, src: &[u8]) -> usize {
+        str::strlcpy(self, src)
+    }
+
+    #[inline]
+    fn append_nul(&mut self, src: &[u8]) -> usize {
+        str::strlcat(self, src)
+    }
+
+    #[inline]
+    fn tokens<'a>(&'a self, delims: &'a [u8]) -> Tokenizer<'a> {
+        Tokenizer::new(self, delims)
+    }
+
+    #[inline]
+    fn fields<'a>(&'a self, delims: &'a [u8]) -> Splitter<'a> {
+        Splitter::new(self, delims)
+    }
+}
+
+/// Wide string and memory routines as methods on `[wchar_t]`.
+pub trait FastWideExt {
+    /// Index of the first `c`, see [`wmem::wmemchr`].
+    fn fast_find_wide(&self, c: wchar_t) -> Option<usize>;
+
+    /// Index of the last `c`, see [`wmem::wmemrchr`].
+    fn fast_rfind_wide(&self, c: wchar_t) -> Option<usize>;
+
+    /// Lexicographic comparison with `other`, see [`wmem::wmemcmp`].
+    fn fast_cmp(&self, other: &[wchar_t]) -> Ordering;
+
+    /// Copy the start of `src` over the start of `self`, see [`wmem::wmemcpy`].
+    fn fast_copy_from(&mut self, src: &[wchar_t]) -> usize;
+
+    /// Set every element to `c`, see [`wmem::wmemset`].
+    fn fast_fill(&mut self, c: wchar_t);
+
+    /// Length of the string, see [`wide::wcslen`].
+    fn len_nul(&self) -> usize;
+
+    /// The elements of the string, without the terminator.
+    fn until_nul(&self) -> &[wchar_t];
+
+    /// String comparison with `other`, see [`wide::wcscmp`].
+    fn cmp_nul(&self, other: &[wchar_t]) -> Ordering;
+
+    /// String comparison with `other` ignoring ASCII case, see
+    /// [`wide::wcscasecmp`].
+    fn cmp_nul_ignore_ascii_case(&self, other: &[wchar_t]) -> Ordering;
+
+    /// Index of the first `c` in the string, see [`wide::wcschr`].
+    fn find_wide_nul(&self, c: wchar_t) -> Option<usize>;
+
+    /// Index of the last `c` in the string, see [`wide::wcsrchr`].
+    fn rfind_wide_nul(&self, c: wchar_t) -> Option<usize>;
+
+    /// Index of the string `needle` in the string, see [`wide::wcsstr`].
+    fn find_nul(&self, needle: &[wchar_t]) -> Option<usize>;
+
+    /// Length of the prefix made of elements in `accept`, see
+    /// [`wide::wcsspn`].
+    fn span_nul(&self, accept: &[wchar_t]) -> usize;
+
+    /// Length of the prefix made of elements not in `reject`, see
+    /// [`wide::wcscspn`].
+    fn cspan_nul(&self, reject: &[wchar_t]) -> usize;
+
+    /// Index of the first element in `set`, see [`wide::wcspbrk`].
+    fn find_any_nul(&self, set: &[wchar_t]) -> Option<usize>;
+
+    /// Copy the string `src` in, truncating, see [`wide::wcslcpy`].
+    fn copy_nul_from(&mut self, src: &[wchar_t]) -> usize;
+
+    /// Append the string `src`, truncating, see [`wide::wcslcat`].
+    fn append_nul(&mut self, src: &[wchar_t]) -> usize;
+
+    /// Iterator over the tokens of the string, see [`WcsTokens`].
+    fn tokens<'a>(&'a self, delims: &'a [wchar_t]) -> WcsTokens<'a>;
+}
+
+impl FastWideExt for [wchar_t] {
+    #[inline]
+    fn fast_find_wide(&self, c: wchar_t) -> Option<usize> {
+        wmem::wmemchr(self, c)
+    }
+
+    #[inline]
+    fn fast_rfind_wide(&self, c: wchar_t) -> Option<usize> {
+        wmem::wmemrchr(self, c)
+    }
+
+    #[inline]
+    fn fast_cmp(&self, other: &[wchar_t]) -> Ordering {
+        ordering(wmem::wmemcmp(self, other))
+    }
+
+    #[inline]
+    fn fast_copy_from(&mut self, src: &[wchar_t]) -> usize {
+        wmem::wmemcpy(self, src)
+    }
+
+    #[inline]
+    fn fast_fill(&mut self, c: wchar_t) {
+        wmem::wmemset(self, c);
+    }
+
+    #[inline]
+    fn len_nul(&self) -> usize {
+        wide::wcslen(self)
+    }
+
+    #[inline]
+    fn until_nul(&self) -> &[wchar_t] {
+        &self[..wide::wcslen(self)]
+    }
+
+    #[inline]
+    fn cmp_nul(&self, other: &[wchar_t]) -> Ordering {
+        ordering(wide::wcscmp(self, other))
+    }
+
+    #[inline]
+    fn cmp_nul_ignore_ascii_case(&self, other: &[wchar_t]) -> Ordering {
+        ordering(wide::wcscasecmp(self, other))
+    }
+
+    #[inline]
+    fn find_wide_nul(&self, c: wchar_t) -> Option<usize> {
+        wide::wcschr(self, c)
+    }
+
+    #[inline]
+    fn rfind_wide_nul(&self, c: wchar_t) -> Option<usize> {
+        wide::wcsrchr(self, c)
+    }
+
+    #[inline]
+    fn find_nul(&self, needle: &[wchar_t]) -> Option<usize> {
+        wide::wcsstr(self, needle)
+    }
+
+    #[inline]
+    fn span_nul(&self, accept: &[wchar_t]) -> usize {
+        wide::wcsspn(self, accept)
+    }
+
+    #[inline]
+    fn cspan_nul(&self, reject: &[wchar_t]) -> usize {
+        wide::wcscspn(self, reject)
+    }
+
+    #[inline]
+    fn find_any_nul(&self, set: &[wchar_t]) -> Option<usize> {
+        wide::wcspbrk(self, set)
+    }
+
+    #[inline]
+    fn copy_nul_from(&mut self, src: &[wchar_t]) -> usize {
+        wide::wcslcpy(self, src)
+    }
+
+    #[inline]
+    fn append_nul(&mut self, src: &[wchar_t]) -> usize {
+        wide::wcslcat(self, src)
+    }
+
+    #[inline]
+    fn tokens<'a>(&'a self, delims: &'a [wchar_t]) -> WcsTokens<'a> {
+        WcsTokens::new(self, delims)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn w(s: &[u8]) -> [wchar_t; 16] {
+        let mut out = [0 as wchar_t; 16];
+        for (o, &b) in out.iter_mut().zip(s) {
+            *o = b as wchar_t;
+        }
+        out
+    }
+
+    #[test]
+    fn test_bytes_methods_match_functions() {
+        let buf = *b"hello, world\0tail";
+        assert_eq!(buf.fast_find_byte(b'o'), Some(4));
+        assert_eq!(buf.fast_rfind_byte(b'l'), Some(16));
+        assert_eq!(buf.rfind_byte_nul(b'l'), Some(10));
+        assert_eq!(buf.fast_find(b"tail"), Some(13));
+        assert_eq!(buf.find_nul(b"tail\0"), None);
+        assert_eq!(buf.find_nul(b"world"), Some(7));
+        assert_eq!(buf.find_byte_nul(0), Some(12));
+        assert_eq!(buf.len_nul(), 12);
+        assert_eq!(buf.until_nul(), b"hello, world");
+        assert_eq!(buf.span_nul(b"ehlo\0"), 5);
+        assert_eq!(buf.cspan_nul(b", "), 5);
+        assert_eq!(buf.find_any_nul(b"wr"), Some(7));
+    }
+
+    #[test]
+    fn test_bytes_comparisons_return_ordering() {
+        assert_eq!(b"abc".fast_cmp(b"abd"), Ordering::Less);
+        assert_eq!(b"abc".fast_cmp(b"ab"), Ordering::Greater);
+        assert_eq!(b"abc\0x".cmp_nul(b"abc\0y"), Ordering::Equal);
+        assert_eq!(b"abc\0x".fast_cmp(b"abc\0y"), Ordering::Less);
+        assert_eq!(b"ABC\0".cmp_nul_ignore_ascii_case(b"abc"), Ordering::Equal);
+        assert_eq!(b"b\0".cmp_nul(b"abc\0"), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_bytes_mutation_and_tokens() {
+        let mut buf = [0xAAu8; 8];
+        buf.fast_fill(0);
+        assert_eq!(buf, [0; 8]);
+        assert_eq!(buf.copy_nul_from(b"abc\0"), 3);
+        assert_eq!(buf.append_nul(b"defgh"), 8);
+        assert_eq!(&buf, b"abcdefg\0");
+        assert_eq!(buf.fast_copy_from(b"xy"), 2);
+        assert_eq!(&buf[..3], b"xyc");
+
+        let mut tokens = b",a,,b\0c".tokens(b",");
+        assert_eq!(tokens.next(), Some(&b"a"[..]));
+        assert_eq!(tokens.next(), Some(&b"b"[..]));
+        assert_eq!(tokens.next(), None);
+
+        let mut fields = b"a,,b".fields(b",\0");
+        assert_eq!(fields.next(), Some(&b"a"[..]));
+        assert_eq!(fields.next(), Some(&b""[..]));
+        assert_eq!(fields.next(), Some(&b"b"[..]));
+        assert_eq!(fields.next(), None);
+    }
+
+    #[test]
+    fn test_wide_methods_match_functions() {
+        let s = w(b"Hello, world");
+        assert_eq!(s.len_nul(), 12);
+        assert_eq!(s.until_nul(), &w(b"Hello, world")[..12]);
+        assert_eq!(s.fast_find_wide(b'o' as wchar_t), Some(4));
+        assert_eq!(s.fast_rfind_wide(b'o' as wchar_t), Some(8));
+        assert_eq!(s.fast_rfind_wide(0), Some(15));
+        assert_eq!(s.find_wide_nul(0), Some(12));
+        assert_eq!(s.rfind_wide_nul(b'l' as wchar_t), Some(10));
+        assert_eq!(s.find_nul(&w(b"wor")), Some(7));
+        assert_eq!(s.span_nul(&w(b"Hel")), 4);
+        assert_eq!(s.cspan_nul(&w(b" ,")), 5);
+        assert_eq!(s.find_any_nul(&w(b"dw")), Some(7));
+
+        assert_eq!(s.cmp_nul(&w(b"Hello")), Ordering::Greater);
+        assert_eq!(
+            s.cmp_nul_ignore_ascii_case(&w(b"hello, WORLD")),
+            Ordering::Equal
+        );
+        assert_eq!(s.fast_cmp(&w(b"Hello, world!")), Ordering::Less);
+
+        let mut dest = [1 as wchar_t; 8];
+        dest.fast_fill(0);
+        assert_eq!(dest.copy_nul_from(&s), 12);
+        assert_eq!(dest.until_nul(), &s[..7]);
+        assert_eq!(dest.fast_copy_from(&w(b"ab")[..2]), 2);
+        assert_eq!(dest.append_nul(&w(b"xyz")), 10);
+
+        let delims = w(b", ");
+        let mut tokens = s.tokens(&delims);
+        assert_eq!(tokens.next(), Some(&s[..5]));
+        assert_eq!(tokens.next(), Some(&s[7..12]));
+        assert_eq!(tokens.next(), None);
+    }
+}
@@ -0,0 +1,439 @@
+//! Call-shape histograms for the hot routines (`instrument` feature).
+//!
+//! With the feature enabled, every call to one of the [`Routine`]s bumps a
+//! per-thread counter for its log2 size bucket and for the alignment of its
+//! first pointer modulo 64; `memmove` also counts its overlap direction, and
+//! `strlen`/`strnlen` are bucketed by the length they return. The
+//! counters are plain loads and stores on memory owned by the calling thread,
+//! so recording costs a few cycles and never contends.
+//!
+//! [`snapshot`] sums the counters of every thread, including threads that have
+//! exited, and [`reset`] starts a new measurement window:
+//!
+//! ```
+//! # #[cfg(feature = "instrument")] {
+//! use faststrings::instrument::{self, Routine};
+//!
+//! instrument::reset();
+//! // ... run traffic ...
+//! let shape = instrument::snapshot();
+//! for (sizes, calls) in shape.get(Routine::Memcpy).size_buckets() {
+//!     println!("memcpy {sizes:?}: {calls}");
+//! }
+//! # }
+//! ```
+
+use core::fmt;
+use core::ops::RangeInclusive;
+use core::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::vec::Vec;
+
+/// Number of size buckets: one for zero, then one per power of two.
+pub const SIZE_BUCKETS: usize = usize::BITS as usize + 1;
+
+/// Number of alignment buckets (address modulo 64).
+pub const ALIGN_BUCKETS: usize = 64;
+
+/// An instrumented routine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Routine {
+    Memcpy,
+    Memmove,
+    Memset,
+    Memcmp,
+    Memchr,
+    Memrchr,
+    Strlen,
+    Strnlen,
+}
+
+impl Routine {
+    /// Number of instrumented routines.
+    pub const COUNT: usize = 8;
+
+    /// Every instrumented routine.
+    pub const ALL: [Routine; Routine::COUNT] = [
+        Routine::Memcpy,
+        Routine::Memmove,
+        Routine::Memset,
+        Routine::Memcmp,
+        Routine::Memchr,
+        Routine::Memrchr,
+        Routine::Strlen,
+        Routine::Strnlen,
+    ];
+
+    /// Lowercase name, e.g. `"memcpy"`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Routine::Memcpy => "memcpy",
+            Routine::Memmove => "memmove",
+            Routine::Memset => "memset",
+            Routine::Memcmp => "memcmp",
+            Routine::Memchr => "memchr",
+            Routine::Memrchr => "memrchr",
+            Routine::Strlen => "strlen",
+            Routine::Strnlen => "strnlen",
+        }
+    }
+}
+
+/// How a `memmove` call's ranges relate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MoveDirection {
+    /// The ranges do not overlap (or the call moves nothing).
+    Disjoint,
+    /// They overlap with `dest` at or below `src`, so bytes move low to high.
+    Forward,
+    /// They overlap with `dest` above `src`, so bytes move high to low.
+    Backward,
+}
+
+impl MoveDirection {
+    /// Every direction.
+    pub const ALL: [MoveDirection; 3] = [
+        MoveDirection::Disjoint,
+        MoveDirection::Forward,
+        MoveDirection::Backward,
+    ];
+
+    fn of(dest: usize, src: usize, n: usize) -> MoveDirection {
+        if n == 0 || dest.abs_diff(src) >= n {
+            MoveDirection::Disjoint
+        } else if dest <= src {
+            MoveDirection::Forward
+        } else {
+            MoveDirection::Backward
+        }
+    }
+}
+
+/// Size and alignment counts for one routine.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Histogram {
+    /// Calls per size bucket; see [`Histogram::bucket_sizes`].
+    pub sizes: [u64; SIZE_BUCKETS],
+    /// Calls per first-pointer address modulo 64.
+    pub alignments: [u64; ALIGN_BUCKETS],
+}
+
+impl Histogram {
+    const EMPTY: Histogram = Histogram {
+        sizes: [0; SIZE_BUCKETS],
+        alignments: [0; ALIGN_BUCKETS],
+    };
+
+    /// Total calls.
+    pub fn calls(&self) -> u64 {
+        self.sizes.iter().sum()
+    }
+
+    /// The sizes counted in bucket `i`: `0..=0`, then `2^(i-1)..=2^i - 1`.
+    pub const fn bucket_sizes(i: usize) -> RangeInclusive<usize> {
+        match i {
+            0 => 0..=0,
+            _ => (1 << (i - 1))..=(usize::MAX >> (usize::BITS as usize - i)),
+        }
+    }
+
+    /// Non-empty size buckets with their counts, smallest first.
+    pub fn size_buckets(&self) -> impl Iterator<Item = (RangeInclusive<usize>, u64)> + '_ {
+        self.sizes
+            .iter()
+            .enumerate()
+            .filter(|&(_, &calls)| calls != 0)
+            .map(|(i, &calls)| (Histogram::bucket_sizes(i), calls))
+    }
+}
+
+/// Counts for every routine at one point in time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    routines: [Histogram; Routine::COUNT],
+    directions: [u64; 3],
+}
+
+impl Snapshot {
+    const EMPTY: Snapshot = Snapshot {
+        routines: [Histogram::EMPTY; Routine::COUNT],
+        directions: [0; 3],
+    };
+
+    /// Counts for `routine`.
+    pub fn get(&self, routine: Routine) -> &Histogram {
+        &self.routines[routine as usize]
+    }
+
+    /// `memmove` calls that moved in `direction`.
+    pub fn memmove_direction(&self, direction: MoveDirection) -> u64 {
+        self.directions[direction as usize]
+    }
+
+    fn add(&mut self, counters: &Counters) {
+        for (hist, raw) in self.routines.iter_mut().zip(&counters.routines) {
+            for (sum, c) in hist.sizes.iter_mut().zip(&raw.sizes) {
+                *sum += c.load(Ordering::Relaxed);
+            }
+            for (sum, c) in hist.alignments.iter_mut().zip(&raw.alignments) {
+                *sum += c.load(Ordering::Relaxed);
+            }
+        }
+        for (sum, c) in self.directions.iter_mut().zip(&counters.directions) {
+            *sum += c.load(Ordering::Relaxed);
+        }
+    }
+
+    fn saturating_sub(&mut self, base: &Snapshot) {
+        for (hist, base) in self.routines.iter_mut().zip(&base.routines) {
+            for (n, b) in hist.sizes.iter_mut().zip(&base.sizes) {
+                *n = n.saturating_sub(*b);
+            }
+            for (n, b) in hist.alignments.iter_mut().zip(&base.alignments) {
+                *n = n.saturating_sub(*b);
+            }
+        }
+        for (n, b) in self.directions.iter_mut().zip(&base.directions) {
+            *n = n.saturating_sub(*b);
+        }
+    }
+}
+
+impl fmt::Display for Snapshot {
+    /// One line per routine with calls, then one indented line per non-empty
+    /// size bucket.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for routine in Routine::ALL {
+            let hist = self.get(routine);
+            if hist.calls() == 0 {
+                continue;
+            }
+            writeln!(f, "{}: {} calls", routine.name(), hist.calls())?;
+            for (sizes, calls) in hist.size_buckets() {
+                writeln!(f, "  {}..={}: {}", sizes.start(), sizes.end(), calls)?;
+            }
+        }
+        let [disjoint, forward, backward] = self.directions;
+        if disjoint + forward + backward != 0 {
+            writeln!(
+                f,
+                "memmove directions: disjoint {disjoint}, forward {forward}, backward {backward}"
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// =============================================================================
+// PER-THREAD COUNTERS
+// =============================================================================
+
+struct RoutineCounters {
+    sizes: [AtomicU64; SIZE_BUCKETS],
+    alignments: [AtomicU64; ALIGN_BUCKETS],
+}
+
+struct Counters {
+    routines: [RoutineCounters; Routine::COUNT],
+    directions: [AtomicU64; 3],
+}
+
+impl Counters {
+    fn new() -> Self {
+        Counters {
+            routines: [const {
+                RoutineCounters {
+                    sizes: [const { AtomicU64::new(0) }; SIZE_BUCKETS],
+                    alignments: [const { AtomicU64::new(0) }; ALIGN_BUCKETS],
+                }
+            }; Routine::COUNT],
+            directions: [const { AtomicU64::new(0) }; 3],
+        }
+    }
+}
+
+/// Increments a counter only the calling thread writes. Readers may see a
+/// slightly stale value but never a torn one, and no `lock` prefix is needed.
+#[inline(always)]
+fn bump(counter: &AtomicU64) {
+    counter.store(counter.load(Ordering::Relaxed) + 1, Ordering::Relaxed);
+}
+
+struct Registry {
+    live: Vec<Arc<Counters>>,
+    /// Totals of threads that have exited.
+    retired: Snapshot,
+    /// Totals at the last `reset`, subtracted from every snapshot.
+    baseline: Snapshot,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    live: Vec::new(),
+    retired: Snapshot::EMPTY,
+    baseline: Snapshot::EMPTY,
+});
+
+fn registry() -> MutexGuard<'static, Registry> {
+    // Counters stay consistent even if a holder panicked.
+    REGISTRY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl Registry {
+    fn totals(&self) -> Snapshot {
+        let mut totals = self.retired.clone();
+        for counters in &self.live {
+            totals.add(counters);
+        }
+        totals
+    }
+}
+
+struct Local(Arc<Counters>);
+
+impl Local {
+    fn register() -> Self {
+        let counters = Arc::new(Counters::new());
+        registry().live.push(Arc::clone(&counters));
+        Local(counters)
+    }
+}
+
+impl Drop for Local {
+    fn drop(&mut self) {
+        let mut registry = registry();
+        registry.retired.add(&self.0);
+        registry.live.retain(|c| !Arc::ptr_eq(c, &self.0));
+    }
+}
+
+std::thread_local! {
+    static LOCAL: Local = Local::register();
+}
+
+/// Records one call of `routine` on `n` bytes whose first pointer is `addr`.
+#[inline]
+pub(crate) fn record(routine: Routine, n: usize, addr: usize) {
+    // Calls made while the thread's locals are being torn down go uncounted.
+    let _ = LOCAL.try_with(|local| {
+        let counters = &local.0.routines[routine as usize];
+        bump(&counters.sizes[(usize::BITS - n.leading_zeros()) as usize]);
+        bump(&counters.alignments[addr % ALIGN_BUCKETS]);
+    });
+}
+
+/// Records one `memmove` call, including its overlap direction.
+#[inline]
+pub(crate) fn record_memmove(dest: usize, src: usize, n: usize) {
+    record(Routine::Memmove, n, dest);
+    let _ = LOCAL.try_with(|local| {
+        bump(&local.0.directions[MoveDirection::of(dest, src, n) as usize]);
+    });
+}
+
+/// Returns the counts of every thread since the last [`reset`].
+pub fn snapshot() -> Snapshot {
+    let registry = registry();
+    let mut totals = registry.totals();
+    totals.saturating_sub(&registry.baseline);
+    totals
+}
+
+/// Returns the calling thread's counts since it started, ignoring [`reset`].
+pub fn thread_snapshot() -> Snapshot {
+    let mut snapshot = Snapshot::EMPTY;
+    let _ = LOCAL.try_with(|local| snapshot.add(&local.0));
+    snapshot
+}
+
+/// Starts a new window: later [`snapshot`]s only count calls made after this.
+pub fn reset() {
+    let mut registry = registry();
+    registry.baseline = registry.totals();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec;
+
+    #[test]
+    fn test_bucket_sizes_cover_every_size() {
+        assert_eq!(Histogram::bucket_sizes(0), 0..=0);
+        assert_eq!(Histogram::bucket_sizes(1), 1..=1);
+        assert_eq!(Histogram::bucket_sizes(2), 2..=3);
+        assert_eq!(Histogram::bucket_sizes(7), 64..=127);
+        assert_eq!(
+            Histogram::bucket_sizes(SIZE_BUCKETS - 1),
+            (1 << (usize::BITS - 1))..=usize::MAX
+        );
+        for n in [0usize, 1, 2, 3, 4, 63, 64, 65, 4096, usize::MAX] {
+            let bucket = (usize::BITS - n.leading_zeros()) as usize;
+            assert!(Histogram::bucket_sizes(bucket).contains(&n), "n={n}");
+        }
+    }
+
+    #[test]
+    fn test_thread_counts_sizes_alignment_and_direction() {
+        std::thread::spawn(|| {
+            let src = [7u8; 256];
+            let mut dst = [0u8; 256];
+            let mut buf = [1u8; 256];
+            unsafe {
+                crate::memcpy::optimized_memcpy_unified(dst.as_mut_ptr(), src.as_ptr(), 100);
+                crate::memcpy::optimized_memcpy_unified(dst.as_mut_ptr(), src.as_ptr(), 0);
+                crate::memmove::optimized_memmove_unified(
+                    buf.as_mut_ptr(),
+                    buf.as_ptr().add(8),
+                    100,
+                );
+                crate::memmove::optimized_memmove_unified(
+                    buf.as_mut_ptr().add(8),
+                    buf.as_ptr(),
+                    100,
+                );
+                crate::memmove::optimized_memmove_unified(dst.as_mut_ptr(), src.as_ptr(), 100);
+            }
+            assert_eq!(
+                crate::str::strlen(b"hello, world, this is a long string\0"),
+                35
+            );
+
+            let snap = thread_snapshot();
+            let memcpy = snap.get(Routine::Memcpy);
+            // The disjoint memmove must not also count as a memcpy.
+            assert_eq!(memcpy.calls(), 2);
+            assert_eq!(memcpy.sizes[0], 1);
+            assert_eq!(memcpy.sizes[7], 1);
+            assert_eq!(memcpy.alignments[dst.as_ptr() as usize % 64], 2);
+
+            assert_eq!(snap.get(Routine::Memmove).calls(), 3);
+            assert_eq!(snap.memmove_direction(MoveDirection::Forward), 1);
+            assert_eq!(snap.memmove_direction(MoveDirection::Backward), 1);
+            assert_eq!(snap.memmove_direction(MoveDirection::Disjoint), 1);
+
+            assert_eq!(snap.get(Routine::Strlen).calls(), 1);
+            assert_eq!(snap.get(Routine::Strlen).sizes[6], 1);
+            assert_eq!(snap.get(Routine::Memchr).calls(), 0);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_exited_threads_are_kept_until_reset() {
+        // 5000 bytes lands in bucket 13, which no other test fills this much.
+        let hay = vec![1u8; 5000];
+        let count = || snapshot().get(Routine::Strnlen).sizes[13];
+        std::thread::spawn(move || {
+            for _ in 0..1000 {
+                assert_eq!(crate::str::strnlen(&hay, hay.len()), 5000);
+            }
+        })
+        .join()
+        .unwrap();
+
+        assert!(count() >= 1000);
+        reset();
+        assert!(count() < 1000);
+    }
+}
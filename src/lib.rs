@@ -1,28 +1,74 @@
 //! faststrings: high-performance C/POSIX-style string and memory routines.
+//!
+//! The crate is `no_std`. The default `std` feature adds the environment
+//! tunables and calibration; without it, the `alloc` feature still provides
+//! the duplicating routines (`strdup`, `strndup`, `wcsdup`).
+#![no_std]
+#![cfg_attr(feature = "std-simd", feature(portable_simd))]
+// The C exports define `memcpy` and friends, so the optimizer must not turn
+// the kernels' own loops back into calls to them.
+#![cfg_attr(feature = "capi", no_builtins)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+/// The allocator interface taken by the `_in` duplicating routines.
+#[cfg(feature = "alloc")]
+pub use allocator_api2;
+
+#[cfg(feature = "alloc")]
+pub mod arena;
+pub mod arraycstr;
 pub mod bcopy;
+#[cfg(all(target_arch = "x86_64", feature = "std"))]
+pub mod calibrate;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod cpu;
+pub mod cstr;
+#[cfg(target_arch = "x86_64")]
+pub(crate) mod dispatch;
+pub mod ext;
 pub mod ffs;
+#[cfg(feature = "instrument")]
+pub mod instrument;
 pub mod mem;
 pub mod memchr;
 pub mod memcmp;
 pub mod memcpy;
 pub mod memmove;
 pub mod memset;
+pub mod nulstr;
+#[cfg(feature = "preload")]
+pub mod preload;
+pub mod raw;
 pub mod search;
+#[cfg(target_arch = "x86_64")]
 pub mod simd;
+#[cfg(feature = "std-simd")]
+pub mod stdsimd;
 pub mod stpncpy;
 pub mod str;
 pub mod strerror;
 pub mod strerror_r;
+#[cfg(feature = "alloc")]
 pub mod strdup;
+#[cfg(feature = "alloc")]
 pub mod strndup;
 pub mod strtok;
 pub mod strtok_r;
 pub mod strxfrm;
+pub mod timingsafe;
 pub mod token;
+#[cfg(feature = "std")]
+pub mod tunables;
 pub mod types;
+#[cfg(feature = "alloc")]
 pub mod wcsdup;
 pub mod wcstok;
 pub mod wcsxfrm;
 pub mod wide;
 pub mod wmem;
+pub mod zeroize;
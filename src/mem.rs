@@ -2,6 +2,11 @@
 //!
 //! Safe Rust implementations of memory functions operating on byte slices.
 
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::mem::MaybeUninit;
+use core::ops::Range;
+
 /// Copy bytes from source to destination (non-overlapping)
 ///
 /// Copies bytes from `src` to `dest`. Returns the number of bytes copied,
@@ -24,19 +29,107 @@ pub fn memcpy(dest: &mut [u8], src: &[u8]) -> usize {
     n
 }
 
+/// Copy bytes into an uninitialized destination
+///
+/// Like [`memcpy`], but `dest` need not be initialized, so a buffer such as
+/// `Vec::spare_capacity_mut` can be filled without zeroing it first. Returns
+/// the `min(dest.len(), src.len())` bytes written, now initialized.
+///
+/// # Examples
+/// ```
+/// use core::mem::MaybeUninit;
+/// use faststrings::mem::memcpy_uninit;
+/// let mut dest = [MaybeUninit::<u8>::uninit(); 8];
+/// assert_eq!(memcpy_uninit(&mut dest, b"hello"), b"hello");
+/// ```
+pub fn memcpy_uninit<'a>(dest: &'a mut [MaybeUninit<u8>], src: &[u8]) -> &'a mut [u8] {
+    let n = dest.len().min(src.len());
+    let d = dest.as_mut_ptr() as *mut u8;
+    // SAFETY: Both slices hold at least `n` bytes and cannot overlap; the
+    // copy initializes the `n` bytes the returned slice covers.
+    unsafe {
+        crate::memcpy::optimized_memcpy_unified(d, src.as_ptr(), n);
+        core::slice::from_raw_parts_mut(d, n)
+    }
+}
+
+/// Append bytes to a vector
+///
+/// Like `Vec::extend_from_slice`, copying `src` straight into the spare
+/// capacity with the optimized kernel.
+#[cfg(feature = "alloc")]
+pub fn vec_extend(vec: &mut Vec<u8>, src: &[u8]) {
+    vec.reserve(src.len());
+    let len = vec.len();
+    memcpy_uninit(vec.spare_capacity_mut(), src);
+    // SAFETY: The first `src.len()` spare bytes were just initialized.
+    unsafe { vec.set_len(len + src.len()) };
+}
+
 /// Copy bytes from source to destination (overlapping safe)
 ///
 /// Copies bytes from `src` to `dest`, correctly handling overlapping regions.
 /// Returns the number of bytes copied.
 ///
 /// In safe Rust with slices, we cannot have overlapping mutable and immutable
-/// references, so this behaves identically to `memcpy`.
+/// references, so this behaves identically to `memcpy`. Use
+/// [`memmove_within`] for overlapping moves inside one buffer.
 pub fn memmove(dest: &mut [u8], src: &[u8]) -> usize {
     let n = dest.len().min(src.len());
     dest[..n].copy_from_slice(&src[..n]);
     n
 }
 
+/// Move bytes within one buffer (overlapping safe)
+///
+/// Copies `buf[src]` to `buf[dest..]` as if through a temporary, so the two
+/// ranges may overlap in either direction. Returns the number of bytes moved.
+///
+/// # Panics
+///
+/// Panics if `src` is decreasing or ends past the buffer, or if fewer than
+/// `src.len()` bytes follow `dest`.
+///
+/// # Examples
+/// ```
+/// use faststrings::mem::memmove_within;
+/// let mut buf = *b"hello world";
+/// assert_eq!(memmove_within(&mut buf, 0..5, 6), 5);
+/// assert_eq!(&buf, b"hello hello");
+/// ```
+pub fn memmove_within(buf: &mut [u8], src: Range<usize>, dest: usize) -> usize {
+    let n = move_within_len(buf.len(), &src, dest);
+    // SAFETY: `move_within_len` checked that both ranges lie within `buf`.
+    unsafe {
+        let base = buf.as_mut_ptr();
+        crate::memmove::optimized_memmove_unified(base.add(dest), base.add(src.start), n);
+    }
+    n
+}
+
+/// Length of a move of `src` to `dest` within `len` elements.
+///
+/// Panics unless both ranges lie within the buffer.
+pub(crate) fn move_within_len(len: usize, src: &Range<usize>, dest: usize) -> usize {
+    assert!(
+        src.start <= src.end,
+        "source range starts at {} but ends at {}",
+        src.start,
+        src.end
+    );
+    assert!(
+        src.end <= len,
+        "source range end {} out of range for length {len}",
+        src.end
+    );
+    let n = src.end - src.start;
+    assert!(
+        dest <= len - n,
+        "destination {dest} out of range for a move of {n} in length {len}"
+    );
+    n
+}
+
 /// Fill a byte slice with a constant value
 ///
 /// Sets all bytes in `dest` to the value `c`. Returns the number of bytes set.
@@ -53,6 +146,65 @@ pub fn memset(dest: &mut [u8], c: u8) -> usize {
     dest.len()
 }
 
+/// Fill a byte slice with a repeating pattern
+///
+/// Writes `pattern` over `dest` again and again from its first byte; the
+/// last copy is cut short if it does not fit. Returns the number of bytes
+/// set.
+///
+/// # Panics
+///
+/// Panics if `pattern` is empty or longer than
+/// [`MAX_PATTERN_LEN`](crate::memset::MAX_PATTERN_LEN) (64) bytes.
+///
+/// # Examples
+/// ```
+/// use faststrings::mem::memset_pattern;
+/// let mut rgb = [0u8; 8];
+/// memset_pattern(&mut rgb, &[0x10, 0x20, 0x30]);
+/// assert_eq!(rgb, [0x10, 0x20, 0x30, 0x10, 0x20, 0x30, 0x10, 0x20]);
+/// ```
+pub fn memset_pattern(dest: &mut [u8], pattern: &[u8]) -> usize {
+    // SAFETY: `dest` is writable for its length.
+    unsafe { crate::memset::optimized_memset_pattern(dest.as_mut_ptr(), pattern, dest.len()) };
+    dest.len()
+}
+
+/// Fill a slice of 16-bit elements with a constant value
+///
+/// Returns the number of elements set.
+///
+/// # Examples
+/// ```
+/// use faststrings::mem::memset16;
+/// let mut pixels = [0u16; 4];
+/// memset16(&mut pixels, 0xf800);
+/// assert_eq!(pixels, [0xf800; 4]);
+/// ```
+pub fn memset16(dest: &mut [u16], c: u16) -> usize {
+    // SAFETY: `dest` is writable for its length.
+    unsafe { crate::memset::optimized_memset16(dest.as_mut_ptr(), c, dest.len()) };
+    dest.len()
+}
+
+/// Fill a slice of 32-bit elements with a constant value
+///
+/// Returns the number of elements set.
+pub fn memset32(dest: &mut [u32], c: u32) -> usize {
+    // SAFETY: `dest` is writable for its length.
+    unsafe { crate::memset::optimized_memset32(dest.as_mut_ptr(), c, dest.len()) };
+    dest.len()
+}
+
+/// Fill a slice of 64-bit elements with a constant value
+///
+/// Returns the number of elements set.
+pub fn memset64(dest: &mut [u64], c: u64) -> usize {
+    // SAFETY: `dest` is writable for its length.
+    unsafe { crate::memset::optimized_memset64(dest.as_mut_ptr(), c, dest.len()) };
+    dest.len()
+}
+
 /// Compare two byte slices
 ///
 /// Compares byte slices lexicographically.
@@ -205,16 +357,33 @@ pub fn memmem(haystack: &[u8], needle: &[u8]) -> Option<usize> {
     None
 }
 
-/// Set memory to zero (secure-ish in safe Rust)
-///
-/// Zeros out the byte slice. In safe Rust, we cannot guarantee this won't
-/// be optimized away, but the optimizer is less aggressive with slice operations.
+/// Set memory to zero, even if it is never read again
 ///
-/// For truly secure zeroing, the unsafe FFI layer can use `write_volatile`.
+/// Zeros out the byte slice with volatile stores followed by a compiler
+/// fence, so the optimizer cannot drop the wipe as a dead store, as it may
+/// for [`bzero`] right before the buffer goes out of scope. Use it to erase
+/// keys and passwords; [`Zeroizing`](crate::zeroize::Zeroizing) does it on
+/// drop.
 pub fn explicit_bzero(s: &mut [u8]) {
-    s.fill(0);
-    // In safe Rust, we can't use write_volatile or compiler_fence
-    // The FFI layer will need to handle secure zeroing
+    memset_explicit(s, 0);
+}
+
+/// Fill a byte slice with a constant value, even if it is never read again
+///
+/// C23 `memset_explicit`: like [`memset`], but the stores are kept by the
+/// optimizer, see [`explicit_bzero`]. Returns the number of bytes set.
+///
+/// # Examples
+/// ```
+/// use faststrings::mem::memset_explicit;
+/// let mut key = *b"hunter2";
+/// assert_eq!(memset_explicit(&mut key, 0), 7);
+/// assert_eq!(key, [0; 7]);
+/// ```
+pub fn memset_explicit(dest: &mut [u8], c: u8) -> usize {
+    // SAFETY: `dest` is writable for its length.
+    unsafe { crate::memset::optimized_memset_explicit(dest.as_mut_ptr(), c, dest.len()) };
+    dest.len()
 }
 
 /// Zero bytes (BSD)
@@ -226,11 +395,54 @@ pub fn bzero(s: &mut [u8]) {
 
 /// Compare bytes (BSD)
 ///
-/// Compares two byte slices. Equivalent to `memcmp`.
+/// Compares two byte slices. Equivalent to `memcmp`, so it returns early at
+/// the first difference; use [`timingsafe_bcmp`] for secrets.
 pub fn bcmp(s1: &[u8], s2: &[u8]) -> i32 {
     memcmp(s1, s2)
 }
 
+/// Compare two byte slices for equality in constant time (OpenBSD)
+///
+/// Returns 0 if the slices are equal and 1 otherwise. Every byte is read
+/// and the time taken depends only on the lengths, which are not secret,
+/// so it is safe for checking MACs and tokens.
+///
+/// # Examples
+/// ```
+/// use faststrings::mem::timingsafe_bcmp;
+/// assert_eq!(timingsafe_bcmp(b"tag0", b"tag0"), 0);
+/// assert_eq!(timingsafe_bcmp(b"tag0", b"tag1"), 1);
+/// ```
+pub fn timingsafe_bcmp(s1: &[u8], s2: &[u8]) -> i32 {
+    let n = s1.len().min(s2.len());
+    // SAFETY: Both slices hold at least `n` bytes.
+    let res = unsafe { crate::timingsafe::optimized_timingsafe_bcmp(s1.as_ptr(), s2.as_ptr(), n) };
+    res | (s1.len() != s2.len()) as i32
+}
+
+/// Compare two byte slices in constant time (OpenBSD)
+///
+/// Returns -1, 0 or 1 like [`memcmp`], with the shorter slice less if one
+/// is a prefix of the other. Every byte of the common length is read and
+/// the time taken depends only on the lengths.
+///
+/// # Examples
+/// ```
+/// use faststrings::mem::timingsafe_memcmp;
+/// assert_eq!(timingsafe_memcmp(b"abc", b"abd"), -1);
+/// assert_eq!(timingsafe_memcmp(b"abc", b"ab"), 1);
+/// ```
+pub fn timingsafe_memcmp(s1: &[u8], s2: &[u8]) -> i32 {
+    let n = s1.len().min(s2.len());
+    // SAFETY: Both slices hold at least `n` bytes.
+    let res =
+        unsafe { crate::timingsafe::optimized_timingsafe_memcmp(s1.as_ptr(), s2.as_ptr(), n) };
+    // The lengths decide only when the contents tie; pick without a branch
+    // on `res`.
+    let by_len = (s1.len() > s2.len()) as i32 - (s1.len() < s2.len()) as i32;
+    res | (by_len & ((res == 0) as i32).wrapping_neg())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -247,6 +459,87 @@ mod tests {
         assert_eq!(&dest2, b"hel");
     }
 
+    #[test]
+    fn test_memcpy_uninit() {
+        let mut src = [0u8; 600];
+        for (i, b) in src.iter_mut().enumerate() {
+            *b = (i % 251) as u8;
+        }
+        for n in [0usize, 1, 15, 62, 63, 64, 255, 600] {
+            let mut dest = [MaybeUninit::<u8>::uninit(); 600];
+            assert_eq!(memcpy_uninit(&mut dest[..n], &src), &src[..n]);
+            assert_eq!(memcpy_uninit(&mut dest, &src[..n]), &src[..n]);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_vec_extend() {
+        let mut v = Vec::from(&b"ab"[..]);
+        vec_extend(&mut v, b"");
+        vec_extend(&mut v, b"cde");
+        let long = [b'z'; 500];
+        vec_extend(&mut v, &long);
+        assert_eq!(&v[..5], b"abcde");
+        assert_eq!(&v[5..], &long[..]);
+    }
+
+    #[test]
+    fn test_memmove_within_matches_copy_within() {
+        let mut init = [0u8; 1100];
+        for (i, b) in init.iter_mut().enumerate() {
+            *b = (i * 7 + 3) as u8;
+        }
+
+        for n in [0usize, 1, 7, 31, 63, 64, 65, 255, 256, 257, 1000] {
+            for (start, dest) in [(0, 1), (1, 0), (0, 33), (33, 0), (50, 50)] {
+                if start + n > init.len() || dest + n > init.len() {
+                    continue;
+                }
+                let mut expect = init;
+                expect.copy_within(start..start + n, dest);
+                let mut buf = init;
+                assert_eq!(memmove_within(&mut buf, start..start + n, dest), n);
+                assert_eq!(buf, expect, "n={n} start={start} dest={dest}");
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_memmove_within_rejects_short_destination() {
+        memmove_within(&mut [0u8; 8], 0..4, 5);
+    }
+
+    #[test]
+    fn test_memset_elements_and_pattern() {
+        let mut w16 = [0u16; 37];
+        assert_eq!(memset16(&mut w16, 0xabcd), 37);
+        assert!(w16.iter().all(|&v| v == 0xabcd));
+
+        let mut w32 = [0u32; 129];
+        assert_eq!(memset32(&mut w32[1..], 0xdead_beef), 128);
+        assert_eq!(w32[0], 0);
+        assert!(w32[1..].iter().all(|&v| v == 0xdead_beef));
+
+        let mut w64 = [1u64; 300];
+        assert_eq!(memset64(&mut w64[..299], u64::MAX), 299);
+        assert!(w64[..299].iter().all(|&v| v == u64::MAX));
+        assert_eq!(w64[299], 1);
+
+        let mut buf = [0u8; 1000];
+        let pat = *b"0123456789";
+        assert_eq!(memset_pattern(&mut buf[3..], &pat), 997);
+        assert_eq!(&buf[..3], &[0, 0, 0]);
+        assert!(buf[3..].chunks(10).all(|c| c == &pat[..c.len()]));
+    }
+
+    #[test]
+    #[should_panic(expected = "fill pattern must be 1 to 64 bytes")]
+    fn test_memset_pattern_rejects_long_pattern() {
+        memset_pattern(&mut [0u8; 8], &[1u8; 65]);
+    }
+
     #[test]
     fn test_memcmp_variants() {
         assert_eq!(memcmp(b"abc", b"abc"), 0);
@@ -281,10 +574,39 @@ mod tests {
         assert_eq!(bcmp(b"abc", b"abc"), 0);
     }
 
+    #[test]
+    fn test_timingsafe_compares() {
+        assert_eq!(timingsafe_bcmp(b"", b""), 0);
+        assert_eq!(timingsafe_bcmp(b"secret", b"secret"), 0);
+        assert_eq!(timingsafe_bcmp(b"secret", b"secreT"), 1);
+        assert_eq!(timingsafe_bcmp(b"secret", b"secre"), 1);
+
+        assert_eq!(timingsafe_memcmp(b"", b""), 0);
+        assert_eq!(timingsafe_memcmp(b"\x80", b"\x7f"), 1);
+        assert_eq!(timingsafe_memcmp(b"ab", b"abc"), -1);
+        assert_eq!(timingsafe_memcmp(b"abd", b"abcd"), 1);
+        let a = [0x5au8; 100];
+        let mut b = a;
+        b[40] = 0;
+        b[70] = 0xff;
+        assert_eq!(timingsafe_memcmp(&a, &b), 1);
+        assert_eq!(timingsafe_memcmp(&b, &a), -1);
+        assert_eq!(timingsafe_memcmp(&a, &a), 0);
+    }
+
     #[test]
     fn test_explicit_bzero() {
         let mut buf = [5u8, 6, 7];
         explicit_bzero(&mut buf);
         assert_eq!(buf, [0u8; 3]);
+
+        let mut big = [0x11u8; 300];
+        for n in [0usize, 1, 31, 32, 33, 255, 299] {
+            assert_eq!(memset_explicit(&mut big[1..1 + n], 0xee), n);
+            assert!(big[1..1 + n].iter().all(|&b| b == 0xee));
+            explicit_bzero(&mut big[1..1 + n]);
+            assert!(big[1..1 + n].iter().all(|&b| b == 0));
+            assert_eq!(big[0], 0x11);
+        }
     }
 }
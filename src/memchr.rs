@@ -4,11 +4,29 @@
 #[cfg(target_arch = "x86_64")]
 use core::arch::x86_64::*;
 
+#[cfg(target_arch = "x86_64")]
+use crate::cpu::{self, IsaLevel};
+#[cfg(target_arch = "x86_64")]
+use crate::dispatch::FnSlot;
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) type ScanFn = unsafe fn(*const u8, usize, u8) -> Option<usize>;
+
+/// Forward scan kernel for the bytes after the first, chosen on first use.
+#[cfg(target_arch = "x86_64")]
+pub(crate) static MEMCHR_SCAN: FnSlot<ScanFn> = FnSlot::new(resolve_memchr_scan as *mut ());
+
+/// Reverse scan kernel for the bytes before the last, chosen on first use.
+#[cfg(target_arch = "x86_64")]
+static MEMRCHR_SCAN: FnSlot<ScanFn> = FnSlot::new(resolve_memrchr_scan as *mut ());
+
+#[cfg(target_arch = "x86_64")]
 #[inline(always)]
 fn first_set_bit(mask: i32) -> usize {
     (mask as u32).trailing_zeros() as usize
 }
 
+#[cfg(target_arch = "x86_64")]
 #[inline(always)]
 fn last_set_bit(mask: i32) -> usize {
     31 - (mask as u32).leading_zeros() as usize
@@ -28,6 +46,17 @@ fn has_zero_byte_u64(x: u64) -> bool {
 /// - `s` must be valid for reads of `n` bytes.
 #[inline(always)]
 pub unsafe fn optimized_memchr_unified(s: *const u8, n: usize, needle: u8) -> Option<usize> {
+    #[cfg(feature = "instrument")]
+    crate::instrument::record(crate::instrument::Routine::Memchr, n, s as usize);
+
+    // SAFETY: Forwarded caller contract.
+    unsafe { memchr_dispatched(s, n, needle) }
+}
+
+/// `optimized_memchr_unified` without instrumentation, for callers that
+/// record the call under their own routine.
+#[inline(always)]
+pub(crate) unsafe fn memchr_dispatched(s: *const u8, n: usize, needle: u8) -> Option<usize> {
     if n == 0 {
         return None;
     }
@@ -40,12 +69,17 @@ pub unsafe fn optimized_memchr_unified(s: *const u8, n: usize, needle: u8) -> Op
 
     #[cfg(target_arch = "x86_64")]
     {
-        return unsafe { optimized_memchr_avx2(s.add(1), n - 1, needle).map(|i| i + 1) };
+        unsafe { (MEMCHR_SCAN.get())(s.add(1), n - 1, needle).map(|i| i + 1) }
     }
 
-    #[cfg(not(target_arch = "x86_64"))]
+    #[cfg(all(not(target_arch = "x86_64"), feature = "std-simd"))]
     {
-        unsafe { optimized_memchr_scalar(s.add(1), n - 1, needle).map(|i| i + 1) }
+        unsafe { crate::stdsimd::memchr(s.add(1), n - 1, needle).map(|i| i + 1) }
+    }
+
+    #[cfg(all(not(target_arch = "x86_64"), not(feature = "std-simd")))]
+    {
+        unsafe { optimized_memchr_scalar_wide(s.add(1), n - 1, needle).map(|i| i + 1) }
     }
 }
 
@@ -58,6 +92,9 @@ pub unsafe fn optimized_memchr_unified(s: *const u8, n: usize, needle: u8) -> Op
 /// - `s` must be valid for reads of `n` bytes.
 #[inline(always)]
 pub unsafe fn optimized_memrchr_unified(s: *const u8, n: usize, needle: u8) -> Option<usize> {
+    #[cfg(feature = "instrument")]
+    crate::instrument::record(crate::instrument::Routine::Memrchr, n, s as usize);
+
     if n == 0 {
         return None;
     }
@@ -71,15 +108,52 @@ pub unsafe fn optimized_memrchr_unified(s: *const u8, n: usize, needle: u8) -> O
 
     #[cfg(target_arch = "x86_64")]
     {
-        return unsafe { optimized_memrchr_avx2(s, n - 1, needle) };
+        unsafe { (MEMRCHR_SCAN.get())(s, n - 1, needle) }
     }
 
     #[cfg(not(target_arch = "x86_64"))]
     {
-        unsafe { optimized_memrchr_scalar(s, n - 1, needle) }
+        unsafe { optimized_memrchr_scalar_wide(s, n - 1, needle) }
     }
 }
 
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn select_memchr_scan(level: IsaLevel) -> ScanFn {
+    match level {
+        IsaLevel::Avx512 => optimized_memchr_avx512,
+        IsaLevel::Avx2 => optimized_memchr_avx2,
+        IsaLevel::Sse42 | IsaLevel::Sse2 => optimized_memchr_sse2,
+        IsaLevel::Scalar => optimized_memchr_scalar_wide,
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn select_memrchr_scan(level: IsaLevel) -> ScanFn {
+    match level {
+        IsaLevel::Avx512 => optimized_memrchr_avx512,
+        IsaLevel::Avx2 => optimized_memrchr_avx2,
+        IsaLevel::Sse42 | IsaLevel::Sse2 => optimized_memrchr_sse2,
+        IsaLevel::Scalar => optimized_memrchr_scalar_wide,
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn resolve_memchr_scan(s: *const u8, n: usize, needle: u8) -> Option<usize> {
+    let f = select_memchr_scan(cpu::isa_level());
+    MEMCHR_SCAN.set(f);
+    // SAFETY: Forwarded caller contract.
+    unsafe { f(s, n, needle) }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn resolve_memrchr_scan(s: *const u8, n: usize, needle: u8) -> Option<usize> {
+    let f = select_memrchr_scan(cpu::isa_level());
+    MEMRCHR_SCAN.set(f);
+    // SAFETY: Forwarded caller contract.
+    unsafe { f(s, n, needle) }
+}
+
+#[cfg(any(target_arch = "x86_64", not(feature = "std-simd")))]
 #[inline(always)]
 unsafe fn optimized_memchr_scalar(s: *const u8, n: usize, needle: u8) -> Option<usize> {
     let mut i = 0usize;
@@ -106,6 +180,7 @@ unsafe fn optimized_memrchr_scalar(s: *const u8, n: usize, needle: u8) -> Option
     None
 }
 
+#[cfg(any(target_arch = "x86_64", not(feature = "std-simd")))]
 #[inline(always)]
 unsafe fn optimized_memchr_scalar_wide(s: *const u8, n: usize, needle: u8) -> Option<usize> {
     let needle_64 = u64::from_ne_bytes([needle; 8]);
@@ -150,6 +225,237 @@ unsafe fn optimized_memrchr_scalar_wide(s: *const u8, n: usize, needle: u8) -> O
     optimized_memrchr_scalar(s, i, needle)
 }
 
+#[cfg(target_arch = "x86_64")]
+unsafe fn optimized_memchr_sse2(s: *const u8, n: usize, needle: u8) -> Option<usize> {
+    // SAFETY: Caller guarantees `s` is readable for `n` bytes; every unaligned
+    // 16-byte load stays below `n`.
+    unsafe {
+        let needle_v = _mm_set1_epi8(needle as i8);
+        let mut i = 0usize;
+
+        while i + 64 <= n {
+            let eq0 = _mm_cmpeq_epi8(_mm_loadu_si128(s.add(i) as *const __m128i), needle_v);
+            let eq1 = _mm_cmpeq_epi8(_mm_loadu_si128(s.add(i + 16) as *const __m128i), needle_v);
+            let eq2 = _mm_cmpeq_epi8(_mm_loadu_si128(s.add(i + 32) as *const __m128i), needle_v);
+            let eq3 = _mm_cmpeq_epi8(_mm_loadu_si128(s.add(i + 48) as *const __m128i), needle_v);
+            let any = _mm_or_si128(_mm_or_si128(eq0, eq1), _mm_or_si128(eq2, eq3));
+
+            if _mm_movemask_epi8(any) == 0 {
+                i += 64;
+                continue;
+            }
+
+            let m0 = _mm_movemask_epi8(eq0);
+            if m0 != 0 {
+                return Some(i + first_set_bit(m0));
+            }
+            let m1 = _mm_movemask_epi8(eq1);
+            if m1 != 0 {
+                return Some(i + 16 + first_set_bit(m1));
+            }
+            let m2 = _mm_movemask_epi8(eq2);
+            if m2 != 0 {
+                return Some(i + 32 + first_set_bit(m2));
+            }
+            let m3 = _mm_movemask_epi8(eq3);
+            return Some(i + 48 + first_set_bit(m3));
+        }
+
+        while i + 16 <= n {
+            let eq = _mm_cmpeq_epi8(_mm_loadu_si128(s.add(i) as *const __m128i), needle_v);
+            let m = _mm_movemask_epi8(eq);
+            if m != 0 {
+                return Some(i + first_set_bit(m));
+            }
+            i += 16;
+        }
+
+        optimized_memchr_scalar_wide(s.add(i), n - i, needle).map(|tail| i + tail)
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn optimized_memrchr_sse2(s: *const u8, n: usize, needle: u8) -> Option<usize> {
+    // SAFETY: Caller guarantees `s` is readable for `n` bytes; every unaligned
+    // 16-byte load starts at or above `s` and ends at or below `s + n`.
+    unsafe {
+        let needle_v = _mm_set1_epi8(needle as i8);
+        let mut i = n;
+
+        while i >= 64 {
+            let base = i - 64;
+            let eq0 = _mm_cmpeq_epi8(_mm_loadu_si128(s.add(base) as *const __m128i), needle_v);
+            let eq1 = _mm_cmpeq_epi8(
+                _mm_loadu_si128(s.add(base + 16) as *const __m128i),
+                needle_v,
+            );
+            let eq2 = _mm_cmpeq_epi8(
+                _mm_loadu_si128(s.add(base + 32) as *const __m128i),
+                needle_v,
+            );
+            let eq3 = _mm_cmpeq_epi8(
+                _mm_loadu_si128(s.add(base + 48) as *const __m128i),
+                needle_v,
+            );
+            let any = _mm_or_si128(_mm_or_si128(eq0, eq1), _mm_or_si128(eq2, eq3));
+
+            if _mm_movemask_epi8(any) == 0 {
+                i = base;
+                continue;
+            }
+
+            let m3 = _mm_movemask_epi8(eq3);
+            if m3 != 0 {
+                return Some(base + 48 + last_set_bit(m3));
+            }
+            let m2 = _mm_movemask_epi8(eq2);
+            if m2 != 0 {
+                return Some(base + 32 + last_set_bit(m2));
+            }
+            let m1 = _mm_movemask_epi8(eq1);
+            if m1 != 0 {
+                return Some(base + 16 + last_set_bit(m1));
+            }
+            let m0 = _mm_movemask_epi8(eq0);
+            return Some(base + last_set_bit(m0));
+        }
+
+        while i >= 16 {
+            let base = i - 16;
+            let eq = _mm_cmpeq_epi8(_mm_loadu_si128(s.add(base) as *const __m128i), needle_v);
+            let m = _mm_movemask_epi8(eq);
+            if m != 0 {
+                return Some(base + last_set_bit(m));
+            }
+            i = base;
+        }
+
+        optimized_memrchr_scalar_wide(s, i, needle)
+    }
+}
+
+/// Opmask selecting the low `n` byte lanes of a 512-bit vector (`n <= 64`).
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+fn low_lanes_mask64(n: usize) -> __mmask64 {
+    if n >= 64 { u64::MAX } else { (1u64 << n) - 1 }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512bw,avx512vl")]
+unsafe fn optimized_memchr_avx512(s: *const u8, n: usize, needle: u8) -> Option<usize> {
+    let needle_v = _mm512_set1_epi8(needle as i8);
+    let mut i = 0usize;
+
+    // SAFETY: Caller guarantees `s` is readable for `n` bytes. Full loads stay
+    // below `n`; the tail uses a masked load, which never touches (or faults
+    // on) lanes outside the mask.
+    unsafe {
+        while i + 256 <= n {
+            let p = s.add(i);
+            let m0 = _mm512_cmpeq_epi8_mask(_mm512_loadu_si512(p as *const __m512i), needle_v);
+            let m1 =
+                _mm512_cmpeq_epi8_mask(_mm512_loadu_si512(p.add(64) as *const __m512i), needle_v);
+            let m2 =
+                _mm512_cmpeq_epi8_mask(_mm512_loadu_si512(p.add(128) as *const __m512i), needle_v);
+            let m3 =
+                _mm512_cmpeq_epi8_mask(_mm512_loadu_si512(p.add(192) as *const __m512i), needle_v);
+
+            if (m0 | m1 | m2 | m3) != 0 {
+                if m0 != 0 {
+                    return Some(i + m0.trailing_zeros() as usize);
+                }
+                if m1 != 0 {
+                    return Some(i + 64 + m1.trailing_zeros() as usize);
+                }
+                if m2 != 0 {
+                    return Some(i + 128 + m2.trailing_zeros() as usize);
+                }
+                return Some(i + 192 + m3.trailing_zeros() as usize);
+            }
+            i += 256;
+        }
+
+        while i + 64 <= n {
+            let v = _mm512_loadu_si512(s.add(i) as *const __m512i);
+            let m = _mm512_cmpeq_epi8_mask(v, needle_v);
+            if m != 0 {
+                return Some(i + m.trailing_zeros() as usize);
+            }
+            i += 64;
+        }
+
+        if i < n {
+            let k = low_lanes_mask64(n - i);
+            let v = _mm512_maskz_loadu_epi8(k, s.add(i) as *const i8);
+            let m = _mm512_mask_cmpeq_epi8_mask(k, v, needle_v);
+            if m != 0 {
+                return Some(i + m.trailing_zeros() as usize);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512bw,avx512vl")]
+unsafe fn optimized_memrchr_avx512(s: *const u8, n: usize, needle: u8) -> Option<usize> {
+    let needle_v = _mm512_set1_epi8(needle as i8);
+    let mut i = n;
+
+    // SAFETY: Caller guarantees `s` is readable for `n` bytes. Blocks descend
+    // from `n`, and the leading partial block uses a fault-suppressing masked
+    // load.
+    unsafe {
+        while i >= 256 {
+            let base = i - 256;
+            let p = s.add(base);
+            let m0 = _mm512_cmpeq_epi8_mask(_mm512_loadu_si512(p as *const __m512i), needle_v);
+            let m1 =
+                _mm512_cmpeq_epi8_mask(_mm512_loadu_si512(p.add(64) as *const __m512i), needle_v);
+            let m2 =
+                _mm512_cmpeq_epi8_mask(_mm512_loadu_si512(p.add(128) as *const __m512i), needle_v);
+            let m3 =
+                _mm512_cmpeq_epi8_mask(_mm512_loadu_si512(p.add(192) as *const __m512i), needle_v);
+
+            if (m0 | m1 | m2 | m3) != 0 {
+                if m3 != 0 {
+                    return Some(base + 192 + 63 - m3.leading_zeros() as usize);
+                }
+                if m2 != 0 {
+                    return Some(base + 128 + 63 - m2.leading_zeros() as usize);
+                }
+                if m1 != 0 {
+                    return Some(base + 64 + 63 - m1.leading_zeros() as usize);
+                }
+                return Some(base + 63 - m0.leading_zeros() as usize);
+            }
+            i = base;
+        }
+
+        while i >= 64 {
+            i -= 64;
+            let v = _mm512_loadu_si512(s.add(i) as *const __m512i);
+            let m = _mm512_cmpeq_epi8_mask(v, needle_v);
+            if m != 0 {
+                return Some(i + 63 - m.leading_zeros() as usize);
+            }
+        }
+
+        if i > 0 {
+            let k = low_lanes_mask64(i);
+            let v = _mm512_maskz_loadu_epi8(k, s as *const i8);
+            let m = _mm512_mask_cmpeq_epi8_mask(k, v, needle_v);
+            if m != 0 {
+                return Some(63 - m.leading_zeros() as usize);
+            }
+        }
+    }
+
+    None
+}
+
 #[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn optimized_memchr_avx2(s: *const u8, n: usize, needle: u8) -> Option<usize> {
@@ -569,4 +875,64 @@ mod tests {
             }
         }
     }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_memchr_memrchr_every_isa_level() {
+        let base = seeded_buf();
+
+        for level in cpu::supported_levels() {
+            let fwd = select_memchr_scan(level);
+            let rev = select_memrchr_scan(level);
+            for n in 1..=300usize {
+                assert_eq!(
+                    unsafe { fwd(base.as_ptr(), n, NEEDLE) },
+                    None,
+                    "{level:?} n={n}"
+                );
+                assert_eq!(
+                    unsafe { rev(base.as_ptr(), n, NEEDLE) },
+                    None,
+                    "{level:?} n={n}"
+                );
+
+                for pos in [0, n / 3, n / 2, n - 1] {
+                    let mut local = base;
+                    local[pos] = NEEDLE;
+                    assert_eq!(
+                        unsafe { fwd(local.as_ptr(), n, NEEDLE) },
+                        Some(pos),
+                        "{level:?} memchr n={n} pos={pos}"
+                    );
+                    assert_eq!(
+                        unsafe { rev(local.as_ptr(), n, NEEDLE) },
+                        Some(pos),
+                        "{level:?} memrchr n={n} pos={pos}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_memchr_memrchr_every_isa_level_ignore_bytes_outside_range() {
+        let mut buf = [NEEDLE; 400];
+        for level in cpu::supported_levels() {
+            let fwd = select_memchr_scan(level);
+            let rev = select_memrchr_scan(level);
+            for n in 1..=300usize {
+                // Needles sit just before and just after `buf[100..100 + n]`.
+                buf.fill(NEEDLE);
+                buf[100..100 + n].fill(0);
+                let p = buf[100..].as_ptr();
+                assert_eq!(unsafe { fwd(p, n, NEEDLE) }, None, "{level:?} memchr n={n}");
+                assert_eq!(
+                    unsafe { rev(p, n, NEEDLE) },
+                    None,
+                    "{level:?} memrchr n={n}"
+                );
+            }
+        }
+    }
 }
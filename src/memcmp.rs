@@ -1,8 +1,21 @@
-//! Optimized memcmp with AVX2 scanning.
+//! Optimized memcmp with AVX2 scanning and a portable word-at-a-time fallback.
 #![allow(unsafe_code)]
 
+#[cfg(target_arch = "x86_64")]
 use core::arch::x86_64::*;
 
+#[cfg(target_arch = "x86_64")]
+use crate::cpu::{self, IsaLevel};
+#[cfg(target_arch = "x86_64")]
+use crate::dispatch::FnSlot;
+
+#[cfg(target_arch = "x86_64")]
+type CmpFn = unsafe fn(*const u8, *const u8, usize) -> i32;
+
+/// Kernel used for comparisons of 32 bytes and up, chosen on first use.
+#[cfg(target_arch = "x86_64")]
+static MEMCMP_LARGE: FnSlot<CmpFn> = FnSlot::new(resolve_memcmp_large as *mut ());
+
 /// High-performance memcmp over exactly `n` bytes.
 ///
 /// Returns:
@@ -15,28 +28,65 @@ use core::arch::x86_64::*;
 /// - `s1` and `s2` must be valid for reads of `n` bytes.
 #[inline(always)]
 pub unsafe fn optimized_memcmp_unified(s1: *const u8, s2: *const u8, n: usize) -> i32 {
-    if n <= 31 {
-        return unsafe { optimized_memcmp_scalar_wide(s1, s2, n) };
-    }
+    #[cfg(feature = "instrument")]
+    crate::instrument::record(crate::instrument::Routine::Memcmp, n, s1 as usize);
 
-    if n <= 64 {
-        #[cfg(target_arch = "x86_64")]
-        {
-            return unsafe { optimized_memcmp_32_to_64(s1, s2, n) };
+    // SAFETY: Forwarded caller contract.
+    unsafe { memcmp_dispatched(s1, s2, n) }
+}
+
+/// `optimized_memcmp_unified` without instrumentation, for callers that
+/// record the call under their own routine.
+#[inline(always)]
+pub(crate) unsafe fn memcmp_dispatched(s1: *const u8, s2: *const u8, n: usize) -> i32 {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if n <= 31 {
+            unsafe { optimized_memcmp_scalar_wide(s1, s2, n) }
+        } else {
+            unsafe { (MEMCMP_LARGE.get())(s1, s2, n) }
         }
     }
 
-    #[cfg(target_arch = "x86_64")]
+    #[cfg(all(not(target_arch = "x86_64"), feature = "std-simd"))]
     {
-        return unsafe { optimized_memcmp_avx2(s1, s2, n) };
+        unsafe { crate::stdsimd::memcmp(s1, s2, n) }
     }
 
-    #[cfg(not(target_arch = "x86_64"))]
+    #[cfg(all(not(target_arch = "x86_64"), not(feature = "std-simd")))]
     {
-        unsafe { optimized_memcmp_scalar(s1, s2, n) }
+        unsafe { optimized_memcmp_scalar_wide(s1, s2, n) }
     }
 }
 
+#[cfg(target_arch = "x86_64")]
+fn select_memcmp_large(level: IsaLevel) -> CmpFn {
+    match level {
+        IsaLevel::Avx512 => optimized_memcmp_avx512,
+        IsaLevel::Avx2 => optimized_memcmp_avx2_dispatch,
+        IsaLevel::Sse42 | IsaLevel::Sse2 => optimized_memcmp_sse2,
+        IsaLevel::Scalar => optimized_memcmp_scalar_wide,
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn resolve_memcmp_large(s1: *const u8, s2: *const u8, n: usize) -> i32 {
+    let f = select_memcmp_large(cpu::isa_level());
+    MEMCMP_LARGE.set(f);
+    // SAFETY: Forwarded caller contract.
+    unsafe { f(s1, s2, n) }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn optimized_memcmp_avx2_dispatch(s1: *const u8, s2: *const u8, n: usize) -> i32 {
+    if n <= 64 {
+        return unsafe { optimized_memcmp_32_to_64(s1, s2, n) };
+    }
+    unsafe { optimized_memcmp_avx2(s1, s2, n) }
+}
+
+#[cfg(any(target_arch = "x86_64", not(feature = "std-simd")))]
 unsafe fn optimized_memcmp_scalar_wide(s1: *const u8, s2: *const u8, n: usize) -> i32 {
     let mut i = 0usize;
 
@@ -69,6 +119,99 @@ unsafe fn optimized_memcmp_scalar_wide(s1: *const u8, s2: *const u8, n: usize) -
     0
 }
 
+#[cfg(target_arch = "x86_64")]
+unsafe fn optimized_memcmp_sse2(s1: *const u8, s2: *const u8, n: usize) -> i32 {
+    let mut i = 0usize;
+
+    // SAFETY: Caller guarantees `s1`/`s2` are readable for `n` bytes; every
+    // unaligned 16-byte load stays below `n`.
+    unsafe {
+        while i + 16 <= n {
+            let a = _mm_loadu_si128(s1.add(i) as *const __m128i);
+            let b = _mm_loadu_si128(s2.add(i) as *const __m128i);
+            let mask = _mm_movemask_epi8(_mm_cmpeq_epi8(a, b)) as u32;
+            if mask != 0xFFFF {
+                let idx = i + (!mask).trailing_zeros() as usize;
+                return (*s1.add(idx) as i32) - (*s2.add(idx) as i32);
+            }
+            i += 16;
+        }
+
+        optimized_memcmp_scalar_wide(s1.add(i), s2.add(i), n - i)
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512bw,avx512vl")]
+unsafe fn optimized_memcmp_avx512(s1: *const u8, s2: *const u8, n: usize) -> i32 {
+    let mut i = 0usize;
+
+    // SAFETY: Caller guarantees `s1`/`s2` are readable for `n` bytes. Full
+    // loads stay below `n`; the tail uses fault-suppressing masked loads.
+    unsafe {
+        while i + 256 <= n {
+            let x0 = _mm512_xor_si512(
+                _mm512_loadu_si512(s1.add(i) as *const __m512i),
+                _mm512_loadu_si512(s2.add(i) as *const __m512i),
+            );
+            let x1 = _mm512_xor_si512(
+                _mm512_loadu_si512(s1.add(i + 64) as *const __m512i),
+                _mm512_loadu_si512(s2.add(i + 64) as *const __m512i),
+            );
+            let x2 = _mm512_xor_si512(
+                _mm512_loadu_si512(s1.add(i + 128) as *const __m512i),
+                _mm512_loadu_si512(s2.add(i + 128) as *const __m512i),
+            );
+            let x3 = _mm512_xor_si512(
+                _mm512_loadu_si512(s1.add(i + 192) as *const __m512i),
+                _mm512_loadu_si512(s2.add(i + 192) as *const __m512i),
+            );
+            let any = _mm512_or_si512(_mm512_or_si512(x0, x1), _mm512_or_si512(x2, x3));
+            if _mm512_test_epi64_mask(any, any) != 0 {
+                // Byte-granular nonzero masks, tested in address order.
+                for (block, x) in [x0, x1, x2, x3].into_iter().enumerate() {
+                    let ne = _mm512_test_epi8_mask(x, x);
+                    if ne != 0 {
+                        let idx = i + block * 64 + ne.trailing_zeros() as usize;
+                        return (*s1.add(idx) as i32) - (*s2.add(idx) as i32);
+                    }
+                }
+            }
+            i += 256;
+        }
+
+        while i + 64 <= n {
+            let a = _mm512_loadu_si512(s1.add(i) as *const __m512i);
+            let b = _mm512_loadu_si512(s2.add(i) as *const __m512i);
+            let ne = _mm512_cmpneq_epi8_mask(a, b);
+            if ne != 0 {
+                let idx = i + ne.trailing_zeros() as usize;
+                return (*s1.add(idx) as i32) - (*s2.add(idx) as i32);
+            }
+            i += 64;
+        }
+
+        if i < n {
+            let rem = n - i;
+            let k = if rem >= 64 {
+                u64::MAX
+            } else {
+                (1u64 << rem) - 1
+            };
+            let a = _mm512_maskz_loadu_epi8(k, s1.add(i) as *const i8);
+            let b = _mm512_maskz_loadu_epi8(k, s2.add(i) as *const i8);
+            let ne = _mm512_mask_cmpneq_epi8_mask(k, a, b);
+            if ne != 0 {
+                let idx = i + ne.trailing_zeros() as usize;
+                return (*s1.add(idx) as i32) - (*s2.add(idx) as i32);
+            }
+        }
+    }
+
+    0
+}
+
+#[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn first_diff_32(s1: *const u8, s2: *const u8) -> usize {
     let a = _mm256_loadu_si256(s1 as *const __m256i);
@@ -106,6 +249,7 @@ unsafe fn optimized_memcmp_32_to_64(s1: *const u8, s2: *const u8, n: usize) -> i
     0
 }
 
+#[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn optimized_memcmp_avx2(s1: *const u8, s2: *const u8, n: usize) -> i32 {
     let mut i = 0usize;
@@ -240,4 +384,37 @@ mod tests {
             }
         }
     }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_memcmp_every_isa_level() {
+        let mut a = [0u8; 1100];
+        for (i, byte) in a.iter_mut().enumerate() {
+            *byte = (i % 251) as u8;
+        }
+
+        for level in cpu::supported_levels() {
+            let kernel = select_memcmp_large(level);
+            for n in [
+                32usize, 33, 47, 63, 64, 65, 127, 128, 129, 255, 256, 257, 1024,
+            ] {
+                let mut b = a;
+                assert_eq!(
+                    unsafe { kernel(a.as_ptr(), b.as_ptr(), n) },
+                    0,
+                    "{level:?} n={n}"
+                );
+                for pos in [0, n / 2, n - 1] {
+                    b = a;
+                    b[pos] ^= 0x5A;
+                    let got = unsafe { kernel(a.as_ptr(), b.as_ptr(), n) };
+                    assert_eq!(
+                        got,
+                        ref_memcmp(&a[..n], &b[..n]),
+                        "{level:?} n={n} pos={pos}"
+                    );
+                }
+            }
+        }
+    }
 }
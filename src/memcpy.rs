@@ -1,33 +1,123 @@
 //! Optimized memcpy with AVX2 and Non-Temporal dispatch
 #![allow(unsafe_code)]
 
+#[cfg(target_arch = "x86_64")]
 use core::arch::x86_64::*;
 
+#[cfg(target_arch = "x86_64")]
+use crate::cpu::{self, IsaLevel};
+#[cfg(target_arch = "x86_64")]
+use crate::dispatch::FnSlot;
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) type CopyFn = unsafe fn(*mut u8, *const u8, usize) -> *mut u8;
+
+/// Kernel used for copies above the inline small path, chosen on first use.
+#[cfg(target_arch = "x86_64")]
+pub(crate) static MEMCPY_LARGE: FnSlot<CopyFn> = FnSlot::new(resolve_memcpy_large as *mut ());
+
 /// High-performance memcpy with automatic dispatch.
 /// This entry point is NOT marked with AVX2 to ensure that 0-64 byte copies
 /// never trigger AVX power-up latency (the "AVX Entry Fee").
 ///
+/// Copies above the small path go through a cached function pointer selected
+/// from the host's CPU features, so this is safe to call on any x86_64 CPU.
+/// Other targets use the portable word-at-a-time copy.
+///
 /// # Safety
 ///
 /// - `dest` and `src` must be valid for reads/writes of `n` bytes
 /// - The memory regions must not overlap
-/// - AVX2 must be supported if the AVX2 path is taken
 #[inline(always)]
 pub unsafe fn optimized_memcpy_unified(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
-    if n <= 62 {
-        // SSE/Scalar path: Legacy SSE encoding, no transition penalty.
-        // Handles up to 64 bytes to avoid AVX entry fee for cache-line sized moves.
-        optimized_memcpy_sse_small(dest, src, n)
-    } else {
-        // AVX path: Dispatches to specialized AVX2/NT logic.
-        optimized_memcpy_avx_dispatch(dest, src, n)
+    #[cfg(feature = "instrument")]
+    crate::instrument::record(crate::instrument::Routine::Memcpy, n, dest as usize);
+
+    // SAFETY: Forwarded caller contract.
+    unsafe { memcpy_dispatched(dest, src, n) }
+}
+
+/// `optimized_memcpy_unified` without instrumentation, for callers that
+/// record the call under their own routine.
+#[inline(always)]
+pub(crate) unsafe fn memcpy_dispatched(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if n <= 62 && !cpu::PORTABLE {
+            // SSE/Scalar path: Legacy SSE encoding, no transition penalty.
+            // Handles up to 64 bytes to avoid AVX entry fee for cache-line sized moves.
+            unsafe { optimized_memcpy_sse_small(dest, src, n) }
+        } else {
+            // Dispatched path: AVX-512/AVX2/NT logic when available, SSE2 loop otherwise.
+            unsafe { (MEMCPY_LARGE.get())(dest, src, n) }
+        }
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        unsafe { optimized_memcpy_scalar_wide(dest, src, n) }
     }
 }
 
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn select_memcpy_large(level: IsaLevel) -> CopyFn {
+    match level {
+        IsaLevel::Avx512 => optimized_memcpy_avx512_dispatch,
+        IsaLevel::Avx2 => optimized_memcpy_avx_dispatch,
+        IsaLevel::Sse42 | IsaLevel::Sse2 => optimized_memcpy_sse2,
+        IsaLevel::Scalar => optimized_memcpy_scalar_wide,
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn resolve_memcpy_large(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    let f = select_memcpy_large(cpu::isa_level());
+    MEMCPY_LARGE.set(f);
+    // SAFETY: Forwarded caller contract.
+    unsafe { f(dest, src, n) }
+}
+
+// =============================================================================
+// PORTABLE PATH: word-at-a-time copy for non-x86 targets
+// =============================================================================
+
+unsafe fn optimized_memcpy_scalar_wide(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    // SAFETY: Caller guarantees `src`/`dest` are valid for `n` bytes and
+    // non-overlapping; unaligned word accesses accept any alignment.
+    unsafe {
+        let mut i = 0usize;
+
+        while i + 32 <= n {
+            let a = core::ptr::read_unaligned(src.add(i) as *const u64);
+            let b = core::ptr::read_unaligned(src.add(i + 8) as *const u64);
+            let c = core::ptr::read_unaligned(src.add(i + 16) as *const u64);
+            let d = core::ptr::read_unaligned(src.add(i + 24) as *const u64);
+            core::ptr::write_unaligned(dest.add(i) as *mut u64, a);
+            core::ptr::write_unaligned(dest.add(i + 8) as *mut u64, b);
+            core::ptr::write_unaligned(dest.add(i + 16) as *mut u64, c);
+            core::ptr::write_unaligned(dest.add(i + 24) as *mut u64, d);
+            i += 32;
+        }
+
+        while i + 8 <= n {
+            let w = core::ptr::read_unaligned(src.add(i) as *const u64);
+            core::ptr::write_unaligned(dest.add(i) as *mut u64, w);
+            i += 8;
+        }
+
+        while i < n {
+            *dest.add(i) = *src.add(i);
+            i += 1;
+        }
+    }
+    dest
+}
+
 // =============================================================================
 // SMALL PATH: SSE2 Implementation (0-64 bytes)
 // =============================================================================
 
+#[cfg(target_arch = "x86_64")]
 #[inline(always)]
 unsafe fn optimized_memcpy_sse_small(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
     // n < 64 guaranteed by dispatch
@@ -101,6 +191,41 @@ unsafe fn optimized_memcpy_sse_small(dest: *mut u8, src: *const u8, n: usize) ->
     dest
 }
 
+// =============================================================================
+// SSE2 FALLBACK: 63+ bytes on CPUs without AVX2
+// =============================================================================
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn optimized_memcpy_sse2(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    // SAFETY: Unaligned SSE2 loads/stores are valid for any alignment; caller
+    // guarantees `src`/`dest` are valid for `n` bytes and non-overlapping.
+    unsafe {
+        let mut d = dest;
+        let mut s = src;
+        let mut rem = n;
+
+        while rem >= 64 {
+            let v0 = _mm_loadu_si128(s as *const __m128i);
+            let v1 = _mm_loadu_si128(s.add(16) as *const __m128i);
+            let v2 = _mm_loadu_si128(s.add(32) as *const __m128i);
+            let v3 = _mm_loadu_si128(s.add(48) as *const __m128i);
+            _mm_storeu_si128(d as *mut __m128i, v0);
+            _mm_storeu_si128(d.add(16) as *mut __m128i, v1);
+            _mm_storeu_si128(d.add(32) as *mut __m128i, v2);
+            _mm_storeu_si128(d.add(48) as *mut __m128i, v3);
+            d = d.add(64);
+            s = s.add(64);
+            rem -= 64;
+        }
+
+        if rem > 0 {
+            optimized_memcpy_sse_small(d, s, rem);
+        }
+    }
+    dest
+}
+
+#[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn copy_tail_avx2(mut d: *mut u8, mut s: *const u8, mut rem: usize) {
     while rem >= 32 {
@@ -148,6 +273,7 @@ unsafe fn copy_tail_avx2(mut d: *mut u8, mut s: *const u8, mut rem: usize) {
     }
 }
 
+#[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn copy_256_avx2(d: *mut u8, s: *const u8) {
     let v0 = _mm256_loadu_si256(s as *const __m256i);
@@ -168,6 +294,7 @@ unsafe fn copy_256_avx2(d: *mut u8, s: *const u8) {
     _mm256_storeu_si256(d.add(224) as *mut __m256i, v7);
 }
 
+#[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn copy_64_avx2(d: *mut u8, s: *const u8) {
     let v0 = _mm256_loadu_si256(s as *const __m256i);
@@ -176,6 +303,7 @@ unsafe fn copy_64_avx2(d: *mut u8, s: *const u8) {
     _mm256_storeu_si256(d.add(32) as *mut __m256i, v1);
 }
 
+#[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn copy_63_avx2(d: *mut u8, s: *const u8) {
     let v0 = _mm256_loadu_si256(s as *const __m256i);
@@ -188,13 +316,10 @@ unsafe fn copy_63_avx2(d: *mut u8, s: *const u8) {
 // AVX DISPATCHER: Centralizes AVX state and manages VZEROUPPER
 // =============================================================================
 
-// Non-temporal stores regress around the multi-MiB transition on current
-// targets; keep NT for very large copies only.
-const NT_THRESHOLD: usize = 16 * 1024 * 1024;
-
+#[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn optimized_memcpy_avx_dispatch(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
-    if n < NT_THRESHOLD {
+    if n < cpu::memcpy_nt_threshold() {
         optimized_memcpy_avx2_unaligned(dest, src, n);
     } else {
         optimized_memcpy_avx2_nt(dest, src, n);
@@ -207,6 +332,7 @@ unsafe fn optimized_memcpy_avx_dispatch(dest: *mut u8, src: *const u8, n: usize)
 // MEDIUM PATH: AVX2 Unaligned Overlapping
 // =============================================================================
 
+#[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn optimized_memcpy_avx2_unaligned(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
     if n == 63 {
@@ -348,6 +474,7 @@ unsafe fn optimized_memcpy_avx2_unaligned(dest: *mut u8, src: *const u8, n: usiz
     dest
 }
 
+#[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn optimized_memcpy_avx2_nt(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
     let mut d = dest;
@@ -401,9 +528,179 @@ unsafe fn optimized_memcpy_avx2_nt(dest: *mut u8, src: *const u8, n: usize) -> *
     dest
 }
 
+// =============================================================================
+// AVX-512 PATH: 64-byte ZMM moves with masked tails
+// =============================================================================
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512bw,avx512vl")]
+unsafe fn optimized_memcpy_avx512_dispatch(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    // SAFETY: Caller guarantees `src`/`dest` are valid for `n` bytes and
+    // non-overlapping.
+    unsafe {
+        if n <= 256 {
+            copy_upto_256_avx512(dest, src, n);
+        } else if n < cpu::memcpy_nt_threshold() {
+            optimized_memcpy_avx512_unaligned(dest, src, n);
+        } else {
+            optimized_memcpy_avx512_nt(dest, src, n);
+        }
+    }
+
+    dest
+}
+
+/// Copies 0-256 bytes with at most four (possibly overlapping or masked) ZMM
+/// moves; every load is issued before the first store.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512bw,avx512vl")]
+unsafe fn copy_upto_256_avx512(d: *mut u8, s: *const u8, n: usize) {
+    // SAFETY: Caller guarantees `n <= 256` and that `s`/`d` are valid for `n`
+    // bytes; masked moves do not touch lanes outside the mask.
+    unsafe {
+        if n < 64 {
+            let k = if n == 0 { 0 } else { u64::MAX >> (64 - n) };
+            let v = _mm512_maskz_loadu_epi8(k, s as *const i8);
+            _mm512_mask_storeu_epi8(d as *mut i8, k, v);
+        } else if n <= 128 {
+            let v0 = _mm512_loadu_si512(s as *const __m512i);
+            let v1 = _mm512_loadu_si512(s.add(n - 64) as *const __m512i);
+            _mm512_storeu_si512(d as *mut __m512i, v0);
+            _mm512_storeu_si512(d.add(n - 64) as *mut __m512i, v1);
+        } else {
+            let v0 = _mm512_loadu_si512(s as *const __m512i);
+            let v1 = _mm512_loadu_si512(s.add(64) as *const __m512i);
+            let v2 = _mm512_loadu_si512(s.add(n - 128) as *const __m512i);
+            let v3 = _mm512_loadu_si512(s.add(n - 64) as *const __m512i);
+            _mm512_storeu_si512(d as *mut __m512i, v0);
+            _mm512_storeu_si512(d.add(64) as *mut __m512i, v1);
+            _mm512_storeu_si512(d.add(n - 128) as *mut __m512i, v2);
+            _mm512_storeu_si512(d.add(n - 64) as *mut __m512i, v3);
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512bw,avx512vl")]
+unsafe fn optimized_memcpy_avx512_unaligned(dest: *mut u8, src: *const u8, n: usize) {
+    // SAFETY: Caller guarantees `n > 256` and that `src`/`dest` are valid for
+    // `n` bytes and non-overlapping.
+    unsafe {
+        // Head: one unaligned store, then advance `d` to a 64-byte boundary so
+        // the main loop never splits a cache line on the store side.
+        let head = _mm512_loadu_si512(src as *const __m512i);
+        _mm512_storeu_si512(dest as *mut __m512i, head);
+        let advance = 64 - ((dest as usize) & 63);
+        let mut d = dest.add(advance);
+        let mut s = src.add(advance);
+        let mut rem = n - advance;
+
+        while rem > 256 {
+            let v0 = _mm512_loadu_si512(s as *const __m512i);
+            let v1 = _mm512_loadu_si512(s.add(64) as *const __m512i);
+            let v2 = _mm512_loadu_si512(s.add(128) as *const __m512i);
+            let v3 = _mm512_loadu_si512(s.add(192) as *const __m512i);
+            _mm512_store_si512(d as *mut __m512i, v0);
+            _mm512_store_si512(d.add(64) as *mut __m512i, v1);
+            _mm512_store_si512(d.add(128) as *mut __m512i, v2);
+            _mm512_store_si512(d.add(192) as *mut __m512i, v3);
+            d = d.add(256);
+            s = s.add(256);
+            rem -= 256;
+        }
+
+        // Tail: the last 256 bytes of the buffer, overlapping the loop output.
+        copy_last_256_avx512(dest.add(n), src.add(n));
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512bw,avx512vl")]
+unsafe fn optimized_memcpy_avx512_nt(dest: *mut u8, src: *const u8, n: usize) {
+    // SAFETY: Caller guarantees `n >= cpu::memcpy_nt_threshold()` and that `src`/`dest` are
+    // valid for `n` bytes and non-overlapping.
+    unsafe {
+        let head = _mm512_loadu_si512(src as *const __m512i);
+        _mm512_storeu_si512(dest as *mut __m512i, head);
+        let advance = 64 - ((dest as usize) & 63);
+        let mut d = dest.add(advance);
+        let mut s = src.add(advance);
+        let mut rem = n - advance;
+
+        // Non-temporal stores require 64-byte alignment, which `d` has.
+        while rem > 256 {
+            let v0 = _mm512_loadu_si512(s as *const __m512i);
+            let v1 = _mm512_loadu_si512(s.add(64) as *const __m512i);
+            let v2 = _mm512_loadu_si512(s.add(128) as *const __m512i);
+            let v3 = _mm512_loadu_si512(s.add(192) as *const __m512i);
+            _mm512_stream_si512(d as *mut __m512i, v0);
+            _mm512_stream_si512(d.add(64) as *mut __m512i, v1);
+            _mm512_stream_si512(d.add(128) as *mut __m512i, v2);
+            _mm512_stream_si512(d.add(192) as *mut __m512i, v3);
+            d = d.add(256);
+            s = s.add(256);
+            rem -= 256;
+        }
+
+        // Order the NT stores before the cached tail and before returning.
+        _mm_sfence();
+        copy_last_256_avx512(dest.add(n), src.add(n));
+    }
+}
+
+/// Copies the 256 bytes ending at `d_end`/`s_end`.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512bw,avx512vl")]
+unsafe fn copy_last_256_avx512(d_end: *mut u8, s_end: *const u8) {
+    // SAFETY: Caller guarantees the 256 bytes before each end pointer are valid.
+    unsafe {
+        let v0 = _mm512_loadu_si512(s_end.sub(256) as *const __m512i);
+        let v1 = _mm512_loadu_si512(s_end.sub(192) as *const __m512i);
+        let v2 = _mm512_loadu_si512(s_end.sub(128) as *const __m512i);
+        let v3 = _mm512_loadu_si512(s_end.sub(64) as *const __m512i);
+        _mm512_storeu_si512(d_end.sub(256) as *mut __m512i, v0);
+        _mm512_storeu_si512(d_end.sub(192) as *mut __m512i, v1);
+        _mm512_storeu_si512(d_end.sub(128) as *mut __m512i, v2);
+        _mm512_storeu_si512(d_end.sub(64) as *mut __m512i, v3);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::vec;
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_memcpy_every_isa_level() {
+        let mut src = vec![0u8; 8192];
+        for (i, byte) in src.iter_mut().enumerate() {
+            *byte = (i % 251) as u8;
+        }
+
+        for level in cpu::supported_levels() {
+            let kernel = select_memcpy_large(level);
+            for off in 0..32 {
+                for n in [
+                    63usize, 64, 65, 127, 128, 129, 255, 256, 257, 1023, 1024, 1025, 4096,
+                ] {
+                    let mut dst = vec![0u8; 8192];
+                    unsafe {
+                        kernel(dst.as_mut_ptr().add(off), src.as_ptr().add(off), n);
+                    }
+                    assert_eq!(
+                        &dst[off..off + n],
+                        &src[off..off + n],
+                        "{level:?} failed at size {n} off {off}"
+                    );
+                    assert!(
+                        dst[off + n..].iter().all(|&b| b == 0),
+                        "{level:?} overwrote at size {n}"
+                    );
+                }
+            }
+        }
+    }
 
     #[test]
     fn test_memcpy_optimized_0_to_1024() {
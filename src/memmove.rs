@@ -1,74 +1,234 @@
-//! Optimized memmove with overlap-aware AVX2 paths.
+//! Optimized memmove with overlap-aware AVX2 paths and a portable fallback.
 #![allow(unsafe_code)]
 
+#[cfg(target_arch = "x86_64")]
 use core::arch::asm;
+#[cfg(target_arch = "x86_64")]
 use core::arch::x86_64::*;
 
-use crate::memcpy::optimized_memcpy_unified;
+#[cfg(target_arch = "x86_64")]
+use crate::cpu::{self, IsaLevel};
+#[cfg(target_arch = "x86_64")]
+use crate::dispatch::FnSlot;
+#[cfg(target_arch = "x86_64")]
+use crate::memcpy::memcpy_dispatched;
 
-// On current post-2020 cores, `rep movsb` is a clear regression for the
-// overlap-focused 1 KiB..256 KiB ranges in our benchmark suite.
-// Keep AVX2 for small/medium/large tested spans and reserve `rep movsb`
-// for very large copies.
-const REP_MOVSB_FWD_THRESHOLD: usize = 1024 * 1024;
-const REP_MOVSB_BWD_THRESHOLD: usize = 1024 * 1024;
+#[cfg(target_arch = "x86_64")]
+pub(crate) type MoveFn = unsafe fn(*mut u8, *const u8, usize) -> *mut u8;
+#[cfg(target_arch = "x86_64")]
+type MoveKernelFn = unsafe fn(*mut u8, *const u8, usize);
+
+/// Kernel used for moves of 64 bytes and up, chosen on first use.
+#[cfg(target_arch = "x86_64")]
+pub(crate) static MEMMOVE_LARGE: FnSlot<MoveFn> = FnSlot::new(resolve_memmove_large as *mut ());
 
 /// High-performance memmove with automatic overlap handling.
 ///
+/// Moves of 64 bytes and up go through a cached function pointer selected
+/// from the host's CPU features, so this is safe to call on any x86_64 CPU.
+/// Other targets use the portable word-at-a-time move.
+///
 /// # Safety
 ///
 /// - `dest` and `src` must be valid for reads/writes of `n` bytes
 /// - Regions may overlap
 #[inline(always)]
 pub unsafe fn optimized_memmove_unified(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    #[cfg(feature = "instrument")]
+    crate::instrument::record_memmove(dest as usize, src as usize, n);
+
+    // SAFETY: Forwarded caller contract.
+    unsafe { memmove_dispatched(dest, src, n) }
+}
+
+/// `optimized_memmove_unified` without instrumentation, for callers that
+/// record the call under their own routine.
+#[inline(always)]
+pub(crate) unsafe fn memmove_dispatched(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
     if n == 0 || core::ptr::eq(dest as *const u8, src) {
         return dest;
     }
 
-    if n == 64 {
-        memmove_64_avx2(dest, src);
-        return dest;
+    #[cfg(target_arch = "x86_64")]
+    {
+        if n < 64 && !cpu::PORTABLE {
+            unsafe { memmove_small_overlap(dest, src, n) };
+            dest
+        } else {
+            unsafe { (MEMMOVE_LARGE.get())(dest, src, n) }
+        }
     }
 
-    if n <= 64 {
-        memmove_small_overlap(dest, src, n);
-        return dest;
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        unsafe { optimized_memmove_scalar_wide(dest, src, n) }
     }
+}
 
-    let d = dest as usize;
-    let s = src as usize;
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn select_memmove_large(level: IsaLevel) -> MoveFn {
+    match level {
+        IsaLevel::Avx512 | IsaLevel::Avx2 => memmove_large_avx2,
+        IsaLevel::Sse42 | IsaLevel::Sse2 => memmove_large_sse2,
+        IsaLevel::Scalar => optimized_memmove_scalar_wide,
+    }
+}
 
-    if d < s {
-        // Non-overlap: defer to memcpy fast path.
-        if s - d >= n {
-            return optimized_memcpy_unified(dest, src, n);
-        }
+#[cfg(target_arch = "x86_64")]
+unsafe fn resolve_memmove_large(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    let f = select_memmove_large(cpu::isa_level());
+    MEMMOVE_LARGE.set(f);
+    // SAFETY: Forwarded caller contract.
+    unsafe { f(dest, src, n) }
+}
 
-        // Overlap where dest < src: copy low-to-high.
-        if n >= REP_MOVSB_FWD_THRESHOLD {
-            rep_movsb_forward(dest, src, n);
+unsafe fn optimized_memmove_scalar_wide(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    // SAFETY: Caller guarantees `dest`/`src` are valid for `n` bytes; the
+    // direction is chosen so stores never clobber unread source bytes.
+    unsafe {
+        if (dest as usize) < (src as usize) {
+            memmove_forward_scalar_wide(dest, src, n);
         } else {
-            memmove_forward_avx2(dest, src, n);
+            memmove_backward_scalar_wide(dest, src, n);
         }
-        return dest;
     }
+    dest
+}
+
+unsafe fn memmove_forward_scalar_wide(dest: *mut u8, src: *const u8, n: usize) {
+    let mut i = 0usize;
+
+    // SAFETY: Each 32-byte block is fully loaded before it is stored, and
+    // with `dest < src` stores never reach source bytes that are still unread.
+    unsafe {
+        while i + 32 <= n {
+            let a = core::ptr::read_unaligned(src.add(i) as *const u64);
+            let b = core::ptr::read_unaligned(src.add(i + 8) as *const u64);
+            let c = core::ptr::read_unaligned(src.add(i + 16) as *const u64);
+            let d = core::ptr::read_unaligned(src.add(i + 24) as *const u64);
+            core::ptr::write_unaligned(dest.add(i) as *mut u64, a);
+            core::ptr::write_unaligned(dest.add(i + 8) as *mut u64, b);
+            core::ptr::write_unaligned(dest.add(i + 16) as *mut u64, c);
+            core::ptr::write_unaligned(dest.add(i + 24) as *mut u64, d);
+            i += 32;
+        }
+
+        while i + 8 <= n {
+            let w = core::ptr::read_unaligned(src.add(i) as *const u64);
+            core::ptr::write_unaligned(dest.add(i) as *mut u64, w);
+            i += 8;
+        }
+
+        while i < n {
+            *dest.add(i) = *src.add(i);
+            i += 1;
+        }
+    }
+}
+
+unsafe fn memmove_backward_scalar_wide(dest: *mut u8, src: *const u8, n: usize) {
+    let mut rem = n;
+
+    // SAFETY: Blocks descend from the end and are fully loaded before they
+    // are stored; with `dest > src` this never clobbers unread source bytes.
+    unsafe {
+        while rem >= 32 {
+            rem -= 32;
+            let a = core::ptr::read_unaligned(src.add(rem) as *const u64);
+            let b = core::ptr::read_unaligned(src.add(rem + 8) as *const u64);
+            let c = core::ptr::read_unaligned(src.add(rem + 16) as *const u64);
+            let d = core::ptr::read_unaligned(src.add(rem + 24) as *const u64);
+            core::ptr::write_unaligned(dest.add(rem) as *mut u64, a);
+            core::ptr::write_unaligned(dest.add(rem + 8) as *mut u64, b);
+            core::ptr::write_unaligned(dest.add(rem + 16) as *mut u64, c);
+            core::ptr::write_unaligned(dest.add(rem + 24) as *mut u64, d);
+        }
+
+        while rem >= 8 {
+            rem -= 8;
+            let w = core::ptr::read_unaligned(src.add(rem) as *const u64);
+            core::ptr::write_unaligned(dest.add(rem) as *mut u64, w);
+        }
+
+        while rem > 0 {
+            rem -= 1;
+            *dest.add(rem) = *src.add(rem);
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn memmove_large_avx2(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    // SAFETY: Only installed when AVX2 is available; caller contract forwarded.
+    unsafe {
+        if n == 64 {
+            memmove_64_avx2(dest, src);
+            return dest;
+        }
+        memmove_overlapping(dest, src, n, memmove_forward_avx2, memmove_backward_avx2)
+    }
+}
 
-    // d > s here.
-    // Non-overlap: defer to memcpy fast path.
-    if d - s >= n {
-        return optimized_memcpy_unified(dest, src, n);
+#[cfg(target_arch = "x86_64")]
+unsafe fn memmove_large_sse2(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    // SAFETY: SSE2 is baseline on x86_64; caller contract forwarded.
+    unsafe {
+        if n == 64 {
+            memmove_small_overlap(dest, src, n);
+            return dest;
+        }
+        memmove_overlapping(dest, src, n, memmove_forward_sse2, memmove_backward_sse2)
     }
+}
 
-    // Overlap where dest > src: copy high-to-low.
-    if n >= REP_MOVSB_BWD_THRESHOLD {
-        rep_movsb_backward(dest, src, n);
-    } else {
-        memmove_backward_avx2(dest, src, n);
+/// Direction selection shared by every ISA level (n > 64).
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+unsafe fn memmove_overlapping(
+    dest: *mut u8,
+    src: *const u8,
+    n: usize,
+    forward: MoveKernelFn,
+    backward: MoveKernelFn,
+) -> *mut u8 {
+    let d = dest as usize;
+    let s = src as usize;
+
+    // SAFETY: Caller guarantees `dest`/`src` are valid for `n` bytes.
+    unsafe {
+        if d < s {
+            // Non-overlap: defer to memcpy fast path.
+            if s - d >= n {
+                return memcpy_dispatched(dest, src, n);
+            }
+
+            // Overlap where dest < src: copy low-to-high.
+            if n >= cpu::rep_movsb_threshold() {
+                rep_movsb_forward(dest, src, n);
+            } else {
+                forward(dest, src, n);
+            }
+            return dest;
+        }
+
+        // d > s here.
+        // Non-overlap: defer to memcpy fast path.
+        if d - s >= n {
+            return memcpy_dispatched(dest, src, n);
+        }
+
+        // Overlap where dest > src: copy high-to-low.
+        if n >= cpu::rep_movsb_threshold().max(cpu::REP_MOVSB_BACKWARD_MIN) {
+            rep_movsb_backward(dest, src, n);
+        } else {
+            backward(dest, src, n);
+        }
     }
 
     dest
 }
 
+#[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn memmove_64_avx2(dest: *mut u8, src: *const u8) {
     let v0 = _mm256_loadu_si256(src as *const __m256i);
@@ -77,6 +237,7 @@ unsafe fn memmove_64_avx2(dest: *mut u8, src: *const u8) {
     _mm256_storeu_si256(dest.add(32) as *mut __m256i, v1);
 }
 
+#[cfg(target_arch = "x86_64")]
 #[inline(always)]
 unsafe fn rep_movsb_forward(dest: *mut u8, src: *const u8, n: usize) {
     let mut d = dest;
@@ -91,6 +252,7 @@ unsafe fn rep_movsb_forward(dest: *mut u8, src: *const u8, n: usize) {
     );
 }
 
+#[cfg(target_arch = "x86_64")]
 #[inline(always)]
 unsafe fn rep_movsb_backward(dest: *mut u8, src: *const u8, n: usize) {
     let mut d = dest.add(n - 1);
@@ -107,6 +269,7 @@ unsafe fn rep_movsb_backward(dest: *mut u8, src: *const u8, n: usize) {
     );
 }
 
+#[cfg(target_arch = "x86_64")]
 #[inline(always)]
 unsafe fn memmove_small_overlap(dest: *mut u8, src: *const u8, n: usize) {
     if n >= 32 {
@@ -158,6 +321,63 @@ unsafe fn memmove_small_overlap(dest: *mut u8, src: *const u8, n: usize) {
     }
 }
 
+#[cfg(target_arch = "x86_64")]
+unsafe fn memmove_forward_sse2(dest: *mut u8, src: *const u8, n: usize) {
+    // SAFETY: Each 64-byte block is fully loaded before it is stored, and
+    // `dest < src`, so stores never clobber unread source bytes.
+    unsafe {
+        let mut d = dest;
+        let mut s = src;
+        let mut rem = n;
+
+        while rem >= 64 {
+            let v0 = _mm_loadu_si128(s as *const __m128i);
+            let v1 = _mm_loadu_si128(s.add(16) as *const __m128i);
+            let v2 = _mm_loadu_si128(s.add(32) as *const __m128i);
+            let v3 = _mm_loadu_si128(s.add(48) as *const __m128i);
+            _mm_storeu_si128(d as *mut __m128i, v0);
+            _mm_storeu_si128(d.add(16) as *mut __m128i, v1);
+            _mm_storeu_si128(d.add(32) as *mut __m128i, v2);
+            _mm_storeu_si128(d.add(48) as *mut __m128i, v3);
+            d = d.add(64);
+            s = s.add(64);
+            rem -= 64;
+        }
+
+        if rem > 0 {
+            memmove_small_overlap(d, s, rem);
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn memmove_backward_sse2(dest: *mut u8, src: *const u8, n: usize) {
+    // SAFETY: Blocks descend from the end and are fully loaded before they
+    // are stored; with `dest > src` this never clobbers unread source bytes.
+    unsafe {
+        let mut rem = n;
+
+        while rem >= 64 {
+            rem -= 64;
+            let s = src.add(rem);
+            let d = dest.add(rem);
+            let v0 = _mm_loadu_si128(s as *const __m128i);
+            let v1 = _mm_loadu_si128(s.add(16) as *const __m128i);
+            let v2 = _mm_loadu_si128(s.add(32) as *const __m128i);
+            let v3 = _mm_loadu_si128(s.add(48) as *const __m128i);
+            _mm_storeu_si128(d as *mut __m128i, v0);
+            _mm_storeu_si128(d.add(16) as *mut __m128i, v1);
+            _mm_storeu_si128(d.add(32) as *mut __m128i, v2);
+            _mm_storeu_si128(d.add(48) as *mut __m128i, v3);
+        }
+
+        if rem > 0 {
+            memmove_small_overlap(dest, src, rem);
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn memmove_forward_avx2(dest: *mut u8, src: *const u8, n: usize) {
     let mut d = dest;
@@ -191,6 +411,7 @@ unsafe fn memmove_forward_avx2(dest: *mut u8, src: *const u8, n: usize) {
     }
 }
 
+#[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn memmove_backward_avx2(dest: *mut u8, src: *const u8, n: usize) {
     let mut rem = n;
@@ -254,6 +475,8 @@ unsafe fn memmove_backward_avx2(dest: *mut u8, src: *const u8, n: usize) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::vec;
+    use std::vec::Vec;
 
     fn make_buf() -> Vec<u8> {
         let mut buf = vec![0u8; 4096];
@@ -264,11 +487,20 @@ mod tests {
     }
 
     fn check_case(src_off: usize, dst_off: usize, n: usize) {
+        check_case_with(optimized_memmove_unified, src_off, dst_off, n);
+    }
+
+    fn check_case_with(
+        f: unsafe fn(*mut u8, *const u8, usize) -> *mut u8,
+        src_off: usize,
+        dst_off: usize,
+        n: usize,
+    ) {
         let mut got = make_buf();
         let mut expected = got.clone();
 
         unsafe {
-            optimized_memmove_unified(got.as_mut_ptr().add(dst_off), got.as_ptr().add(src_off), n);
+            f(got.as_mut_ptr().add(dst_off), got.as_ptr().add(src_off), n);
             core::ptr::copy(
                 expected.as_ptr().add(src_off),
                 expected.as_mut_ptr().add(dst_off),
@@ -316,4 +548,20 @@ mod tests {
             check_case(0, 31, n);
         }
     }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_memmove_every_isa_level() {
+        for level in cpu::supported_levels() {
+            let kernel = select_memmove_large(level);
+            for n in [
+                64usize, 65, 127, 128, 129, 255, 256, 257, 511, 512, 513, 1023,
+            ] {
+                for (src_off, dst_off) in [(0, 2048), (17, 2200), (1, 0), (31, 0), (0, 1), (0, 31)]
+                {
+                    check_case_with(kernel, src_off, dst_off, n);
+                }
+            }
+        }
+    }
 }
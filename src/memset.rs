@@ -1,31 +1,114 @@
 //! Optimized memset with AVX2 and Non-Temporal dispatch
 #![allow(unsafe_code)]
 
+#[cfg(target_arch = "x86_64")]
 use core::arch::x86_64::*;
 
+#[cfg(target_arch = "x86_64")]
+use crate::cpu::{self, IsaLevel};
+#[cfg(target_arch = "x86_64")]
+use crate::dispatch::FnSlot;
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) type SetFn = unsafe fn(*mut u8, u8, usize);
+
+/// Kernel used for fills above the inline small path, chosen on first use.
+#[cfg(target_arch = "x86_64")]
+pub(crate) static MEMSET_LARGE: FnSlot<SetFn> = FnSlot::new(resolve_memset_large as *mut ());
+
 /// High-performance memset with automatic dispatch.
 /// Uses SSE for small sizes to avoid AVX entry fee, AVX2 for medium,
 /// and non-temporal stores for large buffers to avoid cache pollution.
+/// CPUs without AVX2 use an SSE2 loop for everything above 64 bytes, and
+/// non-x86 targets use a portable word-at-a-time fill.
 ///
 /// # Safety
 ///
 /// - `dest` must be valid for writes of `n` bytes
-/// - AVX2 must be supported if the AVX2 path is taken
 #[inline(always)]
 pub unsafe fn optimized_memset_unified(dest: *mut u8, value: u8, n: usize) {
-    if n <= 64 {
-        if n > 0 {
-            memset_sse_small(dest, value, n);
+    #[cfg(feature = "instrument")]
+    crate::instrument::record(crate::instrument::Routine::Memset, n, dest as usize);
+
+    // SAFETY: Forwarded caller contract.
+    unsafe { memset_dispatched(dest, value, n) }
+}
+
+/// `optimized_memset_unified` without instrumentation, for callers that
+/// record the call under their own routine.
+#[inline(always)]
+pub(crate) unsafe fn memset_dispatched(dest: *mut u8, value: u8, n: usize) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if n <= 64 && !cpu::PORTABLE {
+            if n > 0 {
+                unsafe { memset_sse_small(dest, value, n) };
+            }
+            return;
+        }
+        unsafe { (MEMSET_LARGE.get())(dest, value, n) }
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        unsafe { optimized_memset_scalar_wide(dest, value, n) }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn select_memset_large(level: IsaLevel) -> SetFn {
+    match level {
+        IsaLevel::Avx512 => optimized_memset_avx512_dispatch,
+        IsaLevel::Avx2 => optimized_memset_avx_dispatch,
+        IsaLevel::Sse42 | IsaLevel::Sse2 => optimized_memset_sse2,
+        IsaLevel::Scalar => optimized_memset_scalar_wide,
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn resolve_memset_large(dest: *mut u8, value: u8, n: usize) {
+    let f = select_memset_large(cpu::isa_level());
+    MEMSET_LARGE.set(f);
+    // SAFETY: Forwarded caller contract.
+    unsafe { f(dest, value, n) }
+}
+
+// =============================================================================
+// PORTABLE PATH: word-at-a-time fill for non-x86 targets
+// =============================================================================
+
+unsafe fn optimized_memset_scalar_wide(dest: *mut u8, value: u8, n: usize) {
+    let word = u64::from_ne_bytes([value; 8]);
+    let mut i = 0usize;
+
+    // SAFETY: Caller guarantees `dest` is writable for `n` bytes; unaligned
+    // word stores accept any alignment.
+    unsafe {
+        while i + 32 <= n {
+            core::ptr::write_unaligned(dest.add(i) as *mut u64, word);
+            core::ptr::write_unaligned(dest.add(i + 8) as *mut u64, word);
+            core::ptr::write_unaligned(dest.add(i + 16) as *mut u64, word);
+            core::ptr::write_unaligned(dest.add(i + 24) as *mut u64, word);
+            i += 32;
+        }
+
+        while i + 8 <= n {
+            core::ptr::write_unaligned(dest.add(i) as *mut u64, word);
+            i += 8;
+        }
+
+        while i < n {
+            *dest.add(i) = value;
+            i += 1;
         }
-        return;
     }
-    optimized_memset_avx_dispatch(dest, value, n);
 }
 
 // =============================================================================
 // SMALL PATH: SSE2 Implementation (0-64 bytes)
 // =============================================================================
 
+#[cfg(target_arch = "x86_64")]
 #[inline(always)]
 unsafe fn memset_sse_small(dest: *mut u8, value: u8, n: usize) {
     if n >= 32 {
@@ -73,6 +156,36 @@ unsafe fn memset_sse_small(dest: *mut u8, value: u8, n: usize) {
     }
 }
 
+// =============================================================================
+// SSE2 FALLBACK: 65+ bytes on CPUs without AVX2
+// =============================================================================
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn optimized_memset_sse2(dest: *mut u8, value: u8, n: usize) {
+    // n > 64 guaranteed by dispatch, so the overlapping 64-byte tail is in bounds.
+    // SAFETY: Unaligned SSE2 stores are valid for any alignment; caller
+    // guarantees `dest` is writable for `n` bytes.
+    unsafe {
+        let v = _mm_set1_epi8(value as i8);
+        let mut ptr = dest;
+        let end = dest.add(n);
+
+        while (end as usize) - (ptr as usize) > 64 {
+            _mm_storeu_si128(ptr as *mut __m128i, v);
+            _mm_storeu_si128(ptr.add(16) as *mut __m128i, v);
+            _mm_storeu_si128(ptr.add(32) as *mut __m128i, v);
+            _mm_storeu_si128(ptr.add(48) as *mut __m128i, v);
+            ptr = ptr.add(64);
+        }
+
+        _mm_storeu_si128(end.sub(64) as *mut __m128i, v);
+        _mm_storeu_si128(end.sub(48) as *mut __m128i, v);
+        _mm_storeu_si128(end.sub(32) as *mut __m128i, v);
+        _mm_storeu_si128(end.sub(16) as *mut __m128i, v);
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn memset_tail_avx2(mut d: *mut u8, v: __m256i, value: u8, mut rem: usize) {
     while rem >= 32 {
@@ -107,6 +220,7 @@ unsafe fn memset_tail_avx2(mut d: *mut u8, v: __m256i, value: u8, mut rem: usize
     }
 }
 
+#[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn memset_store_256_avx2(dest: *mut u8, v: __m256i) {
     _mm256_storeu_si256(dest as *mut __m256i, v);
@@ -123,13 +237,10 @@ unsafe fn memset_store_256_avx2(dest: *mut u8, v: __m256i) {
 // AVX DISPATCHER: Centralizes AVX state and manages VZEROUPPER
 // =============================================================================
 
-// Avoid NT transition cliffs around 256 KiB on post-2020 cores. Keep memset on
-// cached AVX2 stores for small/medium/large buffers in benchmarked ranges.
-const NT_THRESHOLD: usize = 2 * 1024 * 1024; // 2 MiB
-
+#[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn optimized_memset_avx_dispatch(dest: *mut u8, value: u8, n: usize) {
-    if n < NT_THRESHOLD {
+    if n < cpu::memset_nt_threshold() {
         optimized_memset_avx2(dest, value, n);
     } else {
         optimized_memset_avx2_nt(dest, value, n);
@@ -140,6 +251,7 @@ unsafe fn optimized_memset_avx_dispatch(dest: *mut u8, value: u8, n: usize) {
 // MEDIUM PATH: AVX2 Implementation (65B - 256KB)
 // =============================================================================
 
+#[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn optimized_memset_avx2(dest: *mut u8, value: u8, n: usize) {
     let v = _mm256_set1_epi8(value as i8);
@@ -200,6 +312,7 @@ unsafe fn optimized_memset_avx2(dest: *mut u8, value: u8, n: usize) {
 // LARGE PATH: Non-Temporal Stores (>256KB)
 // =============================================================================
 
+#[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn optimized_memset_avx2_nt(dest: *mut u8, value: u8, n: usize) {
     let v = _mm256_set1_epi8(value as i8);
@@ -233,9 +346,574 @@ unsafe fn optimized_memset_avx2_nt(dest: *mut u8, value: u8, n: usize) {
     }
 }
 
+// =============================================================================
+// AVX-512 PATH: 64-byte ZMM stores (65B+)
+// =============================================================================
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512bw,avx512vl")]
+unsafe fn optimized_memset_avx512_dispatch(dest: *mut u8, value: u8, n: usize) {
+    let v = _mm512_set1_epi8(value as i8);
+
+    // SAFETY: Caller guarantees `n > 64` and that `dest` is writable for `n`
+    // bytes. Every store lies inside `[dest, dest + n)`.
+    unsafe {
+        if n <= 128 {
+            _mm512_storeu_si512(dest as *mut __m512i, v);
+            _mm512_storeu_si512(dest.add(n - 64) as *mut __m512i, v);
+            return;
+        }
+        if n <= 256 {
+            _mm512_storeu_si512(dest as *mut __m512i, v);
+            _mm512_storeu_si512(dest.add(64) as *mut __m512i, v);
+            _mm512_storeu_si512(dest.add(n - 128) as *mut __m512i, v);
+            _mm512_storeu_si512(dest.add(n - 64) as *mut __m512i, v);
+            return;
+        }
+
+        // Head: one unaligned store, then continue from the next 64-byte
+        // boundary so the loop can use aligned (or streaming) stores.
+        _mm512_storeu_si512(dest as *mut __m512i, v);
+        let advance = 64 - ((dest as usize) & 63);
+        let mut ptr = dest.add(advance);
+        let mut rem = n - advance;
+
+        if n < cpu::memset_nt_threshold() {
+            while rem > 256 {
+                _mm512_store_si512(ptr as *mut __m512i, v);
+                _mm512_store_si512(ptr.add(64) as *mut __m512i, v);
+                _mm512_store_si512(ptr.add(128) as *mut __m512i, v);
+                _mm512_store_si512(ptr.add(192) as *mut __m512i, v);
+                ptr = ptr.add(256);
+                rem -= 256;
+            }
+        } else {
+            while rem > 256 {
+                _mm512_stream_si512(ptr as *mut __m512i, v);
+                _mm512_stream_si512(ptr.add(64) as *mut __m512i, v);
+                _mm512_stream_si512(ptr.add(128) as *mut __m512i, v);
+                _mm512_stream_si512(ptr.add(192) as *mut __m512i, v);
+                ptr = ptr.add(256);
+                rem -= 256;
+            }
+            // REQUIRED: fence ensures NT stores are visible before function returns
+            _mm_sfence();
+        }
+
+        // Tail: the last 256 bytes, overlapping what the loop already wrote.
+        let end = dest.add(n);
+        _mm512_storeu_si512(end.sub(256) as *mut __m512i, v);
+        _mm512_storeu_si512(end.sub(192) as *mut __m512i, v);
+        _mm512_storeu_si512(end.sub(128) as *mut __m512i, v);
+        _mm512_storeu_si512(end.sub(64) as *mut __m512i, v);
+    }
+}
+
+// =============================================================================
+// PATTERN FILL: u16/u32/u64 elements and repeating patterns up to 64 bytes
+// =============================================================================
+
+/// Longest pattern [`optimized_memset_pattern`] repeats, in bytes.
+pub const MAX_PATTERN_LEN: usize = 64;
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) type PatternFn = unsafe fn(*mut u8, &Pattern, usize);
+
+/// Kernel used for pattern fills above the inline small path, chosen on first use.
+#[cfg(target_arch = "x86_64")]
+pub(crate) static MEMSET_PATTERN_LARGE: FnSlot<PatternFn> =
+    FnSlot::new(resolve_memset_pattern_large as *mut ());
+
+/// Fills `n` bytes with `pattern` repeated from its first byte; the last
+/// repetition is cut short when `n` is not a multiple of its length.
+///
+/// Uses the same tiers as [`optimized_memset_unified`]: a plain copy up to
+/// 64 bytes, then SSE2, AVX2 or AVX-512 stores, with non-temporal stores at
+/// the memset threshold. A pattern of one repeated byte takes the byte fill.
+///
+/// # Panics
+///
+/// Panics if `pattern` is empty or longer than [`MAX_PATTERN_LEN`].
+///
+/// # Safety
+///
+/// - `dest` must be valid for writes of `n` bytes
+pub unsafe fn optimized_memset_pattern(dest: *mut u8, pattern: &[u8], n: usize) {
+    assert!(
+        !pattern.is_empty() && pattern.len() <= MAX_PATTERN_LEN,
+        "fill pattern must be 1 to {MAX_PATTERN_LEN} bytes, got {}",
+        pattern.len()
+    );
+
+    #[cfg(feature = "instrument")]
+    crate::instrument::record(crate::instrument::Routine::Memset, n, dest as usize);
+
+    if pattern.iter().all(|&b| b == pattern[0]) {
+        // SAFETY: Forwarded caller contract.
+        return unsafe { memset_dispatched(dest, pattern[0], n) };
+    }
+    // Unrolling into a local copy also lets `pattern` overlap `dest`.
+    let pat = Pattern::new(pattern);
+    // SAFETY: Forwarded caller contract.
+    unsafe { memset_pattern_dispatched(dest, &pat, n) }
+}
+
+/// Fills `count` 16-bit elements with `value`.
+///
+/// # Safety
+///
+/// - `dest` must be valid for writes of `count` elements; it need not be aligned
+#[inline]
+pub unsafe fn optimized_memset16(dest: *mut u16, value: u16, count: usize) {
+    // SAFETY: Forwarded caller contract.
+    unsafe { optimized_memset_pattern(dest as *mut u8, &value.to_ne_bytes(), count * 2) }
+}
+
+/// Fills `count` 32-bit elements with `value`.
+///
+/// # Safety
+///
+/// - `dest` must be valid for writes of `count` elements; it need not be aligned
+#[inline]
+pub unsafe fn optimized_memset32(dest: *mut u32, value: u32, count: usize) {
+    // SAFETY: Forwarded caller contract.
+    unsafe { optimized_memset_pattern(dest as *mut u8, &value.to_ne_bytes(), count * 4) }
+}
+
+/// Fills `count` 64-bit elements with `value`.
+///
+/// # Safety
+///
+/// - `dest` must be valid for writes of `count` elements; it need not be aligned
+#[inline]
+pub unsafe fn optimized_memset64(dest: *mut u64, value: u64, count: usize) {
+    // SAFETY: Forwarded caller contract.
+    unsafe { optimized_memset_pattern(dest as *mut u8, &value.to_ne_bytes(), count * 8) }
+}
+
+/// A fill pattern unrolled so that the bytes from any phase on can be read
+/// as one 64-byte run.
+///
+/// Every store a kernel makes at offset `off` loads from `at(phase(off))`,
+/// so overlapping head and tail stores write the same bytes as the loop.
+pub(crate) struct Pattern {
+    /// `bytes[i]` is byte `i % period` of the pattern.
+    bytes: [u8; 2 * MAX_PATTERN_LEN],
+    period: usize,
+}
+
+impl Pattern {
+    /// Unrolls `pattern`, which holds 1 to [`MAX_PATTERN_LEN`] bytes.
+    pub(crate) fn new(pattern: &[u8]) -> Self {
+        let period = pattern.len();
+        let mut bytes = [0u8; 2 * MAX_PATTERN_LEN];
+        bytes[..period].copy_from_slice(pattern);
+        let mut filled = period;
+        while filled < bytes.len() {
+            let k = filled.min(bytes.len() - filled);
+            bytes.copy_within(..k, filled);
+            filled += k;
+        }
+        Self { bytes, period }
+    }
+
+    /// The 64 pattern bytes starting at `phase`, which is below the period.
+    #[inline(always)]
+    fn at(&self, phase: usize) -> *const u8 {
+        self.bytes[phase..phase + MAX_PATTERN_LEN].as_ptr()
+    }
+
+    /// Phase of the byte `off` bytes into the fill.
+    #[inline(always)]
+    fn phase(&self, off: usize) -> usize {
+        off % self.period
+    }
+
+    /// Phase `step` bytes after `phase`, with `step` itself reduced by
+    /// [`phase`](Self::phase) so no division is needed.
+    #[inline(always)]
+    fn advance(&self, phase: usize, step: usize) -> usize {
+        let next = phase + step;
+        if next >= self.period {
+            next - self.period
+        } else {
+            next
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn memset_pattern_dispatched(dest: *mut u8, pat: &Pattern, n: usize) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if n <= 64 && !cpu::PORTABLE {
+            // The unrolled pattern already holds the first 64 bytes.
+            // SAFETY: Caller guarantees `dest` is writable for `n` bytes.
+            unsafe { crate::memcpy::memcpy_dispatched(dest, pat.at(0), n) };
+            return;
+        }
+        unsafe { (MEMSET_PATTERN_LARGE.get())(dest, pat, n) }
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        unsafe { memset_pattern_scalar_wide(dest, pat, n) }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn select_memset_pattern_large(level: IsaLevel) -> PatternFn {
+    match level {
+        IsaLevel::Avx512 => memset_pattern_avx512_dispatch,
+        IsaLevel::Avx2 => memset_pattern_avx_dispatch,
+        IsaLevel::Sse42 | IsaLevel::Sse2 => memset_pattern_sse2,
+        IsaLevel::Scalar => memset_pattern_scalar_wide,
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn resolve_memset_pattern_large(dest: *mut u8, pat: &Pattern, n: usize) {
+    let f = select_memset_pattern_large(cpu::isa_level());
+    MEMSET_PATTERN_LARGE.set(f);
+    // SAFETY: Forwarded caller contract.
+    unsafe { f(dest, pat, n) }
+}
+
+unsafe fn memset_pattern_scalar_wide(dest: *mut u8, pat: &Pattern, n: usize) {
+    let step = pat.phase(8);
+    let mut phase = 0;
+    let mut i = 0usize;
+
+    // SAFETY: Caller guarantees `dest` is writable for `n` bytes; `pat.at`
+    // is readable for 64 bytes and unaligned word accesses accept any
+    // alignment.
+    unsafe {
+        while i + 8 <= n {
+            let word = core::ptr::read_unaligned(pat.at(phase) as *const u64);
+            core::ptr::write_unaligned(dest.add(i) as *mut u64, word);
+            phase = pat.advance(phase, step);
+            i += 8;
+        }
+
+        let src = pat.at(phase);
+        for k in 0..n - i {
+            *dest.add(i + k) = *src.add(k);
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn memset_pattern_sse2(dest: *mut u8, pat: &Pattern, n: usize) {
+    // n > 64 guaranteed by dispatch, so the overlapping 64-byte tail is in bounds.
+    // SAFETY: Unaligned SSE2 loads and stores are valid for any alignment;
+    // caller guarantees `dest` is writable for `n` bytes and `pat.at` is
+    // readable for 64.
+    unsafe {
+        let step = pat.phase(64);
+        let mut phase = 0;
+        let mut ptr = dest;
+        let end = dest.add(n);
+
+        while (end as usize) - (ptr as usize) > 64 {
+            let src = pat.at(phase);
+            for k in [0, 16, 32, 48] {
+                let v = _mm_loadu_si128(src.add(k) as *const __m128i);
+                _mm_storeu_si128(ptr.add(k) as *mut __m128i, v);
+            }
+            phase = pat.advance(phase, step);
+            ptr = ptr.add(64);
+        }
+
+        let src = pat.at(pat.phase(n - 64));
+        for k in [0, 16, 32, 48] {
+            let v = _mm_loadu_si128(src.add(k) as *const __m128i);
+            _mm_storeu_si128(end.sub(64 - k) as *mut __m128i, v);
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn memset_pattern_avx_dispatch(dest: *mut u8, pat: &Pattern, n: usize) {
+    // SAFETY: Forwarded caller contract.
+    unsafe {
+        if n < cpu::memset_nt_threshold() {
+            memset_pattern_avx2::<false>(dest, pat, n);
+        } else {
+            memset_pattern_avx2::<true>(dest, pat, n);
+        }
+    }
+}
+
+/// AVX2 pattern fill for `n > 64`; `STREAM` selects non-temporal stores for
+/// the aligned main loop.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn memset_pattern_avx2<const STREAM: bool>(dest: *mut u8, pat: &Pattern, n: usize) {
+    // SAFETY: Caller guarantees `n > 64` and that `dest` is writable for `n`
+    // bytes; every store lies inside `[dest, dest + n)` and every load inside
+    // the 64 bytes `pat.at` returns.
+    unsafe {
+        let end = dest.add(n);
+        let head = pat.at(0);
+        let tail = pat.at(pat.phase(n - 64));
+        let t0 = _mm256_loadu_si256(tail as *const __m256i);
+        let t1 = _mm256_loadu_si256(tail.add(32) as *const __m256i);
+
+        _mm256_storeu_si256(
+            dest as *mut __m256i,
+            _mm256_loadu_si256(head as *const __m256i),
+        );
+        if n <= 128 {
+            let h1 = _mm256_loadu_si256(head.add(32) as *const __m256i);
+            _mm256_storeu_si256(dest.add(32) as *mut __m256i, h1);
+            _mm256_storeu_si256(end.sub(64) as *mut __m256i, t0);
+            _mm256_storeu_si256(end.sub(32) as *mut __m256i, t1);
+            return;
+        }
+
+        // Continue from the next 32-byte boundary so the loop can use
+        // aligned (or streaming) stores; the head store covers the gap.
+        let advance = 32 - ((dest as usize) & 31);
+        let mut ptr = dest.add(advance);
+        let mut rem = n - advance;
+        let step = pat.phase(64);
+        let mut phase = pat.phase(advance);
+
+        while rem > 64 {
+            let src = pat.at(phase);
+            let v0 = _mm256_loadu_si256(src as *const __m256i);
+            let v1 = _mm256_loadu_si256(src.add(32) as *const __m256i);
+            if STREAM {
+                _mm256_stream_si256(ptr as *mut __m256i, v0);
+                _mm256_stream_si256(ptr.add(32) as *mut __m256i, v1);
+            } else {
+                _mm256_store_si256(ptr as *mut __m256i, v0);
+                _mm256_store_si256(ptr.add(32) as *mut __m256i, v1);
+            }
+            phase = pat.advance(phase, step);
+            ptr = ptr.add(64);
+            rem -= 64;
+        }
+
+        if STREAM {
+            // REQUIRED: fence ensures NT stores are visible before function returns
+            _mm_sfence();
+        }
+
+        // Tail: the last 64 bytes, overlapping what the loop already wrote.
+        _mm256_storeu_si256(end.sub(64) as *mut __m256i, t0);
+        _mm256_storeu_si256(end.sub(32) as *mut __m256i, t1);
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512bw,avx512vl")]
+unsafe fn memset_pattern_avx512_dispatch(dest: *mut u8, pat: &Pattern, n: usize) {
+    // SAFETY: Caller guarantees `n > 64` and that `dest` is writable for `n`
+    // bytes; every store lies inside `[dest, dest + n)` and every load inside
+    // the 64 bytes `pat.at` returns.
+    unsafe {
+        let end = dest.add(n);
+        let tail = _mm512_loadu_si512(pat.at(pat.phase(n - 64)) as *const __m512i);
+        let head = _mm512_loadu_si512(pat.at(0) as *const __m512i);
+        _mm512_storeu_si512(dest as *mut __m512i, head);
+        if n <= 128 {
+            _mm512_storeu_si512(end.sub(64) as *mut __m512i, tail);
+            return;
+        }
+
+        let advance = 64 - ((dest as usize) & 63);
+        let mut ptr = dest.add(advance);
+        let mut rem = n - advance;
+        let step = pat.phase(64);
+        let mut phase = pat.phase(advance);
+
+        if n < cpu::memset_nt_threshold() {
+            while rem > 64 {
+                let v = _mm512_loadu_si512(pat.at(phase) as *const __m512i);
+                _mm512_store_si512(ptr as *mut __m512i, v);
+                phase = pat.advance(phase, step);
+                ptr = ptr.add(64);
+                rem -= 64;
+            }
+        } else {
+            while rem > 64 {
+                let v = _mm512_loadu_si512(pat.at(phase) as *const __m512i);
+                _mm512_stream_si512(ptr as *mut __m512i, v);
+                phase = pat.advance(phase, step);
+                ptr = ptr.add(64);
+                rem -= 64;
+            }
+            // REQUIRED: fence ensures NT stores are visible before function returns
+            _mm_sfence();
+        }
+
+        _mm512_storeu_si512(end.sub(64) as *mut __m512i, tail);
+    }
+}
+
+// =============================================================================
+// EXPLICIT FILL: stores the optimizer must keep (explicit_bzero, memset_explicit)
+// =============================================================================
+
+/// Kernel used by [`optimized_memset_explicit`], chosen on first use.
+#[cfg(target_arch = "x86_64")]
+pub(crate) static MEMSET_EXPLICIT: FnSlot<SetFn> = FnSlot::new(resolve_memset_explicit as *mut ());
+
+/// Fills `n` bytes with `value` using volatile stores, then issues a
+/// compiler fence, so the fill is kept even when the buffer is never read
+/// again or is about to be freed.
+///
+/// This is the kernel behind `explicit_bzero` and C23 `memset_explicit`.
+/// The bulk of the buffer is written with aligned SSE2 or AVX2 volatile
+/// stores; there is no non-temporal tier, since wiped secrets are usually
+/// small and still hot.
+///
+/// # Safety
+///
+/// - `dest` must be valid for writes of `n` bytes
+#[inline]
+pub unsafe fn optimized_memset_explicit(dest: *mut u8, value: u8, n: usize) {
+    #[cfg(feature = "instrument")]
+    crate::instrument::record(crate::instrument::Routine::Memset, n, dest as usize);
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        // SAFETY: Forwarded caller contract.
+        unsafe { (MEMSET_EXPLICIT.get())(dest, value, n) }
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        // SAFETY: Forwarded caller contract.
+        unsafe { memset_explicit_scalar(dest, value, n) }
+    }
+
+    // Volatile stores are never removed, and the fence keeps them from being
+    // sunk past whatever the caller does next (such as freeing the buffer).
+    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+}
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn select_memset_explicit(level: IsaLevel) -> SetFn {
+    match level {
+        // Wider stores gain nothing once the loop is bound by volatile
+        // store throughput, so AVX-512 hosts share the AVX2 kernel.
+        IsaLevel::Avx512 | IsaLevel::Avx2 => memset_explicit_avx2,
+        IsaLevel::Sse42 | IsaLevel::Sse2 => memset_explicit_sse2,
+        IsaLevel::Scalar => memset_explicit_scalar,
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn resolve_memset_explicit(dest: *mut u8, value: u8, n: usize) {
+    let f = select_memset_explicit(cpu::isa_level());
+    MEMSET_EXPLICIT.set(f);
+    // SAFETY: Forwarded caller contract.
+    unsafe { f(dest, value, n) }
+}
+
+/// Volatile byte stores up to an 8-byte boundary, aligned volatile word
+/// stores, then volatile byte stores for the rest.
+unsafe fn memset_explicit_scalar(dest: *mut u8, value: u8, n: usize) {
+    let word = u64::from_ne_bytes([value; 8]);
+    let head = dest.align_offset(8).min(n);
+
+    // SAFETY: Caller guarantees `dest` is writable for `n` bytes; the word
+    // stores start at an 8-byte boundary.
+    unsafe {
+        for i in 0..head {
+            core::ptr::write_volatile(dest.add(i), value);
+        }
+        let mut d = dest.add(head);
+        let mut rem = n - head;
+        while rem >= 8 {
+            core::ptr::write_volatile(d as *mut u64, word);
+            d = d.add(8);
+            rem -= 8;
+        }
+        for i in 0..rem {
+            core::ptr::write_volatile(d.add(i), value);
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn memset_explicit_sse2(dest: *mut u8, value: u8, n: usize) {
+    let head = dest.align_offset(16).min(n);
+
+    // SAFETY: Caller guarantees `dest` is writable for `n` bytes; the vector
+    // stores start at a 16-byte boundary.
+    unsafe {
+        memset_explicit_scalar(dest, value, head);
+        let v = _mm_set1_epi8(value as i8);
+        let mut d = dest.add(head);
+        let mut rem = n - head;
+        while rem >= 16 {
+            core::ptr::write_volatile(d as *mut __m128i, v);
+            d = d.add(16);
+            rem -= 16;
+        }
+        memset_explicit_scalar(d, value, rem);
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn memset_explicit_avx2(dest: *mut u8, value: u8, n: usize) {
+    let head = dest.align_offset(32).min(n);
+
+    // SAFETY: Caller guarantees `dest` is writable for `n` bytes; the vector
+    // stores start at a 32-byte boundary.
+    unsafe {
+        memset_explicit_scalar(dest, value, head);
+        let v = _mm256_set1_epi8(value as i8);
+        let mut d = dest.add(head);
+        let mut rem = n - head;
+        while rem >= 32 {
+            core::ptr::write_volatile(d as *mut __m256i, v);
+            d = d.add(32);
+            rem -= 32;
+        }
+        memset_explicit_scalar(d, value, rem);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::vec;
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_memset_every_isa_level() {
+        for level in cpu::supported_levels() {
+            let kernel = select_memset_large(level);
+            for off in 0..32 {
+                for n in [
+                    65usize, 127, 128, 129, 255, 256, 257, 511, 512, 1023, 1024, 4097,
+                ] {
+                    let mut dst = vec![0xFFu8; 4200];
+                    unsafe {
+                        kernel(dst.as_mut_ptr().add(off), 0x42, n);
+                    }
+                    assert!(
+                        dst[..off].iter().all(|&b| b == 0xFF),
+                        "{level:?} underwrote"
+                    );
+                    assert!(
+                        dst[off..off + n].iter().all(|&b| b == 0x42),
+                        "{level:?} failed at size {n} off {off}"
+                    );
+                    assert!(
+                        dst[off + n..].iter().all(|&b| b == 0xFF),
+                        "{level:?} overwrote"
+                    );
+                }
+            }
+        }
+    }
 
     #[test]
     fn test_memset_optimized_0_to_1024() {
@@ -280,6 +958,96 @@ mod tests {
         }
     }
 
+    /// Checks `dst[off..off + n]` holds `pat` repeated and the rest is 0xFF.
+    fn check_pattern(dst: &[u8], off: usize, n: usize, pat: &[u8]) {
+        assert!(dst[..off].iter().all(|&b| b == 0xFF), "underwrote");
+        for (i, &b) in dst[off..off + n].iter().enumerate() {
+            assert_eq!(
+                b,
+                pat[i % pat.len()],
+                "len {} size {n} off {off} index {i}",
+                pat.len()
+            );
+        }
+        assert!(dst[off + n..].iter().all(|&b| b == 0xFF), "overwrote");
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_memset_pattern_every_isa_level() {
+        let source: [u8; 64] = core::array::from_fn(|i| (i * 37 + 1) as u8);
+        for level in cpu::supported_levels() {
+            let kernel = select_memset_pattern_large(level);
+            for len in [2usize, 3, 4, 7, 8, 12, 16, 24, 31, 48, 63, 64] {
+                let pat = Pattern::new(&source[..len]);
+                for off in [0usize, 1, 7, 31, 32, 63] {
+                    for n in [65usize, 100, 128, 129, 255, 257, 511, 1024, 4097] {
+                        let mut dst = vec![0xFFu8; 4200];
+                        unsafe { kernel(dst.as_mut_ptr().add(off), &pat, n) };
+                        check_pattern(&dst, off, n, &source[..len]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_memset_pattern_0_to_600() {
+        let source: [u8; 64] = core::array::from_fn(|i| (i * 11 + 5) as u8);
+        let mut dst = [0u8; 640];
+        for len in 1..=64 {
+            for n in (0..=600).step_by(len.min(7)) {
+                for off in [0usize, 5] {
+                    dst.fill(0xFF);
+                    unsafe {
+                        optimized_memset_pattern(dst.as_mut_ptr().add(off), &source[..len], n)
+                    };
+                    check_pattern(&dst, off, n, &source[..len]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_memset_elements_unaligned() {
+        let mut dst = vec![0xFFu8; 2100];
+        for off in [0usize, 1, 2, 3] {
+            for count in [0usize, 1, 31, 32, 33, 250] {
+                dst.fill(0xFF);
+                unsafe { optimized_memset16(dst.as_mut_ptr().add(off) as *mut u16, 0x1234, count) };
+                check_pattern(&dst, off, count * 2, &0x1234u16.to_ne_bytes());
+
+                dst.fill(0xFF);
+                unsafe {
+                    optimized_memset32(dst.as_mut_ptr().add(off) as *mut u32, 0xa1b2_c3d4, count)
+                };
+                check_pattern(&dst, off, count * 4, &0xa1b2_c3d4u32.to_ne_bytes());
+
+                dst.fill(0xFF);
+                let v = 0x0102_0304_0506_0708u64;
+                unsafe { optimized_memset64(dst.as_mut_ptr().add(off) as *mut u64, v, count) };
+                check_pattern(&dst, off, count * 8, &v.to_ne_bytes());
+            }
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_memset_explicit_every_isa_level() {
+        for level in cpu::supported_levels() {
+            let kernel = select_memset_explicit(level);
+            for off in 0..32 {
+                for n in [
+                    0usize, 1, 7, 8, 15, 16, 31, 32, 33, 63, 64, 65, 100, 257, 1024,
+                ] {
+                    let mut dst = vec![0xFFu8; 1100];
+                    unsafe { kernel(dst.as_mut_ptr().add(off), 0, n) };
+                    check_pattern(&dst, off, n, &[0]);
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_memset_zero() {
         let mut dst = [0xFFu8; 256];
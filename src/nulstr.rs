@@ -0,0 +1,520 @@
+//! NUL-terminated byte strings that know their length.
+//!
+//! [`NulStr`] is a borrowed string and [`NulString`] an owned, growable one.
+//! Both keep exactly one NUL, at the end, and carry the length alongside the
+//! pointer, so neither ever scans for the terminator. Appending to a
+//! [`NulString`] therefore costs the size of the appended bytes, where a
+//! `strcat` loop rescans everything written so far on every call. Edits move
+//! bytes with [`optimized_memcpy_unified`] and [`optimized_memmove_unified`].
+//!
+//! ```
+//! use faststrings::nulstr::NulString;
+//!
+//! let mut path = NulString::from(c"/usr");
+//! for part in [c"/local", c"/lib"] {
+//!     path.append(part);
+//! }
+//! path.insert(0, b"root:");
+//! assert_eq!(path.as_c_str(), c"root:/usr/local/lib");
+//! assert_eq!(path.len(), 19);
+//! ```
+#![allow(unsafe_code)]
+
+#[cfg(feature = "alloc")]
+use alloc::borrow::ToOwned;
+#[cfg(feature = "alloc")]
+use alloc::ffi::CString;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::borrow::Borrow;
+use core::ffi::{CStr, FromBytesWithNulError, c_char};
+use core::fmt;
+#[cfg(feature = "alloc")]
+use core::ops::{Bound, Deref, RangeBounds};
+
+use crate::cstr::CStrLike;
+#[cfg(feature = "alloc")]
+use crate::memcpy::optimized_memcpy_unified;
+#[cfg(feature = "alloc")]
+use crate::memmove::optimized_memmove_unified;
+
+/// A borrowed NUL-terminated byte string with a known length.
+///
+/// Like [`CStr`], this is an unsized type used behind a reference, and it
+/// holds no NUL but its terminator.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct NulStr {
+    /// The string and its terminator.
+    bytes: [u8],
+}
+
+impl NulStr {
+    /// Views `s` as a [`NulStr`] without scanning it.
+    #[inline]
+    pub fn from_c_str(s: &CStr) -> &NulStr {
+        // SAFETY: `s` ends in its only NUL.
+        unsafe { Self::from_bytes_with_nul_unchecked(s.to_bytes_with_nul()) }
+    }
+
+    /// Views `bytes`, which must end in its only NUL, as a [`NulStr`].
+    pub fn from_bytes_with_nul(bytes: &[u8]) -> Result<&NulStr, FromBytesWithNulError> {
+        CStr::from_bytes_with_nul(bytes).map(Self::from_c_str)
+    }
+
+    /// Views `bytes` as a [`NulStr`] without checking it.
+    ///
+    /// # Safety
+    ///
+    /// `bytes` must end in a NUL and contain no other.
+    #[inline]
+    pub const unsafe fn from_bytes_with_nul_unchecked(bytes: &[u8]) -> &NulStr {
+        // SAFETY: `NulStr` is a transparent wrapper of `[u8]`.
+        unsafe { &*(bytes as *const [u8] as *const NulStr) }
+    }
+
+    /// Length in bytes, without the terminator.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.bytes.len() - 1
+    }
+
+    /// Whether the string is empty.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The bytes of the string, without the terminator.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len()]
+    }
+
+    /// The bytes of the string, with the terminator.
+    #[inline]
+    pub const fn as_bytes_with_nul(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Pointer to the first byte, for passing to C.
+    #[inline]
+    pub const fn as_ptr(&self) -> *const c_char {
+        self.bytes.as_ptr() as *const c_char
+    }
+
+    /// The string as a [`CStr`].
+    #[inline]
+    pub fn as_c_str(&self) -> &CStr {
+        // SAFETY: `bytes` ends in its only NUL.
+        unsafe { CStr::from_bytes_with_nul_unchecked(&self.bytes) }
+    }
+}
+
+impl CStrLike for NulStr {
+    #[inline]
+    fn str_bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl AsRef<CStr> for NulStr {
+    #[inline]
+    fn as_ref(&self) -> &CStr {
+        self.as_c_str()
+    }
+}
+
+impl<'a> From<&'a CStr> for &'a NulStr {
+    #[inline]
+    fn from(s: &'a CStr) -> Self {
+        NulStr::from_c_str(s)
+    }
+}
+
+impl fmt::Debug for NulStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_c_str(), f)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ToOwned for NulStr {
+    type Owned = NulString;
+
+    fn to_owned(&self) -> NulString {
+        NulString {
+            buf: self.bytes.to_vec(),
+        }
+    }
+}
+
+/// An owned, growable NUL-terminated byte string.
+///
+/// The string is stored with its terminator, so [`as_ptr`](NulStr::as_ptr)
+/// and [`as_c_str`](NulStr::as_c_str) hand it out without copying. Every
+/// method that adds bytes takes a [`CStrLike`] string and adds the bytes
+/// before its first NUL, so the string never holds an interior NUL.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg(feature = "alloc")]
+pub struct NulString {
+    /// The string and its terminator; never empty.
+    buf: Vec<u8>,
+}
+
+#[cfg(feature = "alloc")]
+impl NulString {
+    /// An empty string.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// An empty string with room for `capacity` bytes besides the terminator.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut buf = Vec::with_capacity(capacity.saturating_add(1));
+        buf.push(0);
+        Self { buf }
+    }
+
+    /// Number of bytes the string can hold, without the terminator, before
+    /// it reallocates.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity() - 1
+    }
+
+    /// Reserves room for at least `additional` more bytes.
+    pub fn reserve(&mut self, additional: usize) {
+        self.buf.reserve(additional);
+    }
+
+    /// The string as a borrowed [`NulStr`].
+    #[inline]
+    pub fn as_nul_str(&self) -> &NulStr {
+        // SAFETY: `buf` ends in its only NUL.
+        unsafe { NulStr::from_bytes_with_nul_unchecked(&self.buf) }
+    }
+
+    /// Appends the byte `b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `b` is NUL.
+    pub fn push(&mut self, b: u8) {
+        assert!(b != 0, "cannot push a NUL into a NulString");
+        let len = self.len();
+        self.buf[len] = b;
+        self.buf.push(0);
+    }
+
+    /// Appends `s`, up to its terminator.
+    pub fn append<S: CStrLike + ?Sized>(&mut self, s: &S) {
+        let len = self.len();
+        self.splice(len, len, s.str_bytes());
+    }
+
+    /// Inserts `s`, up to its terminator, at byte offset `idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is greater than the length.
+    pub fn insert<S: CStrLike + ?Sized>(&mut self, idx: usize, s: &S) {
+        assert!(idx <= self.len(), "insertion index out of bounds");
+        self.splice(idx, idx, s.str_bytes());
+    }
+
+    /// Replaces the bytes in `range` with `s`, up to its terminator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or decreasing.
+    pub fn replace_range<R, S>(&mut self, range: R, s: &S)
+    where
+        R: RangeBounds<usize>,
+        S: CStrLike + ?Sized,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&i) => i,
+            Bound::Excluded(&i) => i.checked_add(1).expect("range start overflows"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&i) => i.checked_add(1).expect("range end overflows"),
+            Bound::Excluded(&i) => i,
+            Bound::Unbounded => self.len(),
+        };
+        assert!(
+            start <= end && end <= self.len(),
+            "range {start}..{end} out of bounds for length {}",
+            self.len()
+        );
+        self.splice(start, end, s.str_bytes());
+    }
+
+    /// A copy of the string with every non-overlapping `from` replaced by
+    /// `to`, scanning left to right.
+    ///
+    /// An empty `from` matches nothing.
+    pub fn replace<P, S>(&self, from: &P, to: &S) -> NulString
+    where
+        P: CStrLike + ?Sized,
+        S: CStrLike + ?Sized,
+    {
+        let from = from.str_bytes();
+        let to = to.str_bytes();
+        let mut rest = self.as_bytes();
+        let mut out = NulString::with_capacity(rest.len());
+        if !from.is_empty() {
+            while let Some(i) = crate::mem::memmem(rest, from) {
+                out.extend_bytes(&rest[..i]);
+                out.extend_bytes(to);
+                rest = &rest[i + from.len()..];
+            }
+        }
+        out.extend_bytes(rest);
+        out
+    }
+
+    /// Shortens the string to `len` bytes; does nothing if it is not longer.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            self.buf.truncate(len + 1);
+            self.buf[len] = 0;
+        }
+    }
+
+    /// Empties the string, keeping its capacity.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Converts into a [`CString`] without copying.
+    pub fn into_c_string(self) -> CString {
+        // SAFETY: `buf` ends in its only NUL.
+        unsafe { CString::from_vec_with_nul_unchecked(self.buf) }
+    }
+
+    /// Converts into the bytes of the string with its terminator.
+    pub fn into_bytes_with_nul(self) -> Vec<u8> {
+        self.buf
+    }
+
+    /// Appends `s`, which holds no NUL.
+    fn extend_bytes(&mut self, s: &[u8]) {
+        let len = self.len();
+        self.splice(len, len, s);
+    }
+
+    /// Replaces bytes `start..end` with `s`, which holds no NUL.
+    fn splice(&mut self, start: usize, end: usize, s: &[u8]) {
+        let old_len = self.buf.len();
+        let new_len = old_len - (end - start) + s.len();
+        self.buf.reserve(new_len.saturating_sub(old_len));
+        let base = self.buf.as_mut_ptr();
+        // SAFETY: `start <= end < old_len` and the buffer holds `new_len`
+        // bytes, so the tail (with the terminator) moves within it. `s`
+        // cannot alias `buf` while `self` is borrowed mutably.
+        unsafe {
+            if start + s.len() != end {
+                optimized_memmove_unified(base.add(start + s.len()), base.add(end), old_len - end);
+            }
+            optimized_memcpy_unified(base.add(start), s.as_ptr(), s.len());
+            self.buf.set_len(new_len);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Default for NulString {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Deref for NulString {
+    type Target = NulStr;
+
+    #[inline]
+    fn deref(&self) -> &NulStr {
+        self.as_nul_str()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Borrow<NulStr> for NulString {
+    #[inline]
+    fn borrow(&self) -> &NulStr {
+        self.as_nul_str()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl AsRef<CStr> for NulString {
+    #[inline]
+    fn as_ref(&self) -> &CStr {
+        self.as_c_str()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl CStrLike for NulString {
+    #[inline]
+    fn str_bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// Copies `s` up to its terminator.
+#[cfg(feature = "alloc")]
+impl<S: CStrLike + ?Sized> From<&S> for NulString {
+    fn from(s: &S) -> Self {
+        let s = s.str_bytes();
+        let mut out = NulString::with_capacity(s.len());
+        out.extend_bytes(s);
+        out
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<CString> for NulString {
+    fn from(s: CString) -> Self {
+        Self {
+            buf: s.into_bytes_with_nul(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<NulString> for CString {
+    fn from(s: NulString) -> Self {
+        s.into_c_string()
+    }
+}
+
+/// Wipes the whole buffer, spare capacity included, and leaves the string
+/// empty.
+#[cfg(feature = "alloc")]
+impl crate::zeroize::Zeroize for NulString {
+    fn zeroize(&mut self) {
+        self.buf.zeroize();
+        self.buf.push(0);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for NulString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_nul_str(), f)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    fn check(s: &NulString, expect: &[u8]) {
+        assert_eq!(s.as_bytes(), expect);
+        assert_eq!(s.len(), expect.len());
+        assert_eq!(s.as_bytes_with_nul().last(), Some(&0));
+        assert_eq!(crate::str::strlen(s.as_bytes_with_nul()), s.len());
+        // SAFETY: `as_ptr` points at the terminated string.
+        assert_eq!(unsafe { CStr::from_ptr(s.as_ptr()) }.to_bytes(), expect);
+    }
+
+    #[test]
+    fn test_nul_str_views() {
+        let s = NulStr::from_c_str(c"abc");
+        assert_eq!(s.len(), 3);
+        assert_eq!(s.as_bytes(), b"abc");
+        assert_eq!(s.as_bytes_with_nul(), b"abc\0");
+        assert_eq!(s.as_c_str(), c"abc");
+        assert!(NulStr::from_bytes_with_nul(b"a\0b\0").is_err());
+        assert!(NulStr::from_bytes_with_nul(b"ab").is_err());
+        assert!(NulStr::from_bytes_with_nul(b"\0").unwrap().is_empty());
+        assert_eq!(s.to_owned().as_nul_str(), s);
+    }
+
+    #[test]
+    fn test_push_and_append() {
+        let mut s = NulString::new();
+        check(&s, b"");
+        s.push(b'a');
+        s.append(c"bc");
+        s.append(&b"de\0ignored"[..]);
+        s.append(b"");
+        check(&s, b"abcde");
+
+        // Long enough to take the vector kernels on every append.
+        let chunk = [b'x'; 300];
+        let mut expect = s.as_bytes().to_vec();
+        for _ in 0..20 {
+            s.append(&chunk);
+            expect.extend_from_slice(&chunk);
+        }
+        check(&s, &expect);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_push_nul_panics() {
+        NulString::new().push(0);
+    }
+
+    #[test]
+    fn test_insert_truncate_replace_range() {
+        let mut s = NulString::from(c"hello world");
+        s.insert(5, c",");
+        check(&s, b"hello, world");
+        s.insert(0, b">> ");
+        s.insert(s.len(), b"!");
+        check(&s, b">> hello, world!");
+        s.replace_range(3..8, c"goodbye");
+        check(&s, b">> goodbye, world!");
+        s.replace_range(..3, b"");
+        check(&s, b"goodbye, world!");
+        s.replace_range(7..=7, c"");
+        check(&s, b"goodbye world!");
+        s.truncate(7);
+        check(&s, b"goodbye");
+        s.truncate(100);
+        check(&s, b"goodbye");
+        s.clear();
+        check(&s, b"");
+    }
+
+    #[test]
+    fn test_large_insert_shifts_tail() {
+        let tail: Vec<u8> = (0..1000).map(|i| (i % 255 + 1) as u8).collect();
+        let mut s = NulString::from(&tail[..]);
+        let head = [b'h'; 257];
+        s.insert(3, &head);
+        let mut expect = tail[..3].to_vec();
+        expect.extend_from_slice(&head);
+        expect.extend_from_slice(&tail[3..]);
+        check(&s, &expect);
+
+        s.replace_range(3..260, b"");
+        check(&s, &tail);
+    }
+
+    #[test]
+    fn test_replace() {
+        let s = NulString::from(c"a-b--c-");
+        check(&s.replace(c"-", c"+"), b"a+b++c+");
+        check(&s.replace(c"--", b"=\0x"), b"a-b=c-");
+        check(&s.replace(c"", c"x"), b"a-b--c-");
+        check(&s.replace(c"-", c""), b"abc");
+    }
+
+    #[test]
+    fn test_conversions() {
+        let s = NulString::from(CString::from(c"abc"));
+        check(&s, b"abc");
+        let c: CString = s.clone().into();
+        assert_eq!(c.as_c_str(), c"abc");
+        assert_eq!(s.into_bytes_with_nul(), b"abc\0");
+        assert_eq!(crate::cstr::strcmp(&NulString::from(b"abc"), c"abd"), -1);
+    }
+}
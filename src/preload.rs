@@ -0,0 +1,158 @@
+//! Selective `LD_PRELOAD` interposition (`preload` feature).
+//!
+//! The `preload` feature builds the [`crate::capi`] exports so that each one
+//! can hand its call on to the next definition of the same symbol, normally
+//! the libc one. Preloading the library then measures a whole application
+//! against glibc one routine at a time:
+//!
+//! ```text
+//! cargo rustc --release --features preload --crate-type cdylib
+//! LD_PRELOAD=target/release/libfaststrings.so ./app
+//! LD_PRELOAD=target/release/libfaststrings.so FASTSTRINGS_PRELOAD=-strcmp,-strstr ./app
+//! LD_PRELOAD=target/release/libfaststrings.so FASTSTRINGS_PRELOAD=-all,memcpy,memset ./app
+//! ```
+//!
+//! [`ENV_VAR`] holds a comma-separated list of export names, each optionally
+//! prefixed with `-` (use the next definition) or `+` (use ours, the default).
+//! `all` names every export. Entries apply left to right, so the last one
+//! naming a routine wins; unknown names are ignored. Without the variable
+//! every export is ours.
+//!
+//! Each export looks up its entry on its first call and resolves the next
+//! definition with `dlsym(RTLD_NEXT, name)`; later calls cost one relaxed
+//! load. Calls made while a routine resolves, including from other threads,
+//! run our implementation.
+#![allow(unsafe_code)]
+
+use core::ffi::{c_char, c_void};
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/// Name of the environment variable read by [`Route::next`].
+pub const ENV_VAR: &str = "FASTSTRINGS_PRELOAD";
+
+unsafe extern "C" {
+    fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+    fn getenv(name: *const c_char) -> *const c_char;
+}
+
+/// `RTLD_NEXT` from `<dlfcn.h>`: search the objects loaded after the caller.
+const RTLD_NEXT: *mut c_void = ptr::without_provenance_mut(usize::MAX);
+
+/// Marks a route that runs our implementation.
+const OURS: *mut c_void = ptr::without_provenance_mut(1);
+
+/// Returns whether `list` (in [`ENV_VAR`] syntax) leaves `name` enabled.
+pub fn is_enabled(list: &[u8], name: &[u8]) -> bool {
+    let mut enabled = true;
+    for entry in list.split(|&b| b == b',').map(<[u8]>::trim_ascii) {
+        let (on, entry) = match entry {
+            [b'-', rest @ ..] => (false, rest),
+            [b'+', rest @ ..] => (true, rest),
+            _ => (true, entry),
+        };
+        if entry == name || entry == b"all" {
+            enabled = on;
+        }
+    }
+    enabled
+}
+
+/// Where one export sends its calls.
+pub struct Route {
+    /// Symbol name, NUL-terminated.
+    symbol: &'static str,
+    /// Null until resolved, then [`OURS`] or the next definition.
+    next: AtomicPtr<c_void>,
+}
+
+impl Route {
+    /// An unresolved route for the NUL-terminated `symbol`.
+    pub const fn new(symbol: &'static str) -> Self {
+        Self {
+            symbol,
+            next: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /// Returns the next definition of the symbol when [`ENV_VAR`] disables
+    /// ours, resolving it on the first call.
+    #[inline(always)]
+    pub fn next(&self) -> Option<*mut c_void> {
+        match self.next.load(Ordering::Relaxed) {
+            p if p == OURS => None,
+            p if p.is_null() => self.resolve(),
+            p => Some(p),
+        }
+    }
+
+    #[cold]
+    fn resolve(&self) -> Option<*mut c_void> {
+        // `getenv` and `dlsym` may copy or compare strings, and programs such
+        // as bash define their own `getenv`; those calls stay with us.
+        self.next.store(OURS, Ordering::Relaxed);
+        // SAFETY: The name is NUL-terminated; a non-null result is a
+        // NUL-terminated string that nothing modifies while we read it.
+        let list = unsafe {
+            let value = getenv(c"FASTSTRINGS_PRELOAD".as_ptr());
+            if value.is_null() {
+                &[][..]
+            } else {
+                core::ffi::CStr::from_ptr(value).to_bytes()
+            }
+        };
+        self.resolve_with(list)
+    }
+
+    /// Resolves the route against `list` instead of [`ENV_VAR`].
+    fn resolve_with(&self, list: &[u8]) -> Option<*mut c_void> {
+        self.next.store(OURS, Ordering::Relaxed);
+        let name = &self.symbol.as_bytes()[..self.symbol.len() - 1];
+        if is_enabled(list, name) {
+            return None;
+        }
+
+        // SAFETY: `symbol` is NUL-terminated.
+        let next = unsafe { dlsym(RTLD_NEXT, self.symbol.as_ptr().cast()) };
+        if next.is_null() {
+            return None;
+        }
+        self.next.store(next, Ordering::Relaxed);
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_list() {
+        assert!(is_enabled(b"", b"strlen"));
+        assert!(!is_enabled(b"-strlen", b"strlen"));
+        assert!(is_enabled(b"-strlen", b"strnlen"));
+        assert!(!is_enabled(b"strcmp, -strlen ,memcpy", b"strlen"));
+        assert!(!is_enabled(b"-all,memcpy", b"strlen"));
+        assert!(is_enabled(b"-all,memcpy", b"memcpy"));
+        assert!(is_enabled(b"-memcpy,+all", b"memcpy"));
+        assert!(is_enabled(b"-strlenx,-strle", b"strlen"));
+    }
+
+    #[test]
+    fn test_disabled_routine_resolves_to_next_definition() {
+        let ours = crate::capi::strlen as unsafe extern "C" fn(*const c_char) -> usize;
+
+        let route = Route::new("strlen\0");
+        assert_eq!(route.resolve_with(b"-all,strlen"), None);
+        assert_eq!(route.next(), None);
+
+        let route = Route::new("strlen\0");
+        let next = route.resolve_with(b"-strlen").expect("libc strlen");
+        assert_ne!(next, ours as *mut c_void);
+        assert_eq!(route.next(), Some(next));
+        // SAFETY: `next` is libc's `strlen`.
+        let next: unsafe extern "C" fn(*const c_char) -> usize =
+            unsafe { core::mem::transmute(next) };
+        assert_eq!(unsafe { next(c"abc".as_ptr()) }, 3);
+    }
+}
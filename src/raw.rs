@@ -0,0 +1,679 @@
+//! Scans of NUL-terminated strings through raw pointers.
+//!
+//! The slice functions in [`crate::str`] and [`crate::search`] need the
+//! extent of their input up front. These take a bare pointer, as handed over
+//! by C, and find the terminator themselves, so a caller never has to bound
+//! the string with a separate `strlen` first.
+//!
+//! Like glibc's string functions, the x86_64 kernels read whole 16-byte
+//! blocks at 16-byte-aligned addresses, which may include bytes before the
+//! string and after its terminator. An aligned block never straddles a page
+//! boundary, so these extra reads stay on pages the string itself occupies
+//! and cannot fault. `strcmp`, which walks two differently aligned strings,
+//! uses unaligned loads only when neither can reach the next page and steps
+//! one byte at a time otherwise. The fallback reads aligned words, and
+//! portable mode reads one byte at a time so Miri sees only in-bounds reads.
+
+#[cfg(target_arch = "x86_64")]
+use crate::cpu::{self, IsaLevel};
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+use core::ffi::c_char;
+
+#[cfg(target_arch = "x86_64")]
+const VEC: usize = 16;
+
+/// Smallest page size of any supported target.
+#[cfg(target_arch = "x86_64")]
+const PAGE: usize = 4096;
+
+const WORD: usize = core::mem::size_of::<usize>();
+const ONES: usize = usize::MAX / 0xFF;
+const HIGHS: usize = ONES << 7;
+
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+fn use_sse2() -> bool {
+    cpu::isa_level() >= IsaLevel::Sse2
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+unsafe fn load(p: *const u8) -> __m128i {
+    // SAFETY: Caller guarantees `p` is 16-byte aligned and on a readable page.
+    unsafe { _mm_load_si128(p as *const __m128i) }
+}
+
+/// Index of the first byte of `s` equal to `c`, or (when `NUL`) to 0.
+///
+/// # Safety
+///
+/// Such a byte must exist, and `s` must be readable up to it.
+#[cfg(target_arch = "x86_64")]
+unsafe fn find_sse2<const NUL: bool>(s: *const u8, c: u8) -> usize {
+    // SAFETY: Every load reads an aligned block holding at least one byte
+    // of the string at or before the match; see the module docs.
+    unsafe {
+        let splat = _mm_set1_epi8(c as i8);
+        let zero = _mm_setzero_si128();
+        let hits = |v: __m128i| {
+            let eq = _mm_cmpeq_epi8(v, splat);
+            if NUL {
+                _mm_or_si128(eq, _mm_cmpeq_epi8(v, zero))
+            } else {
+                eq
+            }
+        };
+
+        // The first block may start before `s`; drop those lanes.
+        let off = s as usize % VEC;
+        let mut p = s.wrapping_sub(off);
+        let m = (_mm_movemask_epi8(hits(load(p))) as u32) >> off;
+        if m != 0 {
+            return m.trailing_zeros() as usize;
+        }
+        p = p.wrapping_add(VEC);
+
+        while !(p as usize).is_multiple_of(4 * VEC) {
+            let m = _mm_movemask_epi8(hits(load(p))) as u32;
+            if m != 0 {
+                return p as usize - s as usize + m.trailing_zeros() as usize;
+            }
+            p = p.wrapping_add(VEC);
+        }
+
+        // 64-byte blocks are aligned too, so four loads share one page.
+        loop {
+            let h0 = hits(load(p));
+            let h1 = hits(load(p.wrapping_add(VEC)));
+            let h2 = hits(load(p.wrapping_add(2 * VEC)));
+            let h3 = hits(load(p.wrapping_add(3 * VEC)));
+            let any = _mm_or_si128(_mm_or_si128(h0, h1), _mm_or_si128(h2, h3));
+            if _mm_movemask_epi8(any) != 0 {
+                let m = (_mm_movemask_epi8(h0) as u64)
+                    | (_mm_movemask_epi8(h1) as u64) << 16
+                    | (_mm_movemask_epi8(h2) as u64) << 32
+                    | (_mm_movemask_epi8(h3) as u64) << 48;
+                return p as usize - s as usize + m.trailing_zeros() as usize;
+            }
+            p = p.wrapping_add(4 * VEC);
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn strnlen_sse2(s: *const u8, maxlen: usize) -> usize {
+    // SAFETY: Each block is aligned and starts at or before byte `i`, which
+    // lies before both the terminator and `maxlen`.
+    unsafe {
+        let zero = _mm_setzero_si128();
+        let off = s as usize % VEC;
+        let m = (_mm_movemask_epi8(_mm_cmpeq_epi8(load(s.wrapping_sub(off)), zero)) as u32) >> off;
+        if m != 0 {
+            return (m.trailing_zeros() as usize).min(maxlen);
+        }
+
+        let mut i = VEC - off;
+        while i < maxlen {
+            let m = _mm_movemask_epi8(_mm_cmpeq_epi8(load(s.wrapping_add(i)), zero)) as u32;
+            if m != 0 {
+                return (i + m.trailing_zeros() as usize).min(maxlen);
+            }
+            i += VEC;
+        }
+    }
+
+    maxlen
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn strrchr_sse2(s: *const u8, c: u8) -> Option<usize> {
+    // SAFETY: Blocks are aligned and read in order up to the one holding the
+    // terminator.
+    unsafe {
+        let splat = _mm_set1_epi8(c as i8);
+        let zero = _mm_setzero_si128();
+        let off = s as usize % VEC;
+        let mut p = s.wrapping_sub(off);
+        let mut last = None;
+        // Lanes before `s` in the first block.
+        let mut skip = off;
+
+        loop {
+            let v = load(p);
+            let nul = (_mm_movemask_epi8(_mm_cmpeq_epi8(v, zero)) as u32) >> skip << skip;
+            let mut eq = (_mm_movemask_epi8(_mm_cmpeq_epi8(v, splat)) as u32) >> skip << skip;
+            if nul != 0 {
+                // Keep matches up to the terminator.
+                eq &= (nul ^ (nul - 1)) & 0xFFFF;
+            }
+            if eq != 0 {
+                last = Some(p as usize + (31 - eq.leading_zeros() as usize) - s as usize);
+            }
+            if nul != 0 {
+                return last;
+            }
+            p = p.wrapping_add(VEC);
+            skip = 0;
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn strcmp_sse2(s1: *const u8, s2: *const u8) -> i32 {
+    // An unaligned 16-byte load at `p` stays on `p`'s page.
+    let fits = |p: *const u8| p as usize % PAGE <= PAGE - VEC;
+    let mut i = 0usize;
+
+    // SAFETY: Vector loads happen only when neither can leave the page that
+    // holds byte `i` of its string; otherwise one byte is compared, and both
+    // strings are readable through their terminators or first difference.
+    unsafe {
+        let zero = _mm_setzero_si128();
+        loop {
+            let (a, b) = (s1.add(i), s2.add(i));
+            if fits(a) && fits(b) {
+                let va = _mm_loadu_si128(a as *const __m128i);
+                let vb = _mm_loadu_si128(b as *const __m128i);
+                let ne = !(_mm_movemask_epi8(_mm_cmpeq_epi8(va, vb)) as u32) & 0xFFFF;
+                let nul = _mm_movemask_epi8(_mm_cmpeq_epi8(va, zero)) as u32;
+                let m = ne | nul;
+                if m != 0 {
+                    let j = m.trailing_zeros() as usize;
+                    return *a.add(j) as i32 - *b.add(j) as i32;
+                }
+                i += VEC;
+            } else {
+                let (x, y) = (*a, *b);
+                if x != y || x == 0 {
+                    return x as i32 - y as i32;
+                }
+                i += 1;
+            }
+        }
+    }
+}
+
+/// Index of the first byte of `s` that is NUL or in the 2-4 byte `set`.
+#[cfg(target_arch = "x86_64")]
+unsafe fn find_small_set_sse2(s: *const u8, set: &[u8]) -> usize {
+    // SAFETY: As in `find_sse2`; the terminator ends the scan.
+    unsafe {
+        let splats = [0, 1, 2, 3].map(|k| _mm_set1_epi8(set[k.min(set.len() - 1)] as i8));
+        let hits = |v: __m128i| {
+            let mut m = _mm_cmpeq_epi8(v, _mm_setzero_si128());
+            for splat in splats {
+                m = _mm_or_si128(m, _mm_cmpeq_epi8(v, splat));
+            }
+            _mm_movemask_epi8(m) as u32
+        };
+
+        let off = s as usize % VEC;
+        let mut p = s.wrapping_sub(off);
+        let m = hits(load(p)) >> off;
+        if m != 0 {
+            return m.trailing_zeros() as usize;
+        }
+        loop {
+            p = p.wrapping_add(VEC);
+            let m = hits(load(p));
+            if m != 0 {
+                return p as usize - s as usize + m.trailing_zeros() as usize;
+            }
+        }
+    }
+}
+
+unsafe fn strnlen_scalar(s: *const u8, maxlen: usize) -> usize {
+    let mut i = 0usize;
+
+    // SAFETY: Caller guarantees `s` is readable up to its terminator or
+    // `maxlen` bytes; whole words are read only at aligned addresses.
+    unsafe {
+        if !crate::cpu::PORTABLE {
+            while i < maxlen && !(s.add(i) as usize).is_multiple_of(WORD) {
+                if *s.add(i) == 0 {
+                    return i;
+                }
+                i += 1;
+            }
+            while maxlen - i >= WORD {
+                let w = *(s.add(i) as *const usize);
+                if (w.wrapping_sub(ONES) & !w & HIGHS) != 0 {
+                    break;
+                }
+                i += WORD;
+            }
+        }
+        while i < maxlen && *s.add(i) != 0 {
+            i += 1;
+        }
+    }
+
+    i
+}
+
+/// Length of the NUL-terminated string at `s`.
+///
+/// # Safety
+///
+/// `s` must point to a NUL-terminated string.
+///
+/// # Examples
+/// ```
+/// assert_eq!(unsafe { faststrings::raw::strlen(c"hello".as_ptr()) }, 5);
+/// ```
+pub unsafe fn strlen(s: *const c_char) -> usize {
+    #[cfg(target_arch = "x86_64")]
+    if use_sse2() {
+        // SAFETY: Forwarded caller contract; the terminator matches.
+        return unsafe { find_sse2::<false>(s as *const u8, 0) };
+    }
+
+    // SAFETY: Forwarded caller contract.
+    unsafe { strnlen_scalar(s as *const u8, usize::MAX) }
+}
+
+/// Length of the string at `s`, but at most `maxlen`.
+///
+/// # Safety
+///
+/// `s` must be readable up to its terminator or for `maxlen` bytes,
+/// whichever comes first.
+pub unsafe fn strnlen(s: *const c_char, maxlen: usize) -> usize {
+    if maxlen == 0 {
+        return 0;
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    if use_sse2() {
+        // SAFETY: Forwarded caller contract.
+        return unsafe { strnlen_sse2(s as *const u8, maxlen) };
+    }
+
+    // SAFETY: Forwarded caller contract.
+    unsafe { strnlen_scalar(s as *const u8, maxlen) }
+}
+
+/// Index of the first `c` in the string at `s`; searching for `0` finds the
+/// terminator.
+///
+/// # Safety
+///
+/// `s` must point to a NUL-terminated string.
+///
+/// # Examples
+/// ```
+/// use faststrings::raw::strchr;
+/// let s = c"hello".as_ptr();
+/// assert_eq!(unsafe { strchr(s, b'l') }, Some(2));
+/// assert_eq!(unsafe { strchr(s, b'z') }, None);
+/// assert_eq!(unsafe { strchr(s, 0) }, Some(5));
+/// ```
+pub unsafe fn strchr(s: *const c_char, c: u8) -> Option<usize> {
+    let s = s as *const u8;
+
+    #[cfg(target_arch = "x86_64")]
+    if use_sse2() {
+        // SAFETY: Forwarded caller contract; the terminator matches.
+        let i = unsafe { find_sse2::<true>(s, c) };
+        // SAFETY: `i` is at or before the terminator.
+        return (unsafe { *s.add(i) } == c).then_some(i);
+    }
+
+    let mut i = 0usize;
+    // SAFETY: Forwarded caller contract; the scan stops at the terminator.
+    unsafe {
+        loop {
+            match *s.add(i) {
+                b if b == c => return Some(i),
+                0 => return None,
+                _ => i += 1,
+            }
+        }
+    }
+}
+
+/// Index of the last `c` in the string at `s`; searching for `0` finds the
+/// terminator.
+///
+/// # Safety
+///
+/// `s` must point to a NUL-terminated string.
+pub unsafe fn strrchr(s: *const c_char, c: u8) -> Option<usize> {
+    if c == 0 {
+        // SAFETY: Forwarded caller contract.
+        return Some(unsafe { strlen(s) });
+    }
+    let s = s as *const u8;
+
+    #[cfg(target_arch = "x86_64")]
+    if use_sse2() {
+        // SAFETY: Forwarded caller contract.
+        return unsafe { strrchr_sse2(s, c) };
+    }
+
+    let mut last = None;
+    let mut i = 0usize;
+    // SAFETY: Forwarded caller contract; the scan stops at the terminator.
+    unsafe {
+        while *s.add(i) != 0 {
+            if *s.add(i) == c {
+                last = Some(i);
+            }
+            i += 1;
+        }
+    }
+    last
+}
+
+/// Compares two NUL-terminated strings, returning the difference of the
+/// first mismatching bytes (as `unsigned char`) or 0.
+///
+/// # Safety
+///
+/// `s1` and `s2` must point to NUL-terminated strings.
+///
+/// # Examples
+/// ```
+/// use faststrings::raw::strcmp;
+/// assert!(unsafe { strcmp(c"abc".as_ptr(), c"abd".as_ptr()) } < 0);
+/// assert_eq!(unsafe { strcmp(c"abc".as_ptr(), c"abc".as_ptr()) }, 0);
+/// ```
+pub unsafe fn strcmp(s1: *const c_char, s2: *const c_char) -> i32 {
+    let (s1, s2) = (s1 as *const u8, s2 as *const u8);
+
+    #[cfg(target_arch = "x86_64")]
+    if use_sse2() {
+        // SAFETY: Forwarded caller contract.
+        return unsafe { strcmp_sse2(s1, s2) };
+    }
+
+    let mut i = 0usize;
+    // SAFETY: Forwarded caller contract; the scan stops at the first
+    // terminator or difference.
+    unsafe {
+        loop {
+            let (a, b) = (*s1.add(i), *s2.add(i));
+            if a != b || a == 0 {
+                return a as i32 - b as i32;
+            }
+            i += 1;
+        }
+    }
+}
+
+/// Length of the prefix of the string at `s` made of bytes not in the
+/// string at `reject`.
+///
+/// # Safety
+///
+/// `s` and `reject` must point to NUL-terminated strings.
+///
+/// # Examples
+/// ```
+/// use faststrings::raw::strcspn;
+/// assert_eq!(unsafe { strcspn(c"hello".as_ptr(), c"lo".as_ptr()) }, 2);
+/// assert_eq!(unsafe { strcspn(c"hello".as_ptr(), c"xyz".as_ptr()) }, 5);
+/// ```
+pub unsafe fn strcspn(s: *const c_char, reject: *const c_char) -> usize {
+    // SAFETY: Forwarded caller contract.
+    let reject = unsafe { core::slice::from_raw_parts(reject as *const u8, strlen(reject)) };
+    match reject.len() {
+        // SAFETY: Forwarded caller contract.
+        0 => return unsafe { strlen(s) },
+        // SAFETY: Forwarded caller contract; the terminator matches.
+        1 => return unsafe { strchrnul(s as *const u8, reject[0]) },
+        #[cfg(target_arch = "x86_64")]
+        2..=4 if use_sse2() => {
+            // SAFETY: Forwarded caller contract.
+            return unsafe { find_small_set_sse2(s as *const u8, reject) };
+        }
+        _ => {}
+    }
+
+    let mut set = [false; 256];
+    set[0] = true;
+    for &b in reject {
+        set[b as usize] = true;
+    }
+    let s = s as *const u8;
+    let mut i = 0usize;
+    // SAFETY: Forwarded caller contract; the scan stops at the terminator.
+    while !set[unsafe { *s.add(i) } as usize] {
+        i += 1;
+    }
+    i
+}
+
+/// Index of the first `c` or terminator in the string at `s`.
+unsafe fn strchrnul(s: *const u8, c: u8) -> usize {
+    #[cfg(target_arch = "x86_64")]
+    if use_sse2() {
+        // SAFETY: Forwarded caller contract; the terminator matches.
+        return unsafe { find_sse2::<true>(s, c) };
+    }
+
+    let mut i = 0usize;
+    // SAFETY: Forwarded caller contract; the scan stops at the terminator.
+    while !matches!(unsafe { *s.add(i) }, 0) && unsafe { *s.add(i) } != c {
+        i += 1;
+    }
+    i
+}
+
+/// Index of the first `c` at or after `s`, with no length bound.
+///
+/// # Safety
+///
+/// A byte equal to `c` must exist, with every byte before it readable.
+///
+/// # Examples
+/// ```
+/// let s = b"key=value";
+/// assert_eq!(unsafe { faststrings::raw::rawmemchr(s.as_ptr(), b'=') }, 3);
+/// ```
+pub unsafe fn rawmemchr(s: *const u8, c: u8) -> usize {
+    #[cfg(target_arch = "x86_64")]
+    if use_sse2() {
+        // SAFETY: Forwarded caller contract.
+        return unsafe { find_sse2::<false>(s, c) };
+    }
+
+    let mut i = 0usize;
+    // SAFETY: Forwarded caller contract; the scan stops at the match.
+    while unsafe { *s.add(i) } != c {
+        i += 1;
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_all(s: &[u8], set: &[u8]) {
+        let p = s.as_ptr() as *const c_char;
+        let len = s.iter().position(|&b| b == 0).unwrap();
+        let body = &s[..len];
+        unsafe {
+            assert_eq!(strlen(p), len);
+            assert_eq!(strnlen(p, len / 2), len / 2);
+            assert_eq!(strnlen(p, len + 7), len);
+            assert_eq!(rawmemchr(s.as_ptr(), 0), len);
+            for c in [b'a', b'b', b'z', 0] {
+                let first = s[..=len].iter().position(|&b| b == c);
+                let last = s[..=len].iter().rposition(|&b| b == c);
+                assert_eq!(strchr(p, c), first, "len={len} c={c}");
+                assert_eq!(strrchr(p, c), last, "len={len} c={c}");
+            }
+            let set_ptr = set.as_ptr() as *const c_char;
+            let set = &set[..set.len() - 1];
+            let span = body.iter().position(|b| set.contains(b)).unwrap_or(len);
+            assert_eq!(strcspn(p, set_ptr), span, "len={len} set={set:?}");
+        }
+    }
+
+    #[test]
+    fn test_every_alignment_and_length() {
+        let mut buf = [0u8; 160];
+        for start in 0..16 {
+            for len in 0..96 {
+                buf.fill(0xFF);
+                for (i, b) in buf[start..start + len].iter_mut().enumerate() {
+                    *b = b"xyabz"[i % 5] ^ ((i % 7 == 0) as u8);
+                }
+                buf[start + len] = 0;
+                let s = &buf[start..];
+                for set in [&b"\0"[..], b"b\0", b"bq\0", b"zqa\0", b"qrst\0", b"qrstz\0"] {
+                    check_all(s, set);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_strcmp_every_alignment() {
+        let mut a = [0u8; 128];
+        let mut b = [0u8; 128];
+        for (sa, sb) in [(0, 0), (1, 0), (3, 13), (15, 8)] {
+            for len in 0..70 {
+                a.fill(0);
+                b.fill(0);
+                for i in 0..len {
+                    a[sa + i] = b'a' + (i % 26) as u8;
+                    b[sb + i] = b'a' + (i % 26) as u8;
+                }
+                let cmp = |a: &[u8], b: &[u8]| unsafe {
+                    strcmp(a[sa..].as_ptr().cast(), b[sb..].as_ptr().cast())
+                };
+                assert_eq!(cmp(&a, &b), 0);
+                if len > 0 {
+                    b[sb + len - 1] = 0xF0;
+                    assert_eq!(cmp(&a, &b), a[sa + len - 1] as i32 - 0xF0);
+                    b[sb + len - 1] = 0;
+                    assert_eq!(cmp(&a, &b), a[sa + len - 1] as i32);
+                }
+            }
+        }
+    }
+
+    #[cfg(all(target_os = "linux", not(miri)))]
+    mod guard_page {
+        use super::*;
+        use core::ffi::{c_int, c_long, c_void};
+
+        unsafe extern "C" {
+            fn mmap(
+                addr: *mut c_void,
+                len: usize,
+                prot: c_int,
+                flags: c_int,
+                fd: c_int,
+                off: i64,
+            ) -> *mut c_void;
+            fn mprotect(addr: *mut c_void, len: usize, prot: c_int) -> c_int;
+            fn munmap(addr: *mut c_void, len: usize) -> c_int;
+            fn sysconf(name: c_int) -> c_long;
+        }
+
+        const PROT_NONE: c_int = 0;
+        const PROT_READ_WRITE: c_int = 3;
+        const MAP_PRIVATE_ANONYMOUS: c_int = 0x22;
+        const SC_PAGESIZE: c_int = 30;
+
+        /// One read-write page between two inaccessible ones.
+        struct Fenced {
+            base: *mut u8,
+            page: usize,
+        }
+
+        impl Fenced {
+            fn new() -> Self {
+                unsafe {
+                    let page = sysconf(SC_PAGESIZE) as usize;
+                    let base = mmap(
+                        core::ptr::null_mut(),
+                        3 * page,
+                        PROT_READ_WRITE,
+                        MAP_PRIVATE_ANONYMOUS,
+                        -1,
+                        0,
+                    );
+                    assert_ne!(base as isize, -1, "mmap failed");
+                    assert_eq!(mprotect(base, page, PROT_NONE), 0);
+                    assert_eq!(
+                        mprotect(base.cast::<u8>().add(2 * page).cast(), page, PROT_NONE),
+                        0
+                    );
+                    Self {
+                        base: base.cast(),
+                        page,
+                    }
+                }
+            }
+
+            fn page(&mut self) -> &mut [u8] {
+                unsafe { core::slice::from_raw_parts_mut(self.base.add(self.page), self.page) }
+            }
+        }
+
+        impl Drop for Fenced {
+            fn drop(&mut self) {
+                unsafe { munmap(self.base.cast(), 3 * self.page) };
+            }
+        }
+
+        /// Writes a `len`-byte string ending at the last byte of the page,
+        /// and one starting at its first byte.
+        fn place(page: &mut [u8], len: usize) -> (usize, usize) {
+            let end = page.len() - len - 1;
+            page.fill(0x55);
+            for (i, b) in page[end..].iter_mut().enumerate() {
+                *b = b"xyabz"[i % 5];
+            }
+            *page.last_mut().unwrap() = 0;
+            for (i, b) in page[..len].iter_mut().enumerate() {
+                *b = b"xyabz"[i % 5];
+            }
+            page[len] = 0;
+            (end, 0)
+        }
+
+        #[test]
+        fn test_scans_stop_at_guard_pages() {
+            let mut fenced = Fenced::new();
+            let page = fenced.page();
+            for len in 0..200 {
+                let (tail, head) = place(page, len);
+                for start in [tail, head] {
+                    check_all(&page[start..], b"qb\0");
+                    check_all(&page[start..], b"qrsta\0");
+                }
+            }
+        }
+
+        #[test]
+        fn test_strcmp_at_guard_pages() {
+            let mut fa = Fenced::new();
+            let mut fb = Fenced::new();
+            for len in 0..100 {
+                let (a_tail, a_head) = place(fa.page(), len);
+                for b_len in [len, len + 1, len + 17] {
+                    let (b_tail, b_head) = place(fb.page(), b_len);
+                    let want = if b_len == len {
+                        0
+                    } else {
+                        -(b"xyabz"[len % 5] as i32)
+                    };
+                    for (sa, sb) in [(a_tail, b_tail), (a_head, b_tail), (a_tail, b_head)] {
+                        let pa = fa.page()[sa..].as_ptr().cast();
+                        let pb = fb.page()[sb..].as_ptr().cast();
+                        assert_eq!(unsafe { strcmp(pa, pb) }, want, "len={len} b_len={b_len}");
+                        assert_eq!(unsafe { strcmp(pb, pa) }, -want);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -4,9 +4,39 @@
 
 use crate::str::strlen;
 
+#[cfg(target_arch = "x86_64")]
+use crate::cpu::{self, IsaLevel};
+#[cfg(target_arch = "x86_64")]
+use crate::dispatch::FnSlot;
 #[cfg(target_arch = "x86_64")]
 use core::arch::x86_64::*;
 
+#[cfg(target_arch = "x86_64")]
+type SpanFn = unsafe fn(&[u8], &[u8]) -> usize;
+#[cfg(target_arch = "x86_64")]
+type FindAnyFn = unsafe fn(&[u8], &[u8]) -> Option<usize>;
+
+/// Small-set (1-4 byte) span kernel, chosen on first use.
+#[cfg(target_arch = "x86_64")]
+static SPAN_SMALL_SET: FnSlot<SpanFn> = FnSlot::new(resolve_span_small_set as *mut ());
+
+/// Small-set (1-4 byte) find-any kernel, chosen on first use.
+#[cfg(target_arch = "x86_64")]
+static FIND_ANY_SMALL_SET: FnSlot<FindAnyFn> = FnSlot::new(resolve_find_any_small_set as *mut ());
+
+#[cfg(target_arch = "x86_64")]
+type SetScanFn = unsafe fn(&[u8], &PackedSet, bool) -> usize;
+#[cfg(target_arch = "x86_64")]
+type SubstrFn = unsafe fn(&[u8], &[u8]) -> Option<usize>;
+
+/// Packed-set (`EQUAL_ANY`/`RANGES`) scan kernel, chosen on first use.
+#[cfg(target_arch = "x86_64")]
+static SET_SCAN: FnSlot<SetScanFn> = FnSlot::new(resolve_set_scan as *mut ());
+
+/// Substring search kernel for needles of two bytes and up, chosen on first use.
+#[cfg(target_arch = "x86_64")]
+static STRSTR_SCAN: FnSlot<SubstrFn> = FnSlot::new(resolve_strstr_scan as *mut ());
+
 #[inline(always)]
 fn build_byte_bitmap(set: &[u8]) -> [u64; 4] {
     let mut bitmap = [0u64; 4];
@@ -36,6 +66,526 @@ fn contains_small_set(set: &[u8], c: u8) -> bool {
     }
 }
 
+/// A byte set in the operand form of the SSE4.2 string instructions: either
+/// up to 16 member bytes (`EQUAL_ANY`) or up to 8 inclusive `lo, hi` pairs
+/// (`RANGES`). Range form lets large but contiguous sets such as
+/// `[A-Za-z0-9_]` use the same single-instruction scan.
+#[cfg(target_arch = "x86_64")]
+#[derive(Clone, Copy)]
+struct PackedSet {
+    bytes: [u8; 16],
+    len: u8,
+    ranges: bool,
+}
+
+#[cfg(target_arch = "x86_64")]
+impl PackedSet {
+    /// Packs `set` (whose bitmap is `bitmap`), or returns `None` when it has
+    /// more than 16 bytes and more than 8 runs.
+    fn new(set: &[u8], bitmap: &[u64; 4]) -> Option<Self> {
+        let mut bytes = [0u8; 16];
+        if set.len() <= 16 {
+            bytes[..set.len()].copy_from_slice(set);
+            return Some(Self {
+                bytes,
+                len: set.len() as u8,
+                ranges: false,
+            });
+        }
+
+        // A run starts at a set bit whose lower neighbour is clear and ends at
+        // a set bit whose upper neighbour is clear.
+        let mut len = 0usize;
+        let mut carry_in = 0u64;
+        let mut lo = 0u8;
+        for (w, &word) in bitmap.iter().enumerate() {
+            let next_low = bitmap.get(w + 1).map_or(0, |&next| next & 1);
+            let mut starts = word & !((word << 1) | carry_in);
+            let mut ends = word & !((word >> 1) | (next_low << 63));
+            carry_in = word >> 63;
+
+            while starts != 0 || ends != 0 {
+                let start_bit = starts.trailing_zeros();
+                let end_bit = ends.trailing_zeros();
+                if start_bit <= end_bit && starts != 0 {
+                    lo = (w as u32 * 64 + start_bit) as u8;
+                    starts &= starts - 1;
+                } else {
+                    if len == 16 {
+                        return None;
+                    }
+                    bytes[len] = lo;
+                    bytes[len + 1] = (w as u32 * 64 + end_bit) as u8;
+                    len += 2;
+                    ends &= ends - 1;
+                }
+            }
+        }
+
+        Some(Self {
+            bytes,
+            len: len as u8,
+            ranges: true,
+        })
+    }
+
+    fn to_bitmap(self) -> [u64; 4] {
+        let operand = &self.bytes[..self.len as usize];
+        if !self.ranges {
+            return build_byte_bitmap(operand);
+        }
+        let mut bitmap = [0u64; 4];
+        for pair in operand.chunks_exact(2) {
+            for byte in pair[0]..=pair[1] {
+                bitmap[(byte >> 6) as usize] |= 1u64 << (byte & 63);
+            }
+        }
+        bitmap
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn select_span_small_set(level: IsaLevel) -> SpanFn {
+    match level {
+        IsaLevel::Avx512 => strspn_small_set_avx512,
+        IsaLevel::Avx2 => strspn_small_set_avx2,
+        IsaLevel::Sse42 | IsaLevel::Sse2 => strspn_small_set_sse2,
+        IsaLevel::Scalar => strspn_small_set_scalar_wide,
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn select_find_any_small_set(level: IsaLevel) -> FindAnyFn {
+    match level {
+        IsaLevel::Avx512 => find_first_any_small_set_avx512,
+        IsaLevel::Avx2 => find_first_any_small_set_avx2,
+        IsaLevel::Sse42 | IsaLevel::Sse2 => find_first_any_small_set_sse2,
+        IsaLevel::Scalar => find_first_any_small_set_scalar_wide,
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn resolve_span_small_set(s: &[u8], accept: &[u8]) -> usize {
+    let f = select_span_small_set(cpu::isa_level());
+    SPAN_SMALL_SET.set(f);
+    // SAFETY: Forwarded caller contract.
+    unsafe { f(s, accept) }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn resolve_find_any_small_set(s: &[u8], set: &[u8]) -> Option<usize> {
+    let f = select_find_any_small_set(cpu::isa_level());
+    FIND_ANY_SMALL_SET.set(f);
+    // SAFETY: Forwarded caller contract.
+    unsafe { f(s, set) }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn select_set_scan(level: IsaLevel) -> SetScanFn {
+    match level {
+        IsaLevel::Avx512 | IsaLevel::Avx2 | IsaLevel::Sse42 => set_scan_sse42,
+        IsaLevel::Sse2 | IsaLevel::Scalar => set_scan_bitmap,
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn select_strstr_scan(level: IsaLevel) -> SubstrFn {
+    match level {
+        IsaLevel::Avx512 | IsaLevel::Avx2 | IsaLevel::Sse42 => strstr_sse42,
+        IsaLevel::Sse2 | IsaLevel::Scalar => strstr_memmem,
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn resolve_set_scan(s: &[u8], set: &PackedSet, span: bool) -> usize {
+    let f = select_set_scan(cpu::isa_level());
+    SET_SCAN.set(f);
+    // SAFETY: Forwarded caller contract.
+    unsafe { f(s, set, span) }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn resolve_strstr_scan(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let f = select_strstr_scan(cpu::isa_level());
+    STRSTR_SCAN.set(f);
+    // SAFETY: Forwarded caller contract.
+    unsafe { f(haystack, needle) }
+}
+
+/// Index of the first byte of `s` outside (`span`) or inside (`!span`) `set`,
+/// or `s.len()`.
+#[cfg(target_arch = "x86_64")]
+fn set_scan_bitmap(s: &[u8], set: &PackedSet, span: bool) -> usize {
+    let bitmap = set.to_bitmap();
+    s.iter()
+        .position(|&c| bitmap_contains(&bitmap, c) != span)
+        .unwrap_or(s.len())
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.2")]
+fn set_scan_sse42(s: &[u8], set: &PackedSet, span: bool) -> usize {
+    // Masked negative polarity flips only the valid haystack lanes, so the
+    // padding of a short final chunk never reads as a span break.
+    const ANY: i32 = _SIDD_UBYTE_OPS | _SIDD_CMP_EQUAL_ANY;
+    const RANGES: i32 = _SIDD_UBYTE_OPS | _SIDD_CMP_RANGES;
+    match (set.ranges, span) {
+        (false, false) => set_scan_sse42_mode::<ANY>(s, set),
+        (false, true) => set_scan_sse42_mode::<{ ANY | _SIDD_MASKED_NEGATIVE_POLARITY }>(s, set),
+        (true, false) => set_scan_sse42_mode::<RANGES>(s, set),
+        (true, true) => set_scan_sse42_mode::<{ RANGES | _SIDD_MASKED_NEGATIVE_POLARITY }>(s, set),
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.2")]
+fn set_scan_sse42_mode<const MODE: i32>(s: &[u8], set: &PackedSet) -> usize {
+    let len = s.len();
+    // SAFETY: `set.bytes` is a 16-byte array.
+    let a = unsafe { _mm_loadu_si128(set.bytes.as_ptr() as *const __m128i) };
+    let la = set.len as i32;
+    let mut i = 0usize;
+
+    while i + 16 <= len {
+        // SAFETY: `i + 16 <= s.len()`, and unaligned loads accept any alignment.
+        let b = unsafe { _mm_loadu_si128(s.as_ptr().add(i) as *const __m128i) };
+        let idx = _mm_cmpestri::<MODE>(a, la, b, 16) as usize;
+        if idx < 16 {
+            return i + idx;
+        }
+        i += 16;
+    }
+
+    if i < len {
+        let rem = len - i;
+        let mut tail = [0u8; 16];
+        tail[..rem].copy_from_slice(&s[i..]);
+        // SAFETY: `tail` is a 16-byte array.
+        let b = unsafe { _mm_loadu_si128(tail.as_ptr() as *const __m128i) };
+        let idx = _mm_cmpestri::<MODE>(a, la, b, rem as i32) as usize;
+        if idx < rem {
+            return i + idx;
+        }
+    }
+
+    len
+}
+
+/// `strstr` for `2 <= needle.len() <= haystack.len()` on top of `memmem`.
+#[cfg(target_arch = "x86_64")]
+fn strstr_memmem(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    crate::mem::memmem(haystack, needle)
+}
+
+/// `strstr` for `2 <= needle.len() <= haystack.len()`.
+///
+/// `EQUAL_ORDERED` reports the first offset in each 16-byte window where the
+/// needle's first (up to) 16 bytes match, including a prefix that runs off
+/// the end of the window, so every real match is reported as a candidate and
+/// only candidates are compared in full.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.2")]
+fn strstr_sse42(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    const ORDERED: i32 = _SIDD_UBYTE_OPS | _SIDD_CMP_EQUAL_ORDERED;
+
+    let n_len = needle.len();
+    let prefix_len = n_len.min(16);
+    let mut prefix = [0u8; 16];
+    prefix[..prefix_len].copy_from_slice(&needle[..prefix_len]);
+    // SAFETY: `prefix` is a 16-byte array.
+    let a = unsafe { _mm_loadu_si128(prefix.as_ptr() as *const __m128i) };
+
+    let h_len = haystack.len();
+    let last_start = h_len - n_len;
+    let mut i = 0usize;
+
+    while i <= last_start {
+        let avail = h_len - i;
+        let idx = if avail >= 16 {
+            // SAFETY: `i + 16 <= haystack.len()`.
+            let b = unsafe { _mm_loadu_si128(haystack.as_ptr().add(i) as *const __m128i) };
+            _mm_cmpestri::<ORDERED>(a, prefix_len as i32, b, 16) as usize
+        } else {
+            let mut tail = [0u8; 16];
+            tail[..avail].copy_from_slice(&haystack[i..]);
+            // SAFETY: `tail` is a 16-byte array.
+            let b = unsafe { _mm_loadu_si128(tail.as_ptr() as *const __m128i) };
+            _mm_cmpestri::<ORDERED>(a, prefix_len as i32, b, avail as i32) as usize
+        };
+
+        if idx == 16 {
+            i += 16;
+            continue;
+        }
+
+        let cand = i + idx;
+        if cand > last_start {
+            return None;
+        }
+        if haystack[cand..cand + n_len] == *needle {
+            return Some(cand);
+        }
+        i = cand + 1;
+    }
+
+    None
+}
+
+const SWAR_LOW7: u64 = 0x7F7F_7F7F_7F7F_7F7F;
+const SWAR_HIGHS: u64 = 0x8080_8080_8080_8080;
+
+/// Sets the high bit of every byte of `x` that is zero, and nothing else.
+///
+/// Unlike the classic `(x - 0x01..) & !x & 0x80..` test this never flags a
+/// byte because of a borrow from its neighbour, so the lowest set bit gives
+/// the exact position of the first zero byte.
+#[inline(always)]
+fn zero_byte_mask_u64(x: u64) -> u64 {
+    !((x & SWAR_LOW7).wrapping_add(SWAR_LOW7) | x | SWAR_LOW7)
+}
+
+#[cfg(any(target_arch = "x86_64", not(feature = "std-simd")))]
+#[inline(always)]
+fn small_set_splat_u64(set: &[u8]) -> [u64; 4] {
+    let mut out = [0u64; 4];
+    for (slot, &byte) in out.iter_mut().zip(set) {
+        *slot = u64::from_ne_bytes([byte; 8]);
+    }
+    out
+}
+
+/// Marks (with the high bit) every byte of `word` found in a 1-4 byte set.
+#[cfg(any(target_arch = "x86_64", not(feature = "std-simd")))]
+#[inline(always)]
+fn small_set_match_mask_u64(word: u64, splat: &[u64; 4], set_len: usize) -> u64 {
+    let mut mask = zero_byte_mask_u64(word ^ splat[0]);
+    for &s in &splat[1..set_len] {
+        mask |= zero_byte_mask_u64(word ^ s);
+    }
+    mask
+}
+
+#[cfg(any(target_arch = "x86_64", not(feature = "std-simd")))]
+fn strspn_small_set_scalar_wide(s: &[u8], accept: &[u8]) -> usize {
+    let splat = small_set_splat_u64(accept);
+    let mut chunks = s.chunks_exact(8);
+    let mut i = 0usize;
+
+    for chunk in &mut chunks {
+        // Little-endian load so byte `k` of the chunk lands in bits 8k..8k+8.
+        let word = u64::from_le_bytes(chunk.try_into().unwrap());
+        let miss = !small_set_match_mask_u64(word, &splat, accept.len()) & SWAR_HIGHS;
+        if miss != 0 {
+            return i + (miss.trailing_zeros() / 8) as usize;
+        }
+        i += 8;
+    }
+
+    for &c in chunks.remainder() {
+        if !contains_small_set(accept, c) {
+            return i;
+        }
+        i += 1;
+    }
+
+    i
+}
+
+#[cfg(any(target_arch = "x86_64", not(feature = "std-simd")))]
+fn find_first_any_small_set_scalar_wide(s: &[u8], set: &[u8]) -> Option<usize> {
+    let splat = small_set_splat_u64(set);
+    let mut chunks = s.chunks_exact(8);
+    let mut i = 0usize;
+
+    for chunk in &mut chunks {
+        let word = u64::from_le_bytes(chunk.try_into().unwrap());
+        let hit = small_set_match_mask_u64(word, &splat, set.len());
+        if hit != 0 {
+            return Some(i + (hit.trailing_zeros() / 8) as usize);
+        }
+        i += 8;
+    }
+
+    for &c in chunks.remainder() {
+        if contains_small_set(set, c) {
+            return Some(i);
+        }
+        i += 1;
+    }
+
+    None
+}
+
+/// Builds the SSE2 equality mask of `chunk` against a 1-4 byte set.
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+unsafe fn small_set_eq_mask_sse2(chunk: __m128i, set: &[__m128i; 4], set_len: usize) -> u32 {
+    // SAFETY: SSE2 is baseline on x86_64.
+    unsafe {
+        let mut eq = _mm_cmpeq_epi8(chunk, set[0]);
+        if set_len >= 2 {
+            eq = _mm_or_si128(eq, _mm_cmpeq_epi8(chunk, set[1]));
+        }
+        if set_len >= 3 {
+            eq = _mm_or_si128(eq, _mm_cmpeq_epi8(chunk, set[2]));
+        }
+        if set_len >= 4 {
+            eq = _mm_or_si128(eq, _mm_cmpeq_epi8(chunk, set[3]));
+        }
+        _mm_movemask_epi8(eq) as u32
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+unsafe fn small_set_splat_sse2(set: &[u8]) -> [__m128i; 4] {
+    // SAFETY: SSE2 is baseline on x86_64.
+    unsafe {
+        let mut out = [_mm_setzero_si128(); 4];
+        for (slot, &byte) in out.iter_mut().zip(set) {
+            *slot = _mm_set1_epi8(byte as i8);
+        }
+        out
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn strspn_small_set_sse2(s: &[u8], accept: &[u8]) -> usize {
+    let len = s.len();
+    let set = unsafe { small_set_splat_sse2(accept) };
+    let mut i = 0usize;
+
+    while i + 16 <= len {
+        // SAFETY: `i + 16 <= s.len()`, and unaligned loads accept any alignment.
+        let mask = unsafe {
+            let chunk = _mm_loadu_si128(s.as_ptr().add(i) as *const __m128i);
+            small_set_eq_mask_sse2(chunk, &set, accept.len())
+        };
+        if mask != 0xFFFF {
+            return i + (!mask).trailing_zeros() as usize;
+        }
+        i += 16;
+    }
+
+    while i < len {
+        if !contains_small_set(accept, s[i]) {
+            return i;
+        }
+        i += 1;
+    }
+
+    len
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn find_first_any_small_set_sse2(s: &[u8], set: &[u8]) -> Option<usize> {
+    let len = s.len();
+    let splat = unsafe { small_set_splat_sse2(set) };
+    let mut i = 0usize;
+
+    while i + 16 <= len {
+        // SAFETY: `i + 16 <= s.len()`, and unaligned loads accept any alignment.
+        let mask = unsafe {
+            let chunk = _mm_loadu_si128(s.as_ptr().add(i) as *const __m128i);
+            small_set_eq_mask_sse2(chunk, &splat, set.len())
+        };
+        if mask != 0 {
+            return Some(i + mask.trailing_zeros() as usize);
+        }
+        i += 16;
+    }
+
+    while i < len {
+        if contains_small_set(set, s[i]) {
+            return Some(i);
+        }
+        i += 1;
+    }
+
+    None
+}
+
+/// Marks the lanes of `chunk` equal to any of the first `set_len` splats.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512bw,avx512vl")]
+fn small_set_eq_mask_avx512(chunk: __m512i, set: &[__m512i; 4], set_len: usize) -> __mmask64 {
+    let mut eq = _mm512_cmpeq_epi8_mask(chunk, set[0]);
+    for &splat in &set[1..set_len] {
+        eq |= _mm512_cmpeq_epi8_mask(chunk, splat);
+    }
+    eq
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512bw,avx512vl")]
+fn small_set_splat_avx512(set: &[u8]) -> [__m512i; 4] {
+    let mut out = [_mm512_setzero_si512(); 4];
+    for (slot, &byte) in out.iter_mut().zip(set) {
+        *slot = _mm512_set1_epi8(byte as i8);
+    }
+    out
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512bw,avx512vl")]
+unsafe fn strspn_small_set_avx512(s: &[u8], accept: &[u8]) -> usize {
+    let len = s.len();
+    let set = small_set_splat_avx512(accept);
+    let mut i = 0usize;
+
+    while i + 64 <= len {
+        // SAFETY: `i + 64 <= s.len()`, and unaligned loads accept any alignment.
+        let chunk = unsafe { _mm512_loadu_si512(s.as_ptr().add(i) as *const __m512i) };
+        let mask = small_set_eq_mask_avx512(chunk, &set, accept.len());
+        if mask != u64::MAX {
+            return i + (!mask).trailing_zeros() as usize;
+        }
+        i += 64;
+    }
+
+    if i < len {
+        let k = u64::MAX >> (64 - (len - i));
+        // SAFETY: The masked load only reads the `len - i` bytes left in `s`.
+        let chunk = unsafe { _mm512_maskz_loadu_epi8(k, s.as_ptr().add(i) as *const i8) };
+        let miss = !small_set_eq_mask_avx512(chunk, &set, accept.len()) & k;
+        if miss != 0 {
+            return i + miss.trailing_zeros() as usize;
+        }
+    }
+
+    len
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512bw,avx512vl")]
+unsafe fn find_first_any_small_set_avx512(s: &[u8], set: &[u8]) -> Option<usize> {
+    let len = s.len();
+    let splat = small_set_splat_avx512(set);
+    let mut i = 0usize;
+
+    while i + 64 <= len {
+        // SAFETY: `i + 64 <= s.len()`, and unaligned loads accept any alignment.
+        let chunk = unsafe { _mm512_loadu_si512(s.as_ptr().add(i) as *const __m512i) };
+        let mask = small_set_eq_mask_avx512(chunk, &splat, set.len());
+        if mask != 0 {
+            return Some(i + mask.trailing_zeros() as usize);
+        }
+        i += 64;
+    }
+
+    if i < len {
+        let k = u64::MAX >> (64 - (len - i));
+        // SAFETY: The masked load only reads the `len - i` bytes left in `s`.
+        let chunk = unsafe { _mm512_maskz_loadu_epi8(k, s.as_ptr().add(i) as *const i8) };
+        let hit = small_set_eq_mask_avx512(chunk, &splat, set.len()) & k;
+        if hit != 0 {
+            return Some(i + hit.trailing_zeros() as usize);
+        }
+    }
+
+    None
+}
+
 #[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn strspn_small_set_avx2(s: &[u8], accept: &[u8]) -> usize {
@@ -212,31 +762,47 @@ pub fn strrchr(s: &[u8], c: u8) -> Option<usize> {
 /// assert_eq!(strstr(b"hello\0", b"\0"), Some(0)); // empty needle
 /// ```
 pub fn strstr(haystack: &[u8], needle: &[u8]) -> Option<usize> {
-    let h_len = strlen(haystack);
-    let n_len = strlen(needle);
+    strstr_bytes(&haystack[..strlen(haystack)], &needle[..strlen(needle)])
+}
 
-    if n_len == 0 {
+/// [`strstr`] of two strings given without their terminators.
+pub(crate) fn strstr_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
         return Some(0);
     }
 
-    if n_len > h_len {
+    if needle.len() > haystack.len() {
         return None;
     }
 
-    let haystack = &haystack[..h_len.min(haystack.len())];
-    let needle = &needle[..n_len.min(needle.len())];
+    if needle.len() == 1 {
+        return crate::mem::memchr(haystack, needle[0]);
+    }
 
-    let end = h_len - n_len + 1;
+    #[cfg(target_arch = "x86_64")]
+    {
+        // SAFETY: `2 <= needle.len() <= haystack.len()`; the slot holds a
+        // kernel supported by the running CPU.
+        unsafe { (STRSTR_SCAN.get())(haystack, needle) }
+    }
 
-    (0..end).find(|&i| &haystack[i..i + n_len] == needle)
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        crate::mem::memmem(haystack, needle)
+    }
 }
 
 /// Locate substring (case-insensitive)
 ///
 /// Like strstr, but ignores case when comparing.
 pub fn strcasestr(haystack: &[u8], needle: &[u8]) -> Option<usize> {
-    let h_len = strlen(haystack);
-    let n_len = strlen(needle);
+    strcasestr_bytes(&haystack[..strlen(haystack)], &needle[..strlen(needle)])
+}
+
+/// [`strcasestr`] of two strings given without their terminators.
+pub(crate) fn strcasestr_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let h_len = haystack.len();
+    let n_len = needle.len();
 
     if n_len == 0 {
         return Some(0);
@@ -246,9 +812,6 @@ pub fn strcasestr(haystack: &[u8], needle: &[u8]) -> Option<usize> {
         return None;
     }
 
-    let haystack = &haystack[..h_len.min(haystack.len())];
-    let needle = &needle[..n_len.min(needle.len())];
-
     let end = h_len - n_len + 1;
 
     'outer: for i in 0..end {
@@ -277,11 +840,11 @@ pub fn strcasestr(haystack: &[u8], needle: &[u8]) -> Option<usize> {
 /// assert_eq!(strspn(b"hello\0", b"xyz\0"), 0);
 /// ```
 pub fn strspn(s: &[u8], accept: &[u8]) -> usize {
-    let s_len = strlen(s);
-    let accept_len = strlen(accept);
-    let s = &s[..s_len.min(s.len())];
-    let accept = &accept[..accept_len.min(accept.len())];
+    strspn_bytes(&s[..strlen(s)], &accept[..strlen(accept)])
+}
 
+/// [`strspn`] of two strings given without their terminators.
+pub(crate) fn strspn_bytes(s: &[u8], accept: &[u8]) -> usize {
     if accept.is_empty() {
         return 0;
     }
@@ -289,8 +852,17 @@ pub fn strspn(s: &[u8], accept: &[u8]) -> usize {
     if accept.len() <= 4 {
         #[cfg(target_arch = "x86_64")]
         if s.len() >= 32 {
-            // SAFETY: AVX2 is baseline for this project.
-            return unsafe { strspn_small_set_avx2(s, accept) };
+            // SAFETY: The kernel only requires 1-4 set bytes; the slot holds
+            // a kernel supported by the running CPU.
+            return unsafe { (SPAN_SMALL_SET.get())(s, accept) };
+        }
+        #[cfg(all(not(target_arch = "x86_64"), feature = "std-simd"))]
+        if s.len() >= 16 {
+            return crate::stdsimd::strspn_small_set(s, accept);
+        }
+        #[cfg(all(not(target_arch = "x86_64"), not(feature = "std-simd")))]
+        if s.len() >= 16 {
+            return strspn_small_set_scalar_wide(s, accept);
         }
 
         for (i, &c) in s.iter().enumerate() {
@@ -302,6 +874,15 @@ pub fn strspn(s: &[u8], accept: &[u8]) -> usize {
     }
 
     let bitmap = build_byte_bitmap(accept);
+
+    #[cfg(target_arch = "x86_64")]
+    if s.len() >= 16
+        && let Some(set) = PackedSet::new(accept, &bitmap)
+    {
+        // SAFETY: The slot holds a kernel supported by the running CPU.
+        return unsafe { (SET_SCAN.get())(s, &set, true) };
+    }
+
     for (i, &c) in s.iter().enumerate() {
         if !bitmap_contains(&bitmap, c) {
             return i;
@@ -323,11 +904,11 @@ pub fn strspn(s: &[u8], accept: &[u8]) -> usize {
 /// assert_eq!(strcspn(b"hello\0", b"xyz\0"), 5);
 /// ```
 pub fn strcspn(s: &[u8], reject: &[u8]) -> usize {
-    let s_len = strlen(s);
-    let reject_len = strlen(reject);
-    let s = &s[..s_len.min(s.len())];
-    let reject = &reject[..reject_len.min(reject.len())];
+    strcspn_bytes(&s[..strlen(s)], &reject[..strlen(reject)])
+}
 
+/// [`strcspn`] of two strings given without their terminators.
+pub(crate) fn strcspn_bytes(s: &[u8], reject: &[u8]) -> usize {
     if reject.is_empty() {
         return s.len();
     }
@@ -339,8 +920,17 @@ pub fn strcspn(s: &[u8], reject: &[u8]) -> usize {
     if reject.len() <= 4 {
         #[cfg(target_arch = "x86_64")]
         if s.len() >= 64 {
-            // SAFETY: AVX2 is baseline for this project.
-            return unsafe { find_first_any_small_set_avx2(s, reject) }.unwrap_or(s.len());
+            // SAFETY: The kernel only requires 1-4 set bytes; the slot holds
+            // a kernel supported by the running CPU.
+            return unsafe { (FIND_ANY_SMALL_SET.get())(s, reject) }.unwrap_or(s.len());
+        }
+        #[cfg(all(not(target_arch = "x86_64"), feature = "std-simd"))]
+        if s.len() >= 16 {
+            return crate::stdsimd::find_first_any_small_set(s, reject).unwrap_or(s.len());
+        }
+        #[cfg(all(not(target_arch = "x86_64"), not(feature = "std-simd")))]
+        if s.len() >= 16 {
+            return find_first_any_small_set_scalar_wide(s, reject).unwrap_or(s.len());
         }
 
         for (i, &c) in s.iter().enumerate() {
@@ -352,6 +942,15 @@ pub fn strcspn(s: &[u8], reject: &[u8]) -> usize {
     }
 
     let bitmap = build_byte_bitmap(reject);
+
+    #[cfg(target_arch = "x86_64")]
+    if s.len() >= 16
+        && let Some(set) = PackedSet::new(reject, &bitmap)
+    {
+        // SAFETY: The slot holds a kernel supported by the running CPU.
+        return unsafe { (SET_SCAN.get())(s, &set, false) };
+    }
+
     for (i, &c) in s.iter().enumerate() {
         if bitmap_contains(&bitmap, c) {
             return i;
@@ -373,11 +972,11 @@ pub fn strcspn(s: &[u8], reject: &[u8]) -> usize {
 /// assert_eq!(strpbrk(b"hello\0", b"xyz\0"), None);
 /// ```
 pub fn strpbrk(s: &[u8], accept: &[u8]) -> Option<usize> {
-    let s_len = strlen(s);
-    let accept_len = strlen(accept);
-    let s = &s[..s_len.min(s.len())];
-    let accept = &accept[..accept_len.min(accept.len())];
+    strpbrk_bytes(&s[..strlen(s)], &accept[..strlen(accept)])
+}
 
+/// [`strpbrk`] of two strings given without their terminators.
+pub(crate) fn strpbrk_bytes(s: &[u8], accept: &[u8]) -> Option<usize> {
     if accept.is_empty() {
         return None;
     }
@@ -389,8 +988,17 @@ pub fn strpbrk(s: &[u8], accept: &[u8]) -> Option<usize> {
     if accept.len() <= 4 {
         #[cfg(target_arch = "x86_64")]
         if s.len() >= 64 {
-            // SAFETY: AVX2 is baseline for this project.
-            return unsafe { find_first_any_small_set_avx2(s, accept) };
+            // SAFETY: The kernel only requires 1-4 set bytes; the slot holds
+            // a kernel supported by the running CPU.
+            return unsafe { (FIND_ANY_SMALL_SET.get())(s, accept) };
+        }
+        #[cfg(all(not(target_arch = "x86_64"), feature = "std-simd"))]
+        if s.len() >= 16 {
+            return crate::stdsimd::find_first_any_small_set(s, accept);
+        }
+        #[cfg(all(not(target_arch = "x86_64"), not(feature = "std-simd")))]
+        if s.len() >= 16 {
+            return find_first_any_small_set_scalar_wide(s, accept);
         }
 
         for (i, &c) in s.iter().enumerate() {
@@ -402,6 +1010,16 @@ pub fn strpbrk(s: &[u8], accept: &[u8]) -> Option<usize> {
     }
 
     let bitmap = build_byte_bitmap(accept);
+
+    #[cfg(target_arch = "x86_64")]
+    if s.len() >= 16
+        && let Some(set) = PackedSet::new(accept, &bitmap)
+    {
+        // SAFETY: The slot holds a kernel supported by the running CPU.
+        let pos = unsafe { (SET_SCAN.get())(s, &set, false) };
+        return (pos < s.len()).then_some(pos);
+    }
+
     for (i, &c) in s.iter().enumerate() {
         if bitmap_contains(&bitmap, c) {
             return Some(i);
@@ -429,6 +1047,8 @@ fn to_lower_ascii(c: u8) -> u8 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::vec;
+    use std::vec::Vec;
 
     #[test]
     fn test_strchr_and_strchrnul() {
@@ -460,4 +1080,159 @@ mod tests {
         assert_eq!(strpbrk(b"hello\0", b"xyz\0"), None);
         assert_eq!(strpbrk(b"hello\0", b"lo\0"), Some(2));
     }
+
+    #[test]
+    fn test_zero_byte_mask_is_exact() {
+        // 0x01 directly above a zero byte is where the borrow-based test misfires.
+        let word = u64::from_le_bytes([0x00, 0x01, 0x80, 0x00, 0xFF, 0x01, 0x7F, 0x00]);
+        assert_eq!(zero_byte_mask_u64(word), 0x8000_0000_8000_0080);
+        assert_eq!(zero_byte_mask_u64(u64::MAX), 0);
+        assert_eq!(zero_byte_mask_u64(0), SWAR_HIGHS);
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_small_set_kernels_every_isa_level() {
+        let set = b"-_.,";
+        let mut hay = [b'a'; 200];
+        for level in cpu::supported_levels() {
+            let span = select_span_small_set(level);
+            let find = select_find_any_small_set(level);
+            for set_len in 1..=4 {
+                let set = &set[..set_len];
+                for pos in [0usize, 15, 16, 31, 32, 63, 64, 100, 199] {
+                    hay.fill(b'a');
+                    hay[pos] = set[set_len - 1];
+                    assert_eq!(
+                        unsafe { find(&hay, set) },
+                        Some(pos),
+                        "{level:?} find {pos}"
+                    );
+
+                    hay.fill(set[0]);
+                    hay[pos] = b'a';
+                    assert_eq!(unsafe { span(&hay, set) }, pos, "{level:?} span {pos}");
+                }
+                hay.fill(b'a');
+                assert_eq!(unsafe { find(&hay, set) }, None, "{level:?} find miss");
+            }
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_packed_set_ranges() {
+        let word = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_";
+        let set = PackedSet::new(word, &build_byte_bitmap(word)).unwrap();
+        assert!(set.ranges);
+        assert_eq!(&set.bytes[..set.len as usize], b"09AZ__az");
+
+        // Runs touching both ends of the byte range and crossing a word boundary.
+        let mut edges: Vec<u8> = (0u8..=20).chain(60..=70).chain(250..=255).collect();
+        edges.extend(b"\x80\x82\x84\x86\x88");
+        let set = PackedSet::new(&edges, &build_byte_bitmap(&edges)).unwrap();
+        assert_eq!(
+            &set.bytes[..set.len as usize],
+            &[
+                0, 20, 60, 70, 0x80, 0x80, 0x82, 0x82, 0x84, 0x84, 0x86, 0x86, 0x88, 0x88, 250, 255
+            ]
+        );
+        assert_eq!(set.to_bitmap(), build_byte_bitmap(&edges));
+
+        edges.extend(b"\x8a");
+        assert!(PackedSet::new(&edges, &build_byte_bitmap(&edges)).is_none());
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_set_scan_every_isa_level() {
+        let sets: [&[u8]; 3] = [
+            b"0123456789",
+            b" \t\r\n,;:!?.()[]{}",
+            b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_",
+        ];
+        for level in cpu::supported_levels() {
+            let scan = select_set_scan(level);
+            for set_bytes in sets {
+                let bitmap = build_byte_bitmap(set_bytes);
+                let set = PackedSet::new(set_bytes, &bitmap).unwrap();
+                let inside = set_bytes[set_bytes.len() / 2];
+                for len in 0..80usize {
+                    for pos in [0, len / 2, len.saturating_sub(1), len] {
+                        let mut hay = vec![inside; len];
+                        if pos < len {
+                            hay[pos] = 0xFF;
+                        }
+                        assert_eq!(unsafe { scan(&hay, &set, true) }, pos, "{level:?} span");
+
+                        let mut hay = vec![0xFFu8; len];
+                        if pos < len {
+                            hay[pos] = inside;
+                        }
+                        assert_eq!(unsafe { scan(&hay, &set, false) }, pos, "{level:?} find");
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_strstr_every_isa_level() {
+        fn naive(h: &[u8], n: &[u8]) -> Option<usize> {
+            (0..=h.len() - n.len()).find(|&i| &h[i..i + n.len()] == n)
+        }
+
+        let needle_full = b"abcabcabdabcabcabdX-long-needle-tail";
+        for level in cpu::supported_levels() {
+            let find = select_strstr_scan(level);
+            for n_len in [2usize, 3, 8, 15, 16, 17, 33] {
+                let needle = &needle_full[..n_len];
+                for h_len in n_len..n_len + 70 {
+                    // Haystack of partial matches, with the needle planted at
+                    // several offsets (including straddling 16-byte windows).
+                    for pos in [
+                        None,
+                        Some(0),
+                        Some((h_len - n_len) / 2),
+                        Some(h_len - n_len),
+                    ] {
+                        let mut hay: Vec<u8> =
+                            needle_full.iter().cycle().take(h_len).copied().collect();
+                        for b in hay.iter_mut() {
+                            if *b == needle[n_len - 1] {
+                                *b = b'a';
+                            }
+                        }
+                        if let Some(pos) = pos {
+                            hay[pos..pos + n_len].copy_from_slice(needle);
+                        }
+                        assert_eq!(
+                            unsafe { find(&hay, needle) },
+                            naive(&hay, needle),
+                            "{level:?} n_len={n_len} h_len={h_len} pos={pos:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_large_sets_and_long_haystacks() {
+        let mut ident = vec![b'x'; 300];
+        ident[257] = b'-';
+        ident.push(0);
+        let word = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_\0";
+        assert_eq!(strspn(&ident, word), 257);
+        assert_eq!(strcspn(&ident, b"-+*/%=<>\0"), 257);
+        assert_eq!(strpbrk(&ident, b"-+*/%=<>\0"), Some(257));
+        assert_eq!(strpbrk(&ident, b"+*/%=<>\0"), None);
+
+        let mut hay = vec![b'a'; 4096];
+        hay.push(0);
+        assert_eq!(strstr(&hay, b"aab\0"), None);
+        hay[4000] = b'b';
+        assert_eq!(strstr(&hay, b"aab\0"), Some(3998));
+    }
 }
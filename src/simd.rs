@@ -20,6 +20,8 @@
 #![allow(unsafe_code)]
 
 use core::arch::x86_64::*;
+#[cfg(feature = "std")]
+use core::sync::atomic::{AtomicUsize, Ordering};
 
 // =============================================================================
 // DISPATCH THRESHOLDS
@@ -27,11 +29,67 @@ use core::arch::x86_64::*;
 
 /// Size threshold below which SSE2 is used (avoids AVX entry fee)
 /// SSE small path correctly handles 0-64 bytes, so threshold is 65
+///
+/// This is the default; `tiny_threshold` in `FASTSTRINGS_TUNABLES` overrides it.
 pub const TINY_THRESHOLD: usize = 65;
 
 /// Size threshold above which the aligned loop is used
+///
+/// This is the default; `large_threshold` in `FASTSTRINGS_TUNABLES` overrides it.
 pub const LARGE_THRESHOLD: usize = 256;
 
+// Cached `FASTSTRINGS_TUNABLES` overrides; zero means "not read yet".
+#[cfg(feature = "std")]
+static TINY: AtomicUsize = AtomicUsize::new(0);
+#[cfg(feature = "std")]
+static LARGE: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(feature = "std")]
+#[inline(always)]
+fn cached_cutoff(slot: &AtomicUsize, read: fn() -> usize) -> usize {
+    match slot.load(Ordering::Relaxed) {
+        0 => {
+            // Racing readers compute identical values; tunables are at least 1.
+            let value = read();
+            slot.store(value, Ordering::Relaxed);
+            value
+        }
+        value => value,
+    }
+}
+
+#[cfg(feature = "std")]
+#[inline(always)]
+fn tiny_threshold() -> usize {
+    cached_cutoff(&TINY, || {
+        crate::tunables::get()
+            .tiny_threshold
+            .unwrap_or(TINY_THRESHOLD)
+    })
+}
+
+#[cfg(not(feature = "std"))]
+#[inline(always)]
+fn tiny_threshold() -> usize {
+    TINY_THRESHOLD
+}
+
+#[cfg(feature = "std")]
+#[inline(always)]
+fn large_threshold() -> usize {
+    cached_cutoff(&LARGE, || {
+        crate::tunables::get()
+            .large_threshold
+            .unwrap_or(LARGE_THRESHOLD)
+    })
+}
+
+#[cfg(not(feature = "std"))]
+#[inline(always)]
+fn large_threshold() -> usize {
+    LARGE_THRESHOLD
+}
+
 // =============================================================================
 // UNIFIED DISPATCH: Entry point for all memcpy operations
 // =============================================================================
@@ -52,16 +110,15 @@ pub const LARGE_THRESHOLD: usize = 256;
 // #[target_feature(enable = "avx2")]
 // #[inline]
 pub unsafe fn memcpy_unified(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
-    if n < TINY_THRESHOLD {
-        // Tiny: SSE/scalar - avoid AVX entry fee entirely
-        memcpy_sse_small(dest, src, n)
-    } else if n < LARGE_THRESHOLD {
-        // Medium: Branchless AVX2 overlapping stores (no loops)
-        memcpy_avx2_medium(dest, src, n)
-    } else {
-        // Large: AVX2 with alignment prologue + main loop
-        memcpy_avx2_large(dest, src, n)
-    }
+    memcpy_tiered(
+        dest,
+        src,
+        n,
+        tiny_threshold(),
+        large_threshold(),
+        true,
+        false,
+    )
 }
 
 /// High-performance memcpy variant with refined AVX2 large path.
@@ -74,16 +131,15 @@ pub unsafe fn memcpy_unified(dest: *mut u8, src: *const u8, n: usize) -> *mut u8
 // #[target_feature(enable = "avx2")]
 // #[inline]
 pub unsafe fn memcpy_unified_refined(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
-    if n < TINY_THRESHOLD {
-        // Tiny: SSE/scalar - avoid AVX entry fee entirely
-        memcpy_sse_small(dest, src, n)
-    } else if n < LARGE_THRESHOLD {
-        // Medium: Branchless AVX2 overlapping stores (no loops)
-        memcpy_avx2_medium(dest, src, n)
-    } else {
-        // Large: AVX2 with alignment prologue + main loop
-        memcpy_avx2_large_refined(dest, src, n)
-    }
+    memcpy_tiered(
+        dest,
+        src,
+        n,
+        tiny_threshold(),
+        large_threshold(),
+        true,
+        true,
+    )
 }
 
 /// High-performance memcpy variant without medium-size AVX2 path.
@@ -96,13 +152,15 @@ pub unsafe fn memcpy_unified_refined(dest: *mut u8, src: *const u8, n: usize) ->
 #[target_feature(enable = "avx2")]
 // #[inline]
 pub unsafe fn memcpy_unified_no_medium(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
-    if n < TINY_THRESHOLD {
-        // Tiny: SSE/scalar - avoid AVX entry fee entirely
-        memcpy_sse_small(dest, src, n)
-    } else {
-        // Large: AVX2 with alignment prologue + main loop
-        memcpy_avx2_large(dest, src, n)
-    }
+    memcpy_tiered(
+        dest,
+        src,
+        n,
+        tiny_threshold(),
+        large_threshold(),
+        false,
+        false,
+    )
 }
 
 /// High-performance memcpy variant without medium-size AVX2 path and refined large path.
@@ -115,12 +173,53 @@ pub unsafe fn memcpy_unified_no_medium(dest: *mut u8, src: *const u8, n: usize)
 #[target_feature(enable = "avx2")]
 // #[inline]
 pub unsafe fn memcpy_unified_no_medium_refined(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
-    if n < TINY_THRESHOLD {
+    memcpy_tiered(
+        dest,
+        src,
+        n,
+        tiny_threshold(),
+        large_threshold(),
+        false,
+        true,
+    )
+}
+
+/// Copies `n` bytes with the kernel of the tier `tiny`/`large` put it in.
+///
+/// The cutoffs are tunable, so a tier can be handed sizes its kernel does not
+/// cover; those go to the neighbouring kernel that does. With the default
+/// cutoffs every size lands on its tier's own kernel.
+#[inline(always)]
+unsafe fn memcpy_tiered(
+    dest: *mut u8,
+    src: *const u8,
+    n: usize,
+    tiny: usize,
+    large: usize,
+    medium: bool,
+    refined: bool,
+) -> *mut u8 {
+    if n < tiny {
         // Tiny: SSE/scalar - avoid AVX entry fee entirely
-        memcpy_sse_small(dest, src, n)
-    } else {
+        if n <= 64 {
+            memcpy_sse_small(dest, src, n)
+        } else {
+            memcpy_sse_loop(dest, src, n)
+        }
+    } else if (medium && n < large) || n < 128 {
+        // Medium: Branchless AVX2 overlapping stores (no loops)
+        if n < 64 {
+            memcpy_sse_small(dest, src, n)
+        } else if n <= 256 {
+            memcpy_avx2_medium(dest, src, n)
+        } else {
+            memcpy_avx2_large(dest, src, n)
+        }
+    } else if refined {
         // Large: AVX2 with alignment prologue + main loop
         memcpy_avx2_large_refined(dest, src, n)
+    } else {
+        memcpy_avx2_large(dest, src, n)
     }
 }
 
@@ -141,7 +240,7 @@ pub unsafe fn memcpy_unified_no_medium_refined(dest: *mut u8, src: *const u8, n:
 /// - The memory regions must not overlap
 #[inline(always)]
 pub unsafe fn memcpy_sse_small(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
-    // n < 64 guaranteed by dispatch
+    debug_assert!(n <= 64);
 
     if n >= 32 {
         // 32-63 bytes: 4 × 16-byte loads/stores (overlapping)
@@ -212,6 +311,39 @@ pub unsafe fn memcpy_sse_small(dest: *mut u8, src: *const u8, n: usize) -> *mut
     dest
 }
 
+/// SSE2 memcpy for tiny-tier copies above 64 bytes, which only happen when
+/// `tiny_threshold` is raised.
+///
+/// # Safety
+/// - `dest` and `src` must be valid for reads/writes of `n` bytes
+/// - The memory regions must not overlap
+/// - n must be > 64
+#[inline]
+unsafe fn memcpy_sse_loop(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    debug_assert!(n > 64);
+    let mut offset = 0usize;
+
+    // SAFETY: Unaligned loads/stores are valid for any alignment; caller
+    // guarantees `src`/`dest` are valid for `n` bytes and non-overlapping.
+    unsafe {
+        while offset + 64 < n {
+            let v0 = _mm_loadu_si128(src.add(offset) as *const __m128i);
+            let v1 = _mm_loadu_si128(src.add(offset + 16) as *const __m128i);
+            let v2 = _mm_loadu_si128(src.add(offset + 32) as *const __m128i);
+            let v3 = _mm_loadu_si128(src.add(offset + 48) as *const __m128i);
+            _mm_storeu_si128(dest.add(offset) as *mut __m128i, v0);
+            _mm_storeu_si128(dest.add(offset + 16) as *mut __m128i, v1);
+            _mm_storeu_si128(dest.add(offset + 32) as *mut __m128i, v2);
+            _mm_storeu_si128(dest.add(offset + 48) as *mut __m128i, v3);
+            offset += 64;
+        }
+
+        // Overlapping tail: last 64 bytes
+        memcpy_sse_small(dest.add(n - 64), src.add(n - 64), 64);
+    }
+    dest
+}
+
 // =============================================================================
 // MEDIUM PATH: AVX2 Branchless Implementation (64-1023 bytes)
 // =============================================================================
@@ -230,6 +362,7 @@ pub unsafe fn memcpy_sse_small(dest: *mut u8, src: *const u8, n: usize) -> *mut
 #[target_feature(enable = "avx2")]
 #[inline]
 pub unsafe fn memcpy_avx2_medium(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    debug_assert!((64..=256).contains(&n));
     // 128-256 bytes: 8x 32-byte YMM loads/stores (128 front + 128 back)
     // All loads before stores maximizes memory-level parallelism
     if n >= 128 {
@@ -297,7 +430,7 @@ pub unsafe fn memcpy_avx2_large(dest: *mut u8, src: *const u8, n: usize) -> *mut
     let mut s = src;
     let mut remaining = n;
 
-    // n >= 256 guaranteed by dispatch (LARGE_THRESHOLD)
+    debug_assert!(n >= 128);
 
     // ALIGNMENT PROLOGUE: Copy first 32 bytes, then advance to aligned boundary
     // SAFETY: Unaligned load/store are valid for any alignment; caller guarantees
@@ -415,9 +548,11 @@ pub unsafe fn memcpy_avx2_loop_small(dest: *mut u8, src: *const u8, n: usize) ->
 /// - `dest` and `src` must be valid for reads/writes of `n` bytes
 /// - The memory regions must not overlap
 /// - AVX2 must be supported
+/// - n must be >= 64
 #[target_feature(enable = "avx2")]
 #[inline]
 pub unsafe fn memcpy_avx2_large_refined(dest: *mut u8, src: *const u8, n: usize) -> *mut u8 {
+    debug_assert!(n >= 64);
     // 1. Prologue: Align destination to 32-byte boundary
     // SAFETY: Unaligned load/store are valid for any alignment; caller guarantees
     // `src`/`dest` are valid for `n` bytes.
@@ -465,9 +600,12 @@ pub unsafe fn memcpy_avx2_large_refined(dest: *mut u8, src: *const u8, n: usize)
         remaining -= 256;
     }
 
-    // 3. Tail Dispatch: Use the Medium Path for the final 0-255 bytes
+    // 3. Tail Dispatch: Use the Medium Path for the final 0-255 bytes.
+    // The medium path needs at least 64 bytes, so short tails re-copy the
+    // last 64 (n >= 64 keeps that in bounds).
     if remaining > 0 {
-        memcpy_avx2_medium(dest.add(n - remaining), src.add(n - remaining), remaining);
+        let tail = remaining.max(64);
+        memcpy_avx2_medium(dest.add(n - tail), src.add(n - tail), tail);
     }
 
     dest
@@ -547,7 +685,8 @@ pub unsafe fn memcpy_avx2_unaligned_256_1024(dest: *mut u8, src: *const u8, n: u
 // TESTS
 // =============================================================================
 
-#[cfg(test)]
+// These call the AVX2 kernels directly, which portable mode never selects.
+#[cfg(all(test, not(miri)))]
 mod tests {
     extern crate std;
     use super::*;
@@ -555,6 +694,36 @@ mod tests {
     use std::vec;
     use std::vec::Vec;
 
+    /// Every size is copied correctly whatever the tunable cutoffs are
+    #[test]
+    fn test_tiers_accept_any_cutoffs() {
+        if !is_x86_feature_detected!("avx2") {
+            return;
+        }
+        let src: Vec<u8> = (0..1200).map(|i| (i * 7 + 3) as u8).collect();
+        for (tiny, large) in [(1, 1), (16, 100), (65, 257), (300, 200), (1100, 2000)] {
+            for medium in [false, true] {
+                for refined in [false, true] {
+                    for n in (0..=300).chain([511, 512, 1024, 1199]) {
+                        let mut dest = vec![0u8; n];
+                        unsafe {
+                            memcpy_tiered(
+                                dest.as_mut_ptr(),
+                                src.as_ptr(),
+                                n,
+                                tiny,
+                                large,
+                                medium,
+                                refined,
+                            );
+                        }
+                        assert_eq!(dest, src[..n], "n={n} tiny={tiny} large={large}");
+                    }
+                }
+            }
+        }
+    }
+
     /// Test memcpy_sse_small for all sizes from 0 to 64 bytes
     #[test]
     fn test_sse_small_0_to_64() {
@@ -823,4 +992,36 @@ mod tests {
             assert_eq!(dest[size / 2], 0xCC, "Middle byte wrong at size {}", size);
         }
     }
+
+    /// Test the refined large path, including tails shorter than 64 bytes
+    #[test]
+    fn test_unified_refined_short_tails() {
+        if !is_x86_feature_detected!("avx2") {
+            return;
+        }
+
+        let src: Vec<u8> = (0..2048 + 32).map(|i| (i * 7 + 3) as u8).collect();
+        for size in 256..1600 {
+            for offset in [0, 1, 17, 31] {
+                let mut dest = vec![0u8; size + 64];
+
+                unsafe {
+                    memcpy_unified_refined(dest.as_mut_ptr().add(offset), src.as_ptr(), size);
+                }
+
+                assert_eq!(
+                    dest[offset..offset + size],
+                    src[..size],
+                    "size {size} offset {offset}"
+                );
+                assert!(
+                    dest[..offset]
+                        .iter()
+                        .chain(&dest[offset + size..])
+                        .all(|&b| b == 0),
+                    "Wrote outside the buffer at size {size} offset {offset}"
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,253 @@
+//! Search and compare kernels built on `core::simd` portable vectors.
+//!
+//! Enabled by the nightly-only `std-simd` feature. On x86_64 the hand-written
+//! kernels stay in charge and these are exported for benchmarking against
+//! them; on every other target they replace the word-at-a-time fallbacks in
+//! `memchr`, `memcmp`, `strlen` and the 1-4 byte set spans of `strspn`,
+//! `strcspn` and `strpbrk`.
+//!
+//! Each kernel walks the input in [`LANES`]-byte vectors and finishes the
+//! remainder one byte at a time, so no load ever leaves the input.
+#![allow(unsafe_code)]
+
+use core::simd::prelude::*;
+
+/// Bytes examined per vector.
+pub const LANES: usize = 32;
+
+type Bytes = Simd<u8, LANES>;
+
+/// Loads `LANES` bytes from `ptr`.
+///
+/// # Safety
+///
+/// `ptr` must be valid for reads of `LANES` bytes.
+#[inline(always)]
+unsafe fn load(ptr: *const u8) -> Bytes {
+    // SAFETY: Caller guarantees `LANES` readable bytes; the read tolerates
+    // any alignment.
+    unsafe { core::ptr::read_unaligned(ptr as *const Bytes) }
+}
+
+/// Returns the index of the first `needle` in the `n` bytes at `s`.
+///
+/// # Safety
+///
+/// - `s` must be valid for reads of `n` bytes.
+pub unsafe fn memchr(s: *const u8, n: usize, needle: u8) -> Option<usize> {
+    let splat = Bytes::splat(needle);
+    let mut i = 0usize;
+
+    // SAFETY: Caller guarantees `s` is readable for `n` bytes; every vector
+    // load stays below `n`.
+    unsafe {
+        while i + 2 * LANES <= n {
+            let eq0 = load(s.add(i)).simd_eq(splat);
+            let eq1 = load(s.add(i + LANES)).simd_eq(splat);
+            if (eq0 | eq1).any() {
+                let mask = eq0.to_bitmask() | (eq1.to_bitmask() << LANES);
+                return Some(i + mask.trailing_zeros() as usize);
+            }
+            i += 2 * LANES;
+        }
+
+        if i + LANES <= n {
+            let mask = load(s.add(i)).simd_eq(splat).to_bitmask();
+            if mask != 0 {
+                return Some(i + mask.trailing_zeros() as usize);
+            }
+            i += LANES;
+        }
+
+        while i < n {
+            if *s.add(i) == needle {
+                return Some(i);
+            }
+            i += 1;
+        }
+    }
+
+    None
+}
+
+/// Returns the index of the first NUL in the `len` bytes at `ptr`, or `len`.
+///
+/// # Safety
+///
+/// - `ptr` must be valid for reads of `len` bytes.
+pub unsafe fn strlen(ptr: *const u8, len: usize) -> usize {
+    // SAFETY: Forwarded caller contract.
+    unsafe { memchr(ptr, len, 0) }.unwrap_or(len)
+}
+
+/// Compares `n` bytes like C `memcmp`, returning the difference of the first
+/// unequal pair.
+///
+/// # Safety
+///
+/// - `s1` and `s2` must be valid for reads of `n` bytes.
+pub unsafe fn memcmp(s1: *const u8, s2: *const u8, n: usize) -> i32 {
+    let mut i = 0usize;
+
+    // SAFETY: Caller guarantees both inputs are readable for `n` bytes;
+    // every vector load stays below `n`.
+    unsafe {
+        while i + LANES <= n {
+            let ne = load(s1.add(i)).simd_ne(load(s2.add(i)));
+            if ne.any() {
+                let idx = i + ne.to_bitmask().trailing_zeros() as usize;
+                return (*s1.add(idx) as i32) - (*s2.add(idx) as i32);
+            }
+            i += LANES;
+        }
+
+        while i < n {
+            let (a, b) = (*s1.add(i), *s2.add(i));
+            if a != b {
+                return (a as i32) - (b as i32);
+            }
+            i += 1;
+        }
+    }
+
+    0
+}
+
+/// Lanes of `chunk` equal to any of the first `set_len` splats.
+#[inline(always)]
+fn small_set_eq(chunk: Bytes, splat: &[Bytes; 4], set_len: usize) -> Mask<i8, LANES> {
+    let mut eq = chunk.simd_eq(splat[0]);
+    for &s in &splat[1..set_len] {
+        eq |= chunk.simd_eq(s);
+    }
+    eq
+}
+
+#[inline(always)]
+fn small_set_splat(set: &[u8]) -> [Bytes; 4] {
+    let mut out = [Bytes::splat(set[0]); 4];
+    for (slot, &byte) in out.iter_mut().zip(set) {
+        *slot = Bytes::splat(byte);
+    }
+    out
+}
+
+/// Length of the prefix of `s` made only of bytes in `accept`.
+///
+/// # Panics
+///
+/// Panics unless `accept` holds 1-4 bytes.
+pub fn strspn_small_set(s: &[u8], accept: &[u8]) -> usize {
+    assert!((1..=4).contains(&accept.len()), "set must hold 1-4 bytes");
+    let splat = small_set_splat(accept);
+    let mut chunks = s.chunks_exact(LANES);
+    let mut i = 0usize;
+
+    for chunk in &mut chunks {
+        let miss = !small_set_eq(Bytes::from_slice(chunk), &splat, accept.len());
+        if miss.any() {
+            return i + miss.to_bitmask().trailing_zeros() as usize;
+        }
+        i += LANES;
+    }
+
+    i + chunks
+        .remainder()
+        .iter()
+        .position(|c| !accept.contains(c))
+        .unwrap_or(chunks.remainder().len())
+}
+
+/// Index of the first byte of `s` that is in `set`.
+///
+/// # Panics
+///
+/// Panics unless `set` holds 1-4 bytes.
+pub fn find_first_any_small_set(s: &[u8], set: &[u8]) -> Option<usize> {
+    assert!((1..=4).contains(&set.len()), "set must hold 1-4 bytes");
+    let splat = small_set_splat(set);
+    let mut chunks = s.chunks_exact(LANES);
+    let mut i = 0usize;
+
+    for chunk in &mut chunks {
+        let hit = small_set_eq(Bytes::from_slice(chunk), &splat, set.len());
+        if hit.any() {
+            return Some(i + hit.to_bitmask().trailing_zeros() as usize);
+        }
+        i += LANES;
+    }
+
+    chunks
+        .remainder()
+        .iter()
+        .position(|c| set.contains(c))
+        .map(|p| i + p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seeded_buf() -> [u8; 600] {
+        let mut buf = [0u8; 600];
+        for (i, b) in buf.iter_mut().enumerate() {
+            *b = (i % 251) as u8 | 1;
+        }
+        buf
+    }
+
+    #[test]
+    fn test_memchr_and_strlen_every_position() {
+        let base = seeded_buf();
+        for n in 0..200 {
+            assert_eq!(unsafe { memchr(base.as_ptr(), n, 0) }, None, "n={n}");
+            assert_eq!(unsafe { strlen(base.as_ptr(), n) }, n, "n={n}");
+            for pos in 0..n {
+                let mut buf = base;
+                buf[pos] = 0;
+                assert_eq!(unsafe { memchr(buf.as_ptr(), n, 0) }, Some(pos));
+                assert_eq!(unsafe { strlen(buf.as_ptr(), n) }, pos);
+            }
+        }
+    }
+
+    #[test]
+    fn test_memcmp_every_position() {
+        let a = seeded_buf();
+        for n in 0..200 {
+            assert_eq!(unsafe { memcmp(a.as_ptr(), a.as_ptr(), n) }, 0);
+            for pos in 0..n {
+                let mut b = a;
+                b[pos] = b[pos].wrapping_add(3);
+                let got = unsafe { memcmp(a.as_ptr(), b.as_ptr(), n) };
+                assert_eq!(got, a[pos] as i32 - b[pos] as i32, "n={n} pos={pos}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_small_set_spans() {
+        let set = b"-_.";
+        let mut s = [b'-'; 300];
+        for (i, b) in s.iter_mut().enumerate() {
+            *b = set[i % 3];
+        }
+        for len in [0usize, 1, 31, 32, 33, 64, 65, 299] {
+            for set_len in 1..=3 {
+                let set = &set[..set_len];
+                let naive = s[..len].iter().position(|c| !set.contains(c));
+                assert_eq!(strspn_small_set(&s[..len], set), naive.unwrap_or(len));
+            }
+            let mut t = s;
+            if len > 0 {
+                t[len - 1] = b'x';
+            }
+            assert_eq!(strspn_small_set(&t[..len], b"-_."), len.saturating_sub(1));
+            assert_eq!(
+                find_first_any_small_set(&t[..len], b"xyz"),
+                len.checked_sub(1)
+            );
+            assert_eq!(find_first_any_small_set(&s[..len], b"xyz"), None);
+        }
+    }
+}
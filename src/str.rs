@@ -3,10 +3,23 @@
 //! Safe Rust implementations of C string functions. These operate on byte slices
 //! and treat 0 (null byte) as the string terminator.
 
+#[cfg(target_arch = "x86_64")]
+use crate::cpu::{self, IsaLevel};
+#[cfg(target_arch = "x86_64")]
+use crate::dispatch::FnSlot;
+use crate::memchr::memchr_dispatched;
 #[cfg(target_arch = "x86_64")]
 use core::arch::x86_64::*;
-use crate::memchr::optimized_memchr_unified;
+use core::mem::MaybeUninit;
+
+#[cfg(target_arch = "x86_64")]
+type LenScanFn = unsafe fn(*const u8, usize) -> usize;
+
+/// Bounded NUL scan kernel, chosen on first use.
+#[cfg(target_arch = "x86_64")]
+static STRLEN_SCAN: FnSlot<LenScanFn> = FnSlot::new(resolve_strlen_scan as *mut ());
 
+#[cfg(any(target_arch = "x86_64", not(feature = "std-simd")))]
 #[inline(always)]
 fn has_zero_byte(word: usize) -> bool {
     let ones = usize::MAX / 0xFF;
@@ -14,6 +27,7 @@ fn has_zero_byte(word: usize) -> bool {
     ((word.wrapping_sub(ones)) & !word & highs) != 0
 }
 
+#[cfg(any(target_arch = "x86_64", not(feature = "std-simd")))]
 #[inline(always)]
 unsafe fn strlen_scan_scalar(mut ptr: *const u8, mut len: usize) -> usize {
     let mut scanned = 0usize;
@@ -55,6 +69,76 @@ unsafe fn strlen_scan_scalar(mut ptr: *const u8, mut len: usize) -> usize {
     scanned + len
 }
 
+#[cfg(target_arch = "x86_64")]
+fn select_strlen_scan(level: IsaLevel) -> LenScanFn {
+    match level {
+        IsaLevel::Avx512 => strlen_scan_avx512,
+        IsaLevel::Avx2 => strlen_scan_avx2,
+        IsaLevel::Sse42 | IsaLevel::Sse2 => strlen_scan_sse2,
+        IsaLevel::Scalar => strlen_scan_scalar,
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn resolve_strlen_scan(ptr: *const u8, len: usize) -> usize {
+    let f = select_strlen_scan(cpu::isa_level());
+    STRLEN_SCAN.set(f);
+    // SAFETY: Forwarded caller contract.
+    unsafe { f(ptr, len) }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn strlen_scan_sse2(ptr: *const u8, len: usize) -> usize {
+    // SAFETY: Caller guarantees `ptr` is readable for `len` bytes; every
+    // unaligned 16-byte load stays below `len`.
+    unsafe {
+        let zero = _mm_setzero_si128();
+        let mut i = 0usize;
+
+        while i + 16 <= len {
+            let e = _mm_cmpeq_epi8(_mm_loadu_si128(ptr.add(i) as *const __m128i), zero);
+            let m = _mm_movemask_epi8(e);
+            if m != 0 {
+                return i + (m as u32).trailing_zeros() as usize;
+            }
+            i += 16;
+        }
+
+        i + strlen_scan_scalar(ptr.add(i), len - i)
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512bw,avx512vl")]
+unsafe fn strlen_scan_avx512(ptr: *const u8, len: usize) -> usize {
+    let mut i = 0usize;
+
+    // SAFETY: Caller guarantees `ptr` is readable for `len` bytes. Full loads
+    // stay below `len`; the tail uses a fault-suppressing masked load.
+    unsafe {
+        while i + 64 <= len {
+            let v = _mm512_loadu_si512(ptr.add(i) as *const __m512i);
+            let m = _mm512_testn_epi8_mask(v, v);
+            if m != 0 {
+                return i + m.trailing_zeros() as usize;
+            }
+            i += 64;
+        }
+
+        if i < len {
+            let k = u64::MAX >> (64 - (len - i));
+            let v = _mm512_maskz_loadu_epi8(k, ptr.add(i) as *const i8);
+            // Masked-off lanes load as zero, so restrict the test to `k`.
+            let m = _mm512_mask_testn_epi8_mask(k, v, v);
+            if m != 0 {
+                return i + m.trailing_zeros() as usize;
+            }
+        }
+    }
+
+    len
+}
+
 #[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
 unsafe fn strlen_scan_avx2(ptr: *const u8, len: usize) -> usize {
@@ -128,10 +212,16 @@ unsafe fn strlen_scan(ptr: *const u8, len: usize) -> usize {
 
     #[cfg(target_arch = "x86_64")]
     {
-        return 1 + strlen_scan_avx2(ptr.add(1), len - 1);
+        1 + (STRLEN_SCAN.get())(ptr.add(1), len - 1)
+    }
+
+    #[cfg(all(not(target_arch = "x86_64"), feature = "std-simd"))]
+    {
+        // SAFETY: Forwarded caller contract.
+        1 + unsafe { crate::stdsimd::strlen(ptr.add(1), len - 1) }
     }
 
-    #[cfg(not(target_arch = "x86_64"))]
+    #[cfg(all(not(target_arch = "x86_64"), not(feature = "std-simd")))]
     {
         1 + strlen_scan_scalar(ptr.add(1), len - 1)
     }
@@ -150,11 +240,16 @@ unsafe fn strlen_scan(ptr: *const u8, len: usize) -> usize {
 /// assert_eq!(strlen(b"hello"), 5); // no null terminator
 /// ```
 pub fn strlen(s: &[u8]) -> usize {
-    if s.len() < 32 {
-        return unsafe { strlen_scan(s.as_ptr(), s.len()) };
-    }
+    let len = if s.len() < 32 {
+        unsafe { strlen_scan(s.as_ptr(), s.len()) }
+    } else {
+        unsafe { memchr_dispatched(s.as_ptr(), s.len(), 0).unwrap_or(s.len()) }
+    };
+
+    #[cfg(feature = "instrument")]
+    crate::instrument::record(crate::instrument::Routine::Strlen, len, s.as_ptr() as usize);
 
-    unsafe { optimized_memchr_unified(s.as_ptr(), s.len(), 0).unwrap_or(s.len()) }
+    len
 }
 
 /// Calculate bounded length of a null-terminated string
@@ -169,11 +264,20 @@ pub fn strlen(s: &[u8]) -> usize {
 /// ```
 pub fn strnlen(s: &[u8], maxlen: usize) -> usize {
     let limit = s.len().min(maxlen);
-    if limit < 32 {
-        return unsafe { strlen_scan(s.as_ptr(), limit) };
-    }
+    let len = if limit < 32 {
+        unsafe { strlen_scan(s.as_ptr(), limit) }
+    } else {
+        unsafe { memchr_dispatched(s.as_ptr(), limit, 0).unwrap_or(limit) }
+    };
+
+    #[cfg(feature = "instrument")]
+    crate::instrument::record(
+        crate::instrument::Routine::Strnlen,
+        len,
+        s.as_ptr() as usize,
+    );
 
-    unsafe { optimized_memchr_unified(s.as_ptr(), limit, 0).unwrap_or(limit) }
+    len
 }
 
 /// Version-aware string comparison (musl-compatible).
@@ -259,14 +363,20 @@ pub fn strverscmp(s1: &[u8], s2: &[u8]) -> i32 {
 /// ```
 pub fn strcpy(dest: &mut [u8], src: &[u8]) -> usize {
     let src_len = strlen(src);
-    let copy_len = (src_len + 1).min(dest.len()).min(src.len());
+    strcpy_bytes(dest, &src[..src_len], src_len < src.len())
+}
+
+/// [`strcpy`] of the bytes `src`, followed by a terminator if `terminated`.
+pub(crate) fn strcpy_bytes(dest: &mut [u8], src: &[u8], terminated: bool) -> usize {
+    let src_len = src.len();
+    let copy_len = (src_len + usize::from(terminated)).min(dest.len());
 
     // Copy the string content
     let content_len = copy_len.min(src_len);
     dest[..content_len].copy_from_slice(&src[..content_len]);
 
     // Add null terminator if there's room
-    if copy_len > src_len && copy_len <= dest.len() {
+    if copy_len > src_len {
         dest[src_len] = 0;
         return src_len + 1;
     }
@@ -274,6 +384,42 @@ pub fn strcpy(dest: &mut [u8], src: &[u8]) -> usize {
     content_len
 }
 
+/// Copy a null-terminated string into an uninitialized destination
+///
+/// Like [`strcpy`], but `dest` need not be initialized. Returns the bytes
+/// written, including the null if there was room for it.
+///
+/// # Examples
+/// ```
+/// use core::mem::MaybeUninit;
+/// use faststrings::str::strcpy_uninit;
+/// let mut dest = [MaybeUninit::<u8>::uninit(); 10];
+/// assert_eq!(strcpy_uninit(&mut dest, b"hello\0world"), b"hello\0");
+/// ```
+pub fn strcpy_uninit<'a>(dest: &'a mut [MaybeUninit<u8>], src: &[u8]) -> &'a mut [u8] {
+    let src_len = strlen(src);
+    // `src[src_len]` is the null, when there is one.
+    let n = (src_len + usize::from(src_len < src.len())).min(dest.len());
+    crate::mem::memcpy_uninit(dest, &src[..n])
+}
+
+/// Copy a string with length limit into an uninitialized destination
+///
+/// Like [`strncpy`], but `dest` need not be initialized. Returns the first
+/// `min(dest.len(), n)` bytes of `dest`, all of them written.
+pub fn strncpy_uninit<'a>(dest: &'a mut [MaybeUninit<u8>], src: &[u8], n: usize) -> &'a mut [u8] {
+    let limit = dest.len().min(n);
+    let copy_len = strnlen(src, limit);
+    let d = dest.as_mut_ptr() as *mut u8;
+    // SAFETY: `copy_len <= limit <= dest.len()`; the copy and the padding
+    // initialize all `limit` bytes the returned slice covers.
+    unsafe {
+        crate::memcpy::optimized_memcpy_unified(d, src.as_ptr(), copy_len);
+        crate::memset::optimized_memset_unified(d.add(copy_len), 0, limit - copy_len);
+        core::slice::from_raw_parts_mut(d, limit)
+    }
+}
+
 /// Copy a string with length limit
 ///
 /// Copies at most `n` bytes from `src` to `dest`. If `src` is shorter than `n`,
@@ -335,15 +481,20 @@ pub fn stpcpy(dest: &mut [u8], src: &[u8]) -> usize {
 /// assert_eq!(&dest[..12], b"hello world\0");
 /// ```
 pub fn strcat(dest: &mut [u8], src: &[u8]) -> usize {
+    strcat_bytes(dest, &src[..strlen(src)])
+}
+
+/// [`strcat`] of the bytes `src`, which hold no terminator.
+pub(crate) fn strcat_bytes(dest: &mut [u8], src: &[u8]) -> usize {
     let dest_len = strlen(dest);
-    let src_len = strlen(src);
+    let src_len = src.len();
 
     if dest_len >= dest.len() {
         return dest_len;
     }
 
     let remaining = dest.len() - dest_len;
-    let copy_len = src_len.min(remaining - 1).min(src.len());
+    let copy_len = src_len.min(remaining - 1);
 
     dest[dest_len..dest_len + copy_len].copy_from_slice(&src[..copy_len]);
 
@@ -395,8 +546,13 @@ pub fn strncat(dest: &mut [u8], src: &[u8], n: usize) -> usize {
 /// assert!(strcmp(b"abd\0", b"abc\0") > 0);
 /// ```
 pub fn strcmp(s1: &[u8], s2: &[u8]) -> i32 {
-    let len1 = strlen(s1);
-    let len2 = strlen(s2);
+    strcmp_bytes(&s1[..strlen(s1)], &s2[..strlen(s2)])
+}
+
+/// [`strcmp`] of two strings given without their terminators.
+pub(crate) fn strcmp_bytes(s1: &[u8], s2: &[u8]) -> i32 {
+    let len1 = s1.len();
+    let len2 = s2.len();
     let min_len = len1.min(len2);
 
     for i in 0..min_len {
@@ -417,12 +573,17 @@ pub fn strcmp(s1: &[u8], s2: &[u8]) -> i32 {
 ///
 /// Like strcmp, but compares at most `n` bytes.
 pub fn strncmp(s1: &[u8], s2: &[u8], n: usize) -> i32 {
+    strncmp_bytes(&s1[..strnlen(s1, n)], &s2[..strnlen(s2, n)], n)
+}
+
+/// [`strncmp`] of two strings given without their terminators.
+pub(crate) fn strncmp_bytes(s1: &[u8], s2: &[u8], n: usize) -> i32 {
     if n == 0 {
         return 0;
     }
 
-    let len1 = strnlen(s1, n);
-    let len2 = strnlen(s2, n);
+    let len1 = s1.len().min(n);
+    let len2 = s2.len().min(n);
     let min_len = len1.min(len2);
 
     for i in 0..min_len {
@@ -460,8 +621,13 @@ pub fn strcoll(s1: &[u8], s2: &[u8]) -> i32 {
 /// assert!(strcasecmp(b"ABC\0", b"abd\0") < 0);
 /// ```
 pub fn strcasecmp(s1: &[u8], s2: &[u8]) -> i32 {
-    let len1 = strlen(s1);
-    let len2 = strlen(s2);
+    strcasecmp_bytes(&s1[..strlen(s1)], &s2[..strlen(s2)])
+}
+
+/// [`strcasecmp`] of two strings given without their terminators.
+pub(crate) fn strcasecmp_bytes(s1: &[u8], s2: &[u8]) -> i32 {
+    let len1 = s1.len();
+    let len2 = s2.len();
     let min_len = len1.min(len2);
 
     for i in 0..min_len {
@@ -482,12 +648,17 @@ pub fn strcasecmp(s1: &[u8], s2: &[u8]) -> i32 {
 
 /// Compare strings ignoring case with length limit
 pub fn strncasecmp(s1: &[u8], s2: &[u8], n: usize) -> i32 {
+    strncasecmp_bytes(&s1[..strnlen(s1, n)], &s2[..strnlen(s2, n)], n)
+}
+
+/// [`strncasecmp`] of two strings given without their terminators.
+pub(crate) fn strncasecmp_bytes(s1: &[u8], s2: &[u8], n: usize) -> i32 {
     if n == 0 {
         return 0;
     }
 
-    let len1 = strnlen(s1, n);
-    let len2 = strnlen(s2, n);
+    let len1 = s1.len().min(n);
+    let len2 = s2.len().min(n);
     let min_len = len1.min(len2);
 
     for i in 0..min_len {
@@ -524,13 +695,18 @@ pub fn strncasecmp(s1: &[u8], s2: &[u8], n: usize) -> i32 {
 /// assert_eq!(&dest, b"hell\0"); // truncated + null
 /// ```
 pub fn strlcpy(dest: &mut [u8], src: &[u8]) -> usize {
-    let src_len = strlen(src);
+    strlcpy_bytes(dest, &src[..strlen(src)])
+}
+
+/// [`strlcpy`] of the bytes `src`, which hold no terminator.
+pub(crate) fn strlcpy_bytes(dest: &mut [u8], src: &[u8]) -> usize {
+    let src_len = src.len();
 
     if dest.is_empty() {
         return src_len;
     }
 
-    let copy_len = src_len.min(dest.len() - 1).min(src.len());
+    let copy_len = src_len.min(dest.len() - 1);
     dest[..copy_len].copy_from_slice(&src[..copy_len]);
     dest[copy_len] = 0;
 
@@ -542,16 +718,21 @@ pub fn strlcpy(dest: &mut [u8], src: &[u8]) -> usize {
 /// Appends `src` to `dest`, ensuring null-termination and not exceeding `size`.
 /// Returns the total length that would have been created without truncation.
 pub fn strlcat(dest: &mut [u8], src: &[u8]) -> usize {
+    strlcat_bytes(dest, &src[..strlen(src)])
+}
+
+/// [`strlcat`] of the bytes `src`, which hold no terminator.
+pub(crate) fn strlcat_bytes(dest: &mut [u8], src: &[u8]) -> usize {
     let size = dest.len();
     let dest_len = strnlen(dest, size);
-    let src_len = strlen(src);
+    let src_len = src.len();
 
     if dest_len >= size {
         return size + src_len;
     }
 
     let remaining = size - dest_len;
-    let copy_len = src_len.min(remaining - 1).min(src.len());
+    let copy_len = src_len.min(remaining - 1);
 
     dest[dest_len..dest_len + copy_len].copy_from_slice(&src[..copy_len]);
     dest[dest_len + copy_len] = 0;
@@ -568,6 +749,54 @@ fn to_lower_ascii(c: u8) -> u8 {
 mod tests {
     use super::{strlen, strnlen, strverscmp};
 
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_strlen_scan_every_isa_level() {
+        use super::select_strlen_scan;
+        use crate::cpu;
+
+        let buf = [b'x'; 300];
+        for level in cpu::supported_levels() {
+            let scan = select_strlen_scan(level);
+            for len in 0..200usize {
+                for nul in [0, len / 2, len.saturating_sub(1)] {
+                    let mut local = buf;
+                    if nul < len {
+                        local[nul] = 0;
+                    }
+                    let expect = if nul < len { nul } else { len };
+                    assert_eq!(
+                        unsafe { scan(local.as_ptr(), len) },
+                        expect,
+                        "{level:?} len={len} nul={nul}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_strlen_scan_every_isa_level_stops_at_len() {
+        use super::select_strlen_scan;
+        use crate::cpu;
+
+        // Zero bytes right after the scanned range must not be reported.
+        let mut buf = [0u8; 300];
+        for level in cpu::supported_levels() {
+            let scan = select_strlen_scan(level);
+            for len in 0..200usize {
+                buf.fill(0);
+                buf[..len].fill(b'x');
+                assert_eq!(
+                    unsafe { scan(buf.as_ptr(), len) },
+                    len,
+                    "{level:?} len={len}"
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_strlen_and_strnlen_edges() {
         assert_eq!(strlen(b"\0"), 0);
@@ -604,6 +833,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_strcpy_uninit_matches_strcpy() {
+        use super::{strcpy, strcpy_uninit, strncpy, strncpy_uninit};
+        use core::mem::MaybeUninit;
+
+        for src in [&b"\0"[..], b"abc\0", b"abc", b"abc\0def", &[b'x'; 100]] {
+            for cap in [0usize, 1, 3, 4, 5, 100] {
+                let mut init = [0xAAu8; 100];
+                let n = strcpy(&mut init[..cap], src);
+                let mut dest = [MaybeUninit::<u8>::uninit(); 100];
+                assert_eq!(strcpy_uninit(&mut dest[..cap], src), &init[..n]);
+
+                for limit in [0usize, 2, 4, 80] {
+                    let mut init = [0xAAu8; 100];
+                    let n = strncpy(&mut init[..cap], src, limit);
+                    let got = strncpy_uninit(&mut dest[..cap], src, limit);
+                    assert_eq!(got, &init[..n]);
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_strverscmp_numeric_ordering() {
         assert!(strverscmp(b"a1\0", b"a2\0") < 0);
@@ -1,21 +1,134 @@
 //! `strdup` implementation.
+//!
+//! Every duplicating routine (`strdup`, `strndup`, `wcsdup`) comes in four
+//! forms: the plain one returns a global-allocator [`Vec`] and aborts on
+//! allocation failure, `try_` returns the error instead, and `_in` and
+//! `try_..._in` allocate from any [`allocator_api2`] [`Allocator`], such as an
+//! [`Arena`](crate::arena::Arena).
+#![allow(unsafe_code)]
 
+use alloc::collections::TryReserveError;
+use alloc::vec::Vec;
+use allocator_api2::alloc::Allocator;
+use allocator_api2::collections::TryReserveError as TryReserveErrorIn;
+use allocator_api2::vec::Vec as VecIn;
+
+use crate::memcpy::optimized_memcpy_unified;
 use crate::str::strlen;
 
 /// Duplicate a C-style string into an owned buffer.
 ///
 /// The returned vector is always nul-terminated.
 pub fn strdup(src: &[u8]) -> Vec<u8> {
-    let src_len = strlen(src).min(src.len());
-    let mut out = Vec::with_capacity(src_len + 1);
-    out.extend_from_slice(&src[..src_len]);
-    out.push(0);
+    dup(&src[..strlen(src).min(src.len())], 0)
+}
+
+/// Like [`strdup`], but returns an error instead of aborting when the
+/// allocation fails.
+pub fn try_strdup(src: &[u8]) -> Result<Vec<u8>, TryReserveError> {
+    try_dup(&src[..strlen(src).min(src.len())], 0)
+}
+
+/// Like [`strdup`], allocating from `alloc`.
+pub fn strdup_in<A: Allocator>(src: &[u8], alloc: A) -> VecIn<u8, A> {
+    dup_in(&src[..strlen(src).min(src.len())], 0, alloc)
+}
+
+/// Like [`strdup_in`], but returns an error instead of aborting when the
+/// allocation fails.
+pub fn try_strdup_in<A: Allocator>(
+    src: &[u8],
+    alloc: A,
+) -> Result<VecIn<u8, A>, TryReserveErrorIn> {
+    try_dup_in(&src[..strlen(src).min(src.len())], 0, alloc)
+}
+
+/// Copies `s` and a terminating `nul` into an exactly sized vector.
+pub(crate) fn dup<T: Copy>(s: &[T], nul: T) -> Vec<T> {
+    let mut out = Vec::with_capacity(s.len() + 1);
+    // SAFETY: `out` has room for `s` and the terminator.
+    unsafe {
+        write_terminated(out.as_mut_ptr(), s, nul);
+        out.set_len(s.len() + 1);
+    }
     out
 }
 
+/// Fallible [`dup`].
+pub(crate) fn try_dup<T: Copy>(s: &[T], nul: T) -> Result<Vec<T>, TryReserveError> {
+    let mut out = Vec::new();
+    out.try_reserve_exact(s.len() + 1)?;
+    // SAFETY: `out` has room for `s` and the terminator.
+    unsafe {
+        write_terminated(out.as_mut_ptr(), s, nul);
+        out.set_len(s.len() + 1);
+    }
+    Ok(out)
+}
+
+/// Copies `s` and a terminating `nul` into an exactly sized vector from
+/// `alloc`, aborting if the allocation fails.
+pub(crate) fn dup_in<T: Copy, A: Allocator>(s: &[T], nul: T, alloc: A) -> VecIn<T, A> {
+    let mut out = VecIn::with_capacity_in(s.len() + 1, alloc);
+    // SAFETY: `out` has room for `s` and the terminator.
+    unsafe {
+        write_terminated(out.as_mut_ptr(), s, nul);
+        out.set_len(s.len() + 1);
+    }
+    out
+}
+
+/// Fallible [`dup_in`].
+pub(crate) fn try_dup_in<T: Copy, A: Allocator>(
+    s: &[T],
+    nul: T,
+    alloc: A,
+) -> Result<VecIn<T, A>, TryReserveErrorIn> {
+    let mut out = VecIn::new_in(alloc);
+    out.try_reserve_exact(s.len() + 1)?;
+    // SAFETY: `out` has room for `s` and the terminator.
+    unsafe {
+        write_terminated(out.as_mut_ptr(), s, nul);
+        out.set_len(s.len() + 1);
+    }
+    Ok(out)
+}
+
+/// Copies `s` to `dst` and stores `nul` after it.
+///
+/// # Safety
+///
+/// `dst` must be valid for writes of `s.len() + 1` elements and must not
+/// overlap `s`.
+#[inline(always)]
+pub(crate) unsafe fn write_terminated<T: Copy>(dst: *mut T, s: &[T], nul: T) {
+    // SAFETY: Caller guarantees `dst` is writable and disjoint from `s`; the
+    // byte kernel needs no alignment.
+    unsafe {
+        optimized_memcpy_unified(dst as *mut u8, s.as_ptr() as *const u8, size_of_val(s));
+        dst.add(s.len()).write(nul);
+    }
+}
+
 #[cfg(test)]
-mod tests {
-    use super::strdup;
+pub(crate) mod tests {
+    use super::*;
+    use allocator_api2::alloc::{AllocError, Global};
+    use core::alloc::Layout;
+    use core::ptr::NonNull;
+
+    /// An allocator whose every allocation fails.
+    pub(crate) struct Exhausted;
+
+    unsafe impl Allocator for Exhausted {
+        fn allocate(&self, _: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            Err(AllocError)
+        }
+
+        unsafe fn deallocate(&self, _: NonNull<u8>, _: Layout) {
+            unreachable!("nothing was allocated")
+        }
+    }
 
     #[test]
     fn test_strdup_basic() {
@@ -26,4 +139,28 @@ mod tests {
     fn test_strdup_adds_terminator_when_missing() {
         assert_eq!(strdup(b"abc"), b"abc\0");
     }
+
+    #[test]
+    fn test_strdup_variants_agree() {
+        for src in [
+            &b""[..],
+            b"\0",
+            b"abc",
+            b"abc\0def",
+            b"0123456789abcdef0123456789abcdef",
+        ] {
+            let expected = strdup(src);
+            assert_eq!(try_strdup(src).unwrap(), expected);
+            assert_eq!(strdup_in(src, Global).as_slice(), expected);
+            let out = try_strdup_in(src, Global).unwrap();
+            assert_eq!(out.as_slice(), expected);
+            assert_eq!(out.capacity(), out.len());
+        }
+    }
+
+    #[test]
+    fn test_try_strdup_in_reports_allocation_failure() {
+        assert!(try_strdup_in(b"abc\0", Exhausted).is_err());
+        assert!(try_strdup_in(b"", Exhausted).is_err());
+    }
 }
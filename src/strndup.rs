@@ -1,21 +1,52 @@
 //! `strndup` implementation.
 
+use alloc::collections::TryReserveError;
+use alloc::vec::Vec;
+use allocator_api2::alloc::Allocator;
+use allocator_api2::collections::TryReserveError as TryReserveErrorIn;
+use allocator_api2::vec::Vec as VecIn;
+
 use crate::str::strnlen;
+use crate::strdup::{dup, dup_in, try_dup, try_dup_in};
 
 /// Duplicate at most `n` bytes of a C-style string into an owned buffer.
 ///
 /// The returned vector is always nul-terminated.
 pub fn strndup(src: &[u8], n: usize) -> Vec<u8> {
-    let copy_len = strnlen(src, n).min(src.len()).min(n);
-    let mut out = Vec::with_capacity(copy_len + 1);
-    out.extend_from_slice(&src[..copy_len]);
-    out.push(0);
-    out
+    dup(prefix(src, n), 0)
+}
+
+/// Like [`strndup`], but returns an error instead of aborting when the
+/// allocation fails.
+pub fn try_strndup(src: &[u8], n: usize) -> Result<Vec<u8>, TryReserveError> {
+    try_dup(prefix(src, n), 0)
+}
+
+/// Like [`strndup`], allocating from `alloc`.
+pub fn strndup_in<A: Allocator>(src: &[u8], n: usize, alloc: A) -> VecIn<u8, A> {
+    dup_in(prefix(src, n), 0, alloc)
+}
+
+/// Like [`strndup_in`], but returns an error instead of aborting when the
+/// allocation fails.
+pub fn try_strndup_in<A: Allocator>(
+    src: &[u8],
+    n: usize,
+    alloc: A,
+) -> Result<VecIn<u8, A>, TryReserveErrorIn> {
+    try_dup_in(prefix(src, n), 0, alloc)
+}
+
+/// The bytes `strndup` copies: up to the first NUL, at most `n`.
+pub(crate) fn prefix(src: &[u8], n: usize) -> &[u8] {
+    &src[..strnlen(src, n).min(src.len()).min(n)]
 }
 
 #[cfg(test)]
 mod tests {
-    use super::strndup;
+    use super::*;
+    use crate::strdup::tests::Exhausted;
+    use allocator_api2::alloc::Global;
 
     #[test]
     fn test_strndup_stops_at_nul() {
@@ -26,4 +57,20 @@ mod tests {
     fn test_strndup_respects_limit() {
         assert_eq!(strndup(b"abcdef\0", 3), b"abc\0");
     }
+
+    #[test]
+    fn test_strndup_variants_agree() {
+        for (src, n) in [
+            (&b"abcdef\0"[..], 3),
+            (b"ab\0cd", 5),
+            (b"abc", 10),
+            (b"abc", 0),
+        ] {
+            let expected = strndup(src, n);
+            assert_eq!(try_strndup(src, n).unwrap(), expected);
+            assert_eq!(strndup_in(src, n, Global).as_slice(), expected);
+            assert_eq!(try_strndup_in(src, n, Global).unwrap().as_slice(), expected);
+            assert!(try_strndup_in(src, n, Exhausted).is_err());
+        }
+    }
 }
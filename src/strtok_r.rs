@@ -7,9 +7,16 @@ use crate::str::strlen;
 /// `saveptr` stores scan position between calls.
 /// Initialize `*saveptr = 0` before the first call.
 pub fn strtok_r<'a>(s: &'a [u8], delim: &[u8], saveptr: &mut usize) -> Option<&'a [u8]> {
-    let len = strlen(s).min(s.len());
-    let delim_len = strlen(delim).min(delim.len());
-    let delim = &delim[..delim_len];
+    strtok_r_bytes(&s[..strlen(s)], &delim[..strlen(delim)], saveptr)
+}
+
+/// [`strtok_r`] of two strings given without their terminators.
+pub(crate) fn strtok_r_bytes<'a>(
+    s: &'a [u8],
+    delim: &[u8],
+    saveptr: &mut usize,
+) -> Option<&'a [u8]> {
+    let len = s.len();
 
     let mut pos = (*saveptr).min(len + 1);
     if pos > len {
@@ -0,0 +1,478 @@
+//! Constant-time comparisons (`timingsafe_bcmp`, `timingsafe_memcmp`).
+//!
+//! [`optimized_memcmp_unified`](crate::memcmp::optimized_memcmp_unified)
+//! stops at the first difference, so its running time tells an attacker how
+//! long a prefix of a guessed MAC or token was right. The kernels here read
+//! every byte and contain no branch that depends on the data: differences
+//! are folded into an accumulator with SIMD XORs and compares, and the
+//! position of the first one is chosen with masks instead of jumps.
+//! Their time depends only on `n`.
+//!
+//! Both follow OpenBSD: `timingsafe_bcmp` returns 0 when the buffers are
+//! equal and 1 otherwise, `timingsafe_memcmp` returns -1, 0 or 1 in the
+//! order `memcmp` would.
+#![allow(unsafe_code)]
+
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+#[cfg(target_arch = "x86_64")]
+use crate::cpu::{self, IsaLevel};
+#[cfg(target_arch = "x86_64")]
+use crate::dispatch::FnSlot;
+
+#[cfg(target_arch = "x86_64")]
+type CmpFn = unsafe fn(*const u8, *const u8, usize) -> i32;
+
+/// Kernel used by [`optimized_timingsafe_bcmp`], chosen on first use.
+#[cfg(target_arch = "x86_64")]
+static TIMINGSAFE_BCMP: FnSlot<CmpFn> = FnSlot::new(resolve_timingsafe_bcmp as *mut ());
+
+/// Kernel used by [`optimized_timingsafe_memcmp`], chosen on first use.
+#[cfg(target_arch = "x86_64")]
+static TIMINGSAFE_MEMCMP: FnSlot<CmpFn> = FnSlot::new(resolve_timingsafe_memcmp as *mut ());
+
+/// Compares `n` bytes in time independent of their contents.
+///
+/// Returns 0 if they are equal and 1 otherwise.
+///
+/// # Safety
+///
+/// - `s1` and `s2` must be valid for reads of `n` bytes.
+#[inline]
+pub unsafe fn optimized_timingsafe_bcmp(s1: *const u8, s2: *const u8, n: usize) -> i32 {
+    #[cfg(target_arch = "x86_64")]
+    {
+        // SAFETY: Forwarded caller contract.
+        unsafe { (TIMINGSAFE_BCMP.get())(s1, s2, n) }
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        // SAFETY: Forwarded caller contract.
+        unsafe { timingsafe_bcmp_scalar(s1, s2, n) }
+    }
+}
+
+/// Orders `n` bytes like `memcmp`, in time independent of their contents.
+///
+/// Returns -1, 0 or 1 as the first differing byte of `s1`, compared as
+/// `unsigned char`, is below or above the one in `s2`, or 0 if all are
+/// equal.
+///
+/// # Safety
+///
+/// - `s1` and `s2` must be valid for reads of `n` bytes.
+#[inline]
+pub unsafe fn optimized_timingsafe_memcmp(s1: *const u8, s2: *const u8, n: usize) -> i32 {
+    #[cfg(target_arch = "x86_64")]
+    {
+        // SAFETY: Forwarded caller contract.
+        unsafe { (TIMINGSAFE_MEMCMP.get())(s1, s2, n) }
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        // SAFETY: Forwarded caller contract.
+        unsafe { timingsafe_memcmp_scalar(s1, s2, n) }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn select_timingsafe_bcmp(level: IsaLevel) -> CmpFn {
+    match level {
+        // A 32-byte XOR per cycle already outruns the loads; AVX-512 hosts
+        // share the AVX2 kernel.
+        IsaLevel::Avx512 | IsaLevel::Avx2 => timingsafe_bcmp_avx2,
+        IsaLevel::Sse42 | IsaLevel::Sse2 => timingsafe_bcmp_sse2,
+        IsaLevel::Scalar => timingsafe_bcmp_scalar,
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn select_timingsafe_memcmp(level: IsaLevel) -> CmpFn {
+    match level {
+        IsaLevel::Avx512 | IsaLevel::Avx2 => timingsafe_memcmp_avx2,
+        IsaLevel::Sse42 | IsaLevel::Sse2 => timingsafe_memcmp_sse2,
+        IsaLevel::Scalar => timingsafe_memcmp_scalar,
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn resolve_timingsafe_bcmp(s1: *const u8, s2: *const u8, n: usize) -> i32 {
+    let f = select_timingsafe_bcmp(cpu::isa_level());
+    TIMINGSAFE_BCMP.set(f);
+    // SAFETY: Forwarded caller contract.
+    unsafe { f(s1, s2, n) }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn resolve_timingsafe_memcmp(s1: *const u8, s2: *const u8, n: usize) -> i32 {
+    let f = select_timingsafe_memcmp(cpu::isa_level());
+    TIMINGSAFE_MEMCMP.set(f);
+    // SAFETY: Forwarded caller contract.
+    unsafe { f(s1, s2, n) }
+}
+
+/// 1 if `x` is nonzero, else 0, from the borrow of `0 - x` rather than a
+/// comparison the compiler could turn into a branch.
+#[inline(always)]
+fn ct_nonzero(x: u64) -> i32 {
+    ((x as u128).wrapping_neg() >> 127) as i32
+}
+
+/// 1 if `a < b`, else 0, from the borrow of `a - b`.
+#[inline(always)]
+fn ct_less(a: u64, b: u64) -> i32 {
+    ((a as u128).wrapping_sub(b as u128) >> 127) as i32
+}
+
+/// Running state of a constant-time three-way compare.
+#[derive(Clone, Copy)]
+struct Order {
+    /// The answer so far: -1, 0 or 1.
+    res: i32,
+    /// All ones once a difference has been seen, so later ones are ignored.
+    done: i32,
+}
+
+impl Order {
+    const EQUAL: Self = Self { res: 0, done: 0 };
+
+    /// Folds in the next block, whose own order is `cmp`; `differs` is 1
+    /// if the block has any difference.
+    #[inline(always)]
+    fn update(&mut self, cmp: i32, differs: i32) {
+        self.res |= cmp & !self.done;
+        self.done |= differs.wrapping_neg();
+    }
+
+    /// Folds in one byte pair, as OpenBSD's byte loop does.
+    #[inline(always)]
+    fn update_byte(&mut self, a: u8, b: u8) {
+        // Both are -1 or 0: the high bits of a negative difference.
+        let lt = (a as i32 - b as i32) >> 8;
+        let gt = (b as i32 - a as i32) >> 8;
+        self.res |= (lt - gt) & !self.done;
+        self.done |= lt | gt;
+    }
+}
+
+// =============================================================================
+// PORTABLE PATH: word-at-a-time
+// =============================================================================
+
+/// XOR of the byte pairs in `[i, n)`, folded into one word.
+#[inline(always)]
+unsafe fn xor_fold_bytes(s1: *const u8, s2: *const u8, mut i: usize, n: usize) -> u64 {
+    let mut acc = 0u64;
+    while i < n {
+        // SAFETY: Caller guarantees both are readable for `n` bytes.
+        acc |= unsafe { (*s1.add(i) ^ *s2.add(i)) as u64 };
+        i += 1;
+    }
+    acc
+}
+
+unsafe fn timingsafe_bcmp_scalar(s1: *const u8, s2: *const u8, n: usize) -> i32 {
+    let mut acc = 0u64;
+    let mut i = 0usize;
+    // SAFETY: Caller guarantees both are readable for `n` bytes; unaligned
+    // word loads accept any alignment.
+    unsafe {
+        while i + 8 <= n {
+            let a = core::ptr::read_unaligned(s1.add(i) as *const u64);
+            let b = core::ptr::read_unaligned(s2.add(i) as *const u64);
+            acc |= a ^ b;
+            i += 8;
+        }
+        acc |= xor_fold_bytes(s1, s2, i, n);
+    }
+    ct_nonzero(acc)
+}
+
+/// Byte loop over `[i, n)`, continuing `order`.
+#[inline(always)]
+unsafe fn timingsafe_memcmp_bytes(
+    s1: *const u8,
+    s2: *const u8,
+    mut i: usize,
+    n: usize,
+    mut order: Order,
+) -> i32 {
+    while i < n {
+        // SAFETY: Caller guarantees both are readable for `n` bytes.
+        let (a, b) = unsafe { (*s1.add(i), *s2.add(i)) };
+        order.update_byte(a, b);
+        i += 1;
+    }
+    order.res
+}
+
+unsafe fn timingsafe_memcmp_scalar(s1: *const u8, s2: *const u8, n: usize) -> i32 {
+    let mut order = Order::EQUAL;
+    let mut i = 0usize;
+    // SAFETY: Caller guarantees both are readable for `n` bytes; unaligned
+    // word loads accept any alignment.
+    unsafe {
+        while i + 8 <= n {
+            // Big-endian words order like the bytes they hold.
+            let a = u64::from_be(core::ptr::read_unaligned(s1.add(i) as *const u64));
+            let b = u64::from_be(core::ptr::read_unaligned(s2.add(i) as *const u64));
+            let lt = ct_less(a, b);
+            let gt = ct_less(b, a);
+            order.update(gt - lt, lt | gt);
+            i += 8;
+        }
+        timingsafe_memcmp_bytes(s1, s2, i, n, order)
+    }
+}
+
+// =============================================================================
+// SIMD PATHS: SSE2 (16 bytes) and AVX2 (32 bytes) per step
+// =============================================================================
+
+/// Order of one block from its lane masks: `neq` has a bit per differing
+/// byte and `gt` per byte where `s1` is above `s2`. The lowest set bit of
+/// `neq` is the first difference; it decides the sign.
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+fn block_order(neq: u64, gt: u64, order: &mut Order) {
+    let first = neq & neq.wrapping_neg();
+    let differs = ct_nonzero(first);
+    let above = ct_nonzero(first & gt);
+    order.update(2 * above - differs, differs);
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn timingsafe_bcmp_sse2(s1: *const u8, s2: *const u8, n: usize) -> i32 {
+    // SAFETY: Unaligned SSE2 loads are valid for any alignment; caller
+    // guarantees both are readable for `n` bytes.
+    unsafe {
+        let mut acc = _mm_setzero_si128();
+        let mut i = 0usize;
+        while i + 16 <= n {
+            let a = _mm_loadu_si128(s1.add(i) as *const __m128i);
+            let b = _mm_loadu_si128(s2.add(i) as *const __m128i);
+            acc = _mm_or_si128(acc, _mm_xor_si128(a, b));
+            i += 16;
+        }
+        let zero = _mm_movemask_epi8(_mm_cmpeq_epi8(acc, _mm_setzero_si128())) as u64;
+        ct_nonzero((zero ^ 0xFFFF) | xor_fold_bytes(s1, s2, i, n))
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn timingsafe_bcmp_avx2(s1: *const u8, s2: *const u8, n: usize) -> i32 {
+    // SAFETY: Unaligned AVX2 loads are valid for any alignment; caller
+    // guarantees both are readable for `n` bytes.
+    unsafe {
+        let mut acc = _mm256_setzero_si256();
+        let mut i = 0usize;
+        while i + 32 <= n {
+            let a = _mm256_loadu_si256(s1.add(i) as *const __m256i);
+            let b = _mm256_loadu_si256(s2.add(i) as *const __m256i);
+            acc = _mm256_or_si256(acc, _mm256_xor_si256(a, b));
+            i += 32;
+        }
+        let zero = _mm256_cmpeq_epi8(acc, _mm256_setzero_si256());
+        let zero = _mm256_movemask_epi8(zero) as u32 as u64;
+        ct_nonzero((zero ^ 0xFFFF_FFFF) | xor_fold_bytes(s1, s2, i, n))
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn timingsafe_memcmp_sse2(s1: *const u8, s2: *const u8, n: usize) -> i32 {
+    // SAFETY: Unaligned SSE2 loads are valid for any alignment; caller
+    // guarantees both are readable for `n` bytes.
+    unsafe {
+        let mut order = Order::EQUAL;
+        let mut i = 0usize;
+        while i + 16 <= n {
+            let a = _mm_loadu_si128(s1.add(i) as *const __m128i);
+            let b = _mm_loadu_si128(s2.add(i) as *const __m128i);
+            let eq = _mm_movemask_epi8(_mm_cmpeq_epi8(a, b)) as u64;
+            // Unsigned `a >= b` per byte: `max(a, b) == a`.
+            let ge = _mm_movemask_epi8(_mm_cmpeq_epi8(_mm_max_epu8(a, b), a)) as u64;
+            let neq = eq ^ 0xFFFF;
+            block_order(neq, ge & neq, &mut order);
+            i += 16;
+        }
+        timingsafe_memcmp_bytes(s1, s2, i, n, order)
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn timingsafe_memcmp_avx2(s1: *const u8, s2: *const u8, n: usize) -> i32 {
+    // SAFETY: Unaligned AVX2 loads are valid for any alignment; caller
+    // guarantees both are readable for `n` bytes.
+    unsafe {
+        let mut order = Order::EQUAL;
+        let mut i = 0usize;
+        while i + 32 <= n {
+            let a = _mm256_loadu_si256(s1.add(i) as *const __m256i);
+            let b = _mm256_loadu_si256(s2.add(i) as *const __m256i);
+            let eq = _mm256_movemask_epi8(_mm256_cmpeq_epi8(a, b)) as u32 as u64;
+            let max = _mm256_max_epu8(a, b);
+            let ge = _mm256_movemask_epi8(_mm256_cmpeq_epi8(max, a)) as u32 as u64;
+            let neq = eq ^ 0xFFFF_FFFF;
+            block_order(neq, ge & neq, &mut order);
+            i += 32;
+        }
+        timingsafe_memcmp_bytes(s1, s2, i, n, order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use std::vec::Vec;
+
+    type Kernel = unsafe fn(*const u8, *const u8, usize) -> i32;
+
+    fn ref_memcmp(a: &[u8], b: &[u8]) -> i32 {
+        match a.iter().zip(b).find(|(x, y)| x != y) {
+            Some((x, y)) if x < y => -1,
+            Some(_) => 1,
+            None => 0,
+        }
+    }
+
+    /// Every kernel for every level the host supports, as (bcmp, memcmp).
+    fn kernels() -> Vec<(Kernel, Kernel)> {
+        #[cfg(target_arch = "x86_64")]
+        {
+            cpu::supported_levels()
+                .map(|l| (select_timingsafe_bcmp(l), select_timingsafe_memcmp(l)))
+                .collect()
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        {
+            std::vec![(
+                timingsafe_bcmp_scalar as Kernel,
+                timingsafe_memcmp_scalar as Kernel
+            )]
+        }
+    }
+
+    #[test]
+    fn test_timingsafe_matches_reference() {
+        let a: Vec<u8> = (0..200u32).map(|i| (i * 73 + 11) as u8).collect();
+        for (bcmp, memcmp) in kernels() {
+            for n in [0usize, 1, 7, 8, 9, 15, 16, 17, 31, 32, 33, 64, 100, 200] {
+                for pos in [0usize, 1, 7, 8, 15, 16, 31, 32, 63, 99, 199] {
+                    for delta in [1u8, 0x7f, 0x80, 0xff] {
+                        let mut b = a.clone();
+                        b[pos] = b[pos].wrapping_add(delta);
+                        // A second, later difference must not change the answer.
+                        if pos + 3 < b.len() {
+                            b[pos + 3] = b[pos + 3].wrapping_sub(delta);
+                        }
+                        let (a, b) = (&a[..n], &b[..n]);
+                        unsafe {
+                            assert_eq!(bcmp(a.as_ptr(), b.as_ptr(), n), (a != b) as i32);
+                            assert_eq!(
+                                memcmp(a.as_ptr(), b.as_ptr(), n),
+                                ref_memcmp(a, b),
+                                "n={n} pos={pos} delta={delta}"
+                            );
+                            assert_eq!(memcmp(b.as_ptr(), a.as_ptr(), n), -ref_memcmp(a, b));
+                        }
+                    }
+                }
+                unsafe {
+                    assert_eq!(bcmp(a.as_ptr(), a.as_ptr(), n), 0);
+                    assert_eq!(memcmp(a.as_ptr(), a.as_ptr(), n), 0);
+                }
+            }
+        }
+    }
+
+    /// A compare that returns at the first difference, to show the leakage
+    /// test can see one.
+    unsafe fn early_exit(s1: *const u8, s2: *const u8, n: usize) -> i32 {
+        for i in 0..n {
+            let (a, b) = unsafe { (*s1.add(i), *s2.add(i)) };
+            if a != b {
+                return a as i32 - b as i32;
+            }
+        }
+        0
+    }
+
+    /// dudect-style leakage check (Reparaz, Balasch and Verbauwhede,
+    /// "Dude, is my code constant time?"): times `f` on two classes of
+    /// input, a copy of the reference buffer and one that differs in its
+    /// first byte, picked in random order so drift hits both alike. Drops
+    /// the slowest tenth of all samples as interrupts and other noise and
+    /// returns the square of Welch's t statistic between the classes.
+    fn leakage_t2(f: Kernel) -> f64 {
+        const N: usize = 256;
+        const SAMPLES: usize = 4000;
+        const CALLS: usize = 4;
+
+        let reference: Vec<u8> = (0..N).map(|i| (i * 29 + 3) as u8).collect();
+        // One buffer for both classes, so they differ only in contents and
+        // not in address or alignment.
+        let mut other = reference.clone();
+
+        let mut rng = 0x9e37_79b9_7f4a_7c15u64;
+        let mut times = [Vec::with_capacity(SAMPLES), Vec::with_capacity(SAMPLES)];
+        for _ in 0..SAMPLES {
+            rng ^= rng << 13;
+            rng ^= rng >> 7;
+            rng ^= rng << 17;
+            let class = (rng & 1) as usize;
+            other[0] = reference[0] ^ (class as u8 * 0xff);
+            let start = Instant::now();
+            for _ in 0..CALLS {
+                let (a, b) = core::hint::black_box((reference.as_ptr(), other.as_ptr()));
+                core::hint::black_box(unsafe { f(a, b, N) });
+            }
+            times[class].push(start.elapsed().as_nanos() as f64);
+        }
+
+        let mut all: Vec<f64> = times.iter().flatten().copied().collect();
+        all.sort_by(f64::total_cmp);
+        let cutoff = all[all.len() * 9 / 10];
+
+        let stats = |t: &Vec<f64>| {
+            let kept: Vec<f64> = t.iter().copied().filter(|&x| x <= cutoff).collect();
+            let n = kept.len() as f64;
+            let mean = kept.iter().sum::<f64>() / n;
+            let var = kept.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1.0);
+            (mean, var, n)
+        };
+        let (m0, v0, n0) = stats(&times[0]);
+        let (m1, v1, n1) = stats(&times[1]);
+        (m0 - m1) * (m0 - m1) / (v0 / n0 + v1 / n1)
+    }
+
+    /// dudect flags |t| above 10 as a definite leak.
+    const LEAK_T2: f64 = 10.0 * 10.0;
+
+    // Miri's timings say nothing about the hardware.
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn test_timingsafe_no_timing_leak() {
+        assert!(
+            leakage_t2(early_exit) > LEAK_T2,
+            "the leakage test missed an early-exit compare"
+        );
+        for (bcmp, memcmp) in kernels() {
+            let t2 = leakage_t2(bcmp);
+            assert!(
+                t2 < LEAK_T2,
+                "timingsafe_bcmp timing depends on data, t^2={t2}"
+            );
+            let t2 = leakage_t2(memcmp);
+            assert!(
+                t2 < LEAK_T2,
+                "timingsafe_memcmp timing depends on data, t^2={t2}"
+            );
+        }
+    }
+}
@@ -0,0 +1,341 @@
+//! Runtime overrides for kernel selection and size cutoffs.
+//!
+//! Like glibc's `GLIBC_TUNABLES`, the `FASTSTRINGS_TUNABLES` environment
+//! variable is read once, the first time any tunable is consulted, and holds a
+//! colon-separated list of `name=value` entries:
+//!
+//! ```text
+//! FASTSTRINGS_TUNABLES=isa=avx2:non_temporal=off:rep_movsb_threshold=2M
+//! ```
+//!
+//! | Name                  | Value                                          |
+//! |-----------------------|------------------------------------------------|
+//! | `isa`                 | `scalar`, `sse2`, `sse4.2`, `avx2` or `avx512` |
+//! | `non_temporal`        | `on`/`off` (also `1`/`0`, `true`/`false`)      |
+//! | `rep_movsb_threshold` | size, at least 1                               |
+//! | `memcpy_nt_threshold` | size, at least 4K                              |
+//! | `memset_nt_threshold` | size, at least 4K                              |
+//! | `tiny_threshold`      | `simd::TINY_THRESHOLD` override, at least 1    |
+//! | `large_threshold`     | `simd::LARGE_THRESHOLD` override, at least 1   |
+//!
+//! Sizes are decimal byte counts with an optional binary `K`, `M` or `G`
+//! suffix. `isa` caps the level used for kernel selection; it never enables
+//! instructions the CPU lacks. Malformed or out-of-range entries are reported
+//! on stderr and ignored; the remaining entries still apply.
+
+use core::fmt;
+use std::eprintln;
+use std::string::{String, ToString};
+use std::sync::OnceLock;
+use std::vec::Vec;
+
+use crate::cpu::{IsaLevel, Thresholds};
+
+/// Name of the environment variable read by [`get`].
+pub const ENV_VAR: &str = "FASTSTRINGS_TUNABLES";
+
+// The NT kernels assume a copy well past their 256-byte unrolled tails.
+const MIN_NT_THRESHOLD: usize = 4096;
+
+/// Overrides parsed from [`ENV_VAR`]. `None` keeps the built-in choice.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tunables {
+    /// Highest ISA level to select kernels from.
+    pub isa: Option<IsaLevel>,
+    /// `Some(false)` disables non-temporal stores in memcpy and memset.
+    pub non_temporal: Option<bool>,
+    /// Overrides [`Thresholds::rep_movsb`].
+    pub rep_movsb_threshold: Option<usize>,
+    /// Overrides [`Thresholds::memcpy_nt`].
+    pub memcpy_nt_threshold: Option<usize>,
+    /// Overrides [`Thresholds::memset_nt`].
+    pub memset_nt_threshold: Option<usize>,
+    /// Overrides `simd::TINY_THRESHOLD`.
+    pub tiny_threshold: Option<usize>,
+    /// Overrides `simd::LARGE_THRESHOLD`.
+    pub large_threshold: Option<usize>,
+}
+
+/// A rejected tunables entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TunableError {
+    /// The entry as written.
+    pub entry: String,
+    /// Why it was ignored.
+    pub reason: &'static str,
+}
+
+impl fmt::Display for TunableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ignoring `{}`: {}", self.entry, self.reason)
+    }
+}
+
+impl Tunables {
+    /// Parses a tunables string, returning the accepted overrides and one
+    /// error per rejected entry.
+    pub fn parse(spec: &str) -> (Tunables, Vec<TunableError>) {
+        let mut errors = Vec::new();
+        let tunables = Tunables::parse_with(spec, |entry, reason| {
+            errors.push(TunableError {
+                entry: entry.to_string(),
+                reason,
+            })
+        });
+        (tunables, errors)
+    }
+
+    /// Like [`Tunables::parse`], but hands each rejected entry to `on_error`
+    /// instead of collecting it, so nothing is allocated.
+    fn parse_with(spec: &str, mut on_error: impl FnMut(&str, &'static str)) -> Tunables {
+        let mut tunables = Tunables::default();
+        for entry in spec.split(':').map(str::trim).filter(|e| !e.is_empty()) {
+            if let Err(reason) = tunables.set(entry) {
+                on_error(entry, reason);
+            }
+        }
+        tunables
+    }
+
+    fn set(&mut self, entry: &str) -> Result<(), &'static str> {
+        let (name, value) = entry.split_once('=').ok_or("expected `name=value`")?;
+        let value = value.trim();
+        match name.trim() {
+            "isa" => self.isa = Some(parse_isa(value)?),
+            "non_temporal" => self.non_temporal = Some(parse_bool(value)?),
+            "rep_movsb_threshold" => {
+                self.rep_movsb_threshold = Some(parse_size(value, 1..=usize::MAX)?)
+            }
+            "memcpy_nt_threshold" => {
+                self.memcpy_nt_threshold = Some(parse_size(value, MIN_NT_THRESHOLD..=usize::MAX)?)
+            }
+            "memset_nt_threshold" => {
+                self.memset_nt_threshold = Some(parse_size(value, MIN_NT_THRESHOLD..=usize::MAX)?)
+            }
+            // `simd` hands sizes a tier's kernel cannot take to the next one,
+            // so the tiers accept any cutoffs.
+            "tiny_threshold" => self.tiny_threshold = Some(parse_size(value, 1..=usize::MAX)?),
+            "large_threshold" => self.large_threshold = Some(parse_size(value, 1..=usize::MAX)?),
+            _ => return Err("unknown tunable"),
+        }
+        Ok(())
+    }
+
+    /// Applies the threshold overrides to `t`.
+    pub fn apply(&self, t: &mut Thresholds) {
+        if let Some(n) = self.rep_movsb_threshold {
+            t.rep_movsb = n;
+        }
+        if let Some(n) = self.memcpy_nt_threshold {
+            t.memcpy_nt = n;
+        }
+        if let Some(n) = self.memset_nt_threshold {
+            t.memset_nt = n;
+        }
+        if self.non_temporal == Some(false) {
+            t.memcpy_nt = usize::MAX;
+            t.memset_nt = usize::MAX;
+        }
+    }
+}
+
+fn parse_isa(value: &str) -> Result<IsaLevel, &'static str> {
+    if value.eq_ignore_ascii_case("sse42") {
+        return Ok(IsaLevel::Sse42);
+    }
+    IsaLevel::ALL
+        .into_iter()
+        .find(|level| level.name().eq_ignore_ascii_case(value))
+        .ok_or("expected scalar, sse2, sse4.2, avx2 or avx512")
+}
+
+fn parse_bool(value: &str) -> Result<bool, &'static str> {
+    let is = |names: [&str; 4]| names.iter().any(|n| n.eq_ignore_ascii_case(value));
+    if is(["1", "on", "true", "yes"]) {
+        Ok(true)
+    } else if is(["0", "off", "false", "no"]) {
+        Ok(false)
+    } else {
+        Err("expected on or off")
+    }
+}
+
+fn parse_size(value: &str, range: core::ops::RangeInclusive<usize>) -> Result<usize, &'static str> {
+    let (digits, shift) = match value.as_bytes().last() {
+        Some(b'k' | b'K') => (&value[..value.len() - 1], 10),
+        Some(b'm' | b'M') => (&value[..value.len() - 1], 20),
+        Some(b'g' | b'G') => (&value[..value.len() - 1], 30),
+        _ => (value, 0),
+    };
+    let n: usize = digits.parse().map_err(|_| "expected a byte count")?;
+    let n = n.checked_mul(1 << shift).ok_or("size overflows usize")?;
+    if range.contains(&n) {
+        Ok(n)
+    } else {
+        Err("value out of range")
+    }
+}
+
+/// Returns the overrides from [`ENV_VAR`], parsing it on first use.
+///
+/// Rejected entries are reported on stderr once, when the variable is read.
+pub fn get() -> &'static Tunables {
+    static TUNABLES: OnceLock<Tunables> = OnceLock::new();
+    if let Some(tunables) = TUNABLES.get() {
+        return tunables;
+    }
+
+    // With the `capi` feature, std's own copies and formatting land in this
+    // crate's `memcpy`, whose kernel selection reads the tunables again. So
+    // `load` neither allocates nor prints, and problems are reported only
+    // once the value is in place.
+    let mut loaded = false;
+    let tunables = TUNABLES.get_or_init(|| {
+        loaded = true;
+        load()
+    });
+    if loaded {
+        report(tunables);
+    }
+    tunables
+}
+
+/// Calls `f` with the raw value of [`ENV_VAR`], if set.
+#[cfg(not(feature = "capi"))]
+fn with_spec<R>(f: impl FnOnce(Option<&[u8]>) -> R) -> R {
+    let spec = std::env::var_os(ENV_VAR);
+    f(spec.as_deref().map(std::ffi::OsStr::as_encoded_bytes))
+}
+
+/// Calls `f` with the raw value of [`ENV_VAR`], if set.
+///
+/// Reads the environment in place: `std::env::var_os` copies the value.
+#[cfg(feature = "capi")]
+fn with_spec<R>(f: impl FnOnce(Option<&[u8]>) -> R) -> R {
+    unsafe extern "C" {
+        fn getenv(name: *const core::ffi::c_char) -> *const core::ffi::c_char;
+    }
+    // SAFETY: The name is NUL-terminated, and a non-null result is a
+    // NUL-terminated string that stays valid while nothing modifies the
+    // environment, which holds for the duration of `f` like it does for
+    // `var_os`.
+    unsafe {
+        let value = getenv(c"FASTSTRINGS_TUNABLES".as_ptr());
+        f((!value.is_null()).then(|| std::ffi::CStr::from_ptr(value).to_bytes()))
+    }
+}
+
+#[cold]
+fn load() -> Tunables {
+    with_spec(|spec| match spec.map(core::str::from_utf8) {
+        Some(Ok(spec)) => Tunables::parse_with(spec, |_, _| {}),
+        _ => Tunables::default(),
+    })
+}
+
+#[cold]
+fn report(tunables: &Tunables) {
+    with_spec(|spec| match spec.map(core::str::from_utf8) {
+        Some(Ok(spec)) => {
+            for error in Tunables::parse(spec).1 {
+                eprintln!("faststrings: {ENV_VAR}: {error}");
+            }
+        }
+        Some(Err(_)) => eprintln!("faststrings: {ENV_VAR} is not valid UTF-8; ignoring it"),
+        None => {}
+    });
+    if let Some(isa) = tunables.isa {
+        let detected = crate::cpu::detect_isa_level();
+        if isa > detected {
+            eprintln!(
+                "faststrings: {ENV_VAR}: isa={} is not supported by this CPU; using {}",
+                isa.name(),
+                detected.name()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn test_parse_all_tunables() {
+        let (t, errors) = Tunables::parse(
+            "isa=avx2:non_temporal=off:rep_movsb_threshold=2M:memcpy_nt_threshold=64k:\
+             memset_nt_threshold=1G:tiny_threshold=16:large_threshold=4K",
+        );
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(
+            t,
+            Tunables {
+                isa: Some(IsaLevel::Avx2),
+                non_temporal: Some(false),
+                rep_movsb_threshold: Some(2 << 20),
+                memcpy_nt_threshold: Some(64 << 10),
+                memset_nt_threshold: Some(1 << 30),
+                tiny_threshold: Some(16),
+                large_threshold: Some(4096),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_isa_names() {
+        for level in IsaLevel::ALL {
+            let (t, errors) = Tunables::parse(&format!("isa={}", level.name()));
+            assert!(errors.is_empty());
+            assert_eq!(t.isa, Some(level));
+        }
+        assert_eq!(Tunables::parse("isa=SSE42").0.isa, Some(IsaLevel::Sse42));
+    }
+
+    #[test]
+    fn test_invalid_entries_are_reported_and_skipped() {
+        let (t, errors) = Tunables::parse(
+            "isa=avx1024::bogus=1:non_temporal:memcpy_nt_threshold=12q:\
+             memset_nt_threshold=1:tiny_threshold=0:rep_movsb_threshold=99999999999999999999G:\
+             large_threshold=256",
+        );
+        assert_eq!(
+            t,
+            Tunables {
+                large_threshold: Some(256),
+                ..Default::default()
+            }
+        );
+        let rejected: Vec<_> = errors.iter().map(|e| e.entry.as_str()).collect();
+        assert_eq!(
+            rejected,
+            [
+                "isa=avx1024",
+                "bogus=1",
+                "non_temporal",
+                "memcpy_nt_threshold=12q",
+                "memset_nt_threshold=1",
+                "tiny_threshold=0",
+                "rep_movsb_threshold=99999999999999999999G",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_overrides_thresholds() {
+        let mut t = Thresholds::DEFAULT;
+        Tunables::parse("rep_movsb_threshold=4096:memset_nt_threshold=8M")
+            .0
+            .apply(&mut t);
+        assert_eq!(t.rep_movsb, 4096);
+        assert_eq!(t.memcpy_nt, Thresholds::DEFAULT.memcpy_nt);
+        assert_eq!(t.memset_nt, 8 << 20);
+
+        // Disabling NT wins over explicit NT cutoffs.
+        Tunables::parse("memcpy_nt_threshold=8M:non_temporal=0")
+            .0
+            .apply(&mut t);
+        assert_eq!(t.memcpy_nt, usize::MAX);
+        assert_eq!(t.memset_nt, usize::MAX);
+    }
+}
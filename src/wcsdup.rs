@@ -1,5 +1,12 @@
 //! `wcsdup` implementation.
 
+use alloc::collections::TryReserveError;
+use alloc::vec::Vec;
+use allocator_api2::alloc::Allocator;
+use allocator_api2::collections::TryReserveError as TryReserveErrorIn;
+use allocator_api2::vec::Vec as VecIn;
+
+use crate::strdup::{dup, dup_in, try_dup, try_dup_in};
 use crate::types::wchar_t;
 use crate::wide::wcslen;
 
@@ -7,17 +14,35 @@ use crate::wide::wcslen;
 ///
 /// The returned vector is always nul-terminated.
 pub fn wcsdup(src: &[wchar_t]) -> Vec<wchar_t> {
-    let src_len = wcslen(src).min(src.len());
-    let mut out = Vec::with_capacity(src_len + 1);
-    out.extend_from_slice(&src[..src_len]);
-    out.push(0);
-    out
+    dup(&src[..wcslen(src).min(src.len())], 0)
+}
+
+/// Like [`wcsdup`], but returns an error instead of aborting when the
+/// allocation fails.
+pub fn try_wcsdup(src: &[wchar_t]) -> Result<Vec<wchar_t>, TryReserveError> {
+    try_dup(&src[..wcslen(src).min(src.len())], 0)
+}
+
+/// Like [`wcsdup`], allocating from `alloc`.
+pub fn wcsdup_in<A: Allocator>(src: &[wchar_t], alloc: A) -> VecIn<wchar_t, A> {
+    dup_in(&src[..wcslen(src).min(src.len())], 0, alloc)
+}
+
+/// Like [`wcsdup_in`], but returns an error instead of aborting when the
+/// allocation fails.
+pub fn try_wcsdup_in<A: Allocator>(
+    src: &[wchar_t],
+    alloc: A,
+) -> Result<VecIn<wchar_t, A>, TryReserveErrorIn> {
+    try_dup_in(&src[..wcslen(src).min(src.len())], 0, alloc)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::wcsdup;
-    use crate::types::wchar_t;
+    use super::*;
+    use crate::strdup::tests::Exhausted;
+    use allocator_api2::alloc::Global;
+    use std::vec;
 
     #[test]
     fn test_wcsdup_basic() {
@@ -30,4 +55,14 @@ mod tests {
         let src = [b'a' as wchar_t, b'b' as wchar_t];
         assert_eq!(wcsdup(&src), vec![b'a' as wchar_t, b'b' as wchar_t, 0]);
     }
+
+    #[test]
+    fn test_wcsdup_variants_agree() {
+        let src = [b'a' as wchar_t, b'b' as wchar_t, 0, b'c' as wchar_t];
+        let expected = wcsdup(&src);
+        assert_eq!(try_wcsdup(&src).unwrap(), expected);
+        assert_eq!(wcsdup_in(&src, Global).as_slice(), expected);
+        assert_eq!(try_wcsdup_in(&src, Global).unwrap().as_slice(), expected);
+        assert!(try_wcsdup_in(&src, Exhausted).is_err());
+    }
 }
@@ -39,9 +39,37 @@ pub fn wcstok<'a>(
     Some(&s[start..pos])
 }
 
+/// Iterator over the tokens of a nul-terminated wide string.
+///
+/// The iterator form of [`wcstok`].
+pub struct WcsTokens<'a> {
+    s: &'a [wchar_t],
+    delim: &'a [wchar_t],
+    saveptr: usize,
+}
+
+impl<'a> WcsTokens<'a> {
+    /// Create a tokenizer over `s`, splitting at any wide character in `delim`.
+    pub fn new(s: &'a [wchar_t], delim: &'a [wchar_t]) -> Self {
+        Self {
+            s,
+            delim,
+            saveptr: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for WcsTokens<'a> {
+    type Item = &'a [wchar_t];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        wcstok(self.s, self.delim, &mut self.saveptr)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::wcstok;
+    use super::{WcsTokens, wcstok};
     use crate::types::wchar_t;
 
     #[test]
@@ -62,4 +90,21 @@ mod tests {
         assert_eq!(wcstok(&s, &delim, &mut save), Some(&[b'c' as wchar_t][..]));
         assert_eq!(wcstok(&s, &delim, &mut save), None);
     }
+
+    #[test]
+    fn test_wcs_tokens_iterator() {
+        let s = [
+            b',' as wchar_t,
+            b'a' as wchar_t,
+            b',' as wchar_t,
+            b',' as wchar_t,
+            b'b' as wchar_t,
+            0,
+        ];
+        let delim = [b',' as wchar_t, 0];
+        let mut tokens = WcsTokens::new(&s, &delim);
+        assert_eq!(tokens.next(), Some(&[b'a' as wchar_t][..]));
+        assert_eq!(tokens.next(), Some(&[b'b' as wchar_t][..]));
+        assert_eq!(tokens.next(), None);
+    }
 }
@@ -4,6 +4,7 @@
 //! Wide characters are represented as wchar_t.
 
 use crate::types::wchar_t;
+use core::mem::MaybeUninit;
 
 /// Calculate the length of a null-terminated wide string
 ///
@@ -45,6 +46,20 @@ pub fn wcscpy(dest: &mut [wchar_t], src: &[wchar_t]) -> usize {
     content_len
 }
 
+/// Copy a wide string into an uninitialized destination
+///
+/// Like [`wcscpy`], but `dest` need not be initialized. Returns the wide
+/// characters written, including the null if there was room for it.
+pub fn wcscpy_uninit<'a>(
+    dest: &'a mut [MaybeUninit<wchar_t>],
+    src: &[wchar_t],
+) -> &'a mut [wchar_t] {
+    let src_len = wcslen(src);
+    // `src[src_len]` is the null, when there is one.
+    let n = (src_len + usize::from(src_len < src.len())).min(dest.len());
+    crate::wmem::wmemcpy_uninit(dest, &src[..n])
+}
+
 /// Copy a wide string with length limit
 pub fn wcsncpy(dest: &mut [wchar_t], src: &[wchar_t], n: usize) -> usize {
     let limit = dest.len().min(n);
@@ -416,6 +431,19 @@ mod tests {
         assert_eq!(dest2[3], 0);
     }
 
+    #[test]
+    fn test_wcscpy_uninit_matches_wcscpy() {
+        let src = [b'a' as wchar_t, b'b' as wchar_t, 0, b'c' as wchar_t];
+        for cap in 0..5 {
+            let mut init = [7 as wchar_t; 4];
+            let n = wcscpy(&mut init[..cap.min(4)], &src);
+            let mut dest = [MaybeUninit::<wchar_t>::uninit(); 4];
+            assert_eq!(wcscpy_uninit(&mut dest[..cap.min(4)], &src), &init[..n]);
+        }
+        let mut dest = [MaybeUninit::<wchar_t>::uninit(); 4];
+        assert_eq!(wcscpy_uninit(&mut dest, &src[..2]), &src[..2]);
+    }
+
     #[test]
     fn test_wcscat_wcsncat() {
         let mut dest = [b'h' as wchar_t, b'i' as wchar_t, 0, 0, 0];
@@ -5,6 +5,12 @@
 
 #![allow(unsafe_code)]
 
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::mem::MaybeUninit;
+use core::ops::Range;
+
+use crate::mem::move_within_len;
 use crate::types::wchar_t;
 
 /// Copy wide character array
@@ -17,6 +23,42 @@ pub fn wmemcpy(dest: &mut [wchar_t], src: &[wchar_t]) -> usize {
     n
 }
 
+/// Copy wide characters into an uninitialized destination
+///
+/// Like [`wmemcpy`], but `dest` need not be initialized. Returns the
+/// `min(dest.len(), src.len())` wide characters written, now initialized.
+pub fn wmemcpy_uninit<'a>(
+    dest: &'a mut [MaybeUninit<wchar_t>],
+    src: &[wchar_t],
+) -> &'a mut [wchar_t] {
+    let n = dest.len().min(src.len());
+    let d = dest.as_mut_ptr() as *mut wchar_t;
+    // SAFETY: Both slices hold at least `n` elements and cannot overlap; the
+    // byte kernel needs no alignment, and the copy initializes the `n`
+    // elements the returned slice covers.
+    unsafe {
+        crate::memcpy::optimized_memcpy_unified(
+            d as *mut u8,
+            src.as_ptr() as *const u8,
+            n * core::mem::size_of::<wchar_t>(),
+        );
+        core::slice::from_raw_parts_mut(d, n)
+    }
+}
+
+/// Append wide characters to a vector
+///
+/// Like `Vec::extend_from_slice`, copying `src` straight into the spare
+/// capacity with the optimized kernel.
+#[cfg(feature = "alloc")]
+pub fn wvec_extend(vec: &mut Vec<wchar_t>, src: &[wchar_t]) {
+    vec.reserve(src.len());
+    let len = vec.len();
+    wmemcpy_uninit(vec.spare_capacity_mut(), src);
+    // SAFETY: The first `src.len()` spare elements were just initialized.
+    unsafe { vec.set_len(len + src.len()) };
+}
+
 /// Copy wide character array and return end index (wmempcpy).
 ///
 /// Equivalent to wmemcpy but returns the index one past the last element copied.
@@ -30,7 +72,8 @@ pub fn wmempcpy(dest: &mut [wchar_t], src: &[wchar_t]) -> usize {
 
 /// Copy wide character array (overlapping safe)
 ///
-/// In safe Rust, this behaves identically to wmemcpy.
+/// In safe Rust, this behaves identically to wmemcpy. Use
+/// [`wmemmove_within`] for overlapping moves inside one buffer.
 pub fn wmemmove(dest: &mut [wchar_t], src: &[wchar_t]) -> usize {
     let n = dest.len().min(src.len());
     if n == 0 {
@@ -41,11 +84,44 @@ pub fn wmemmove(dest: &mut [wchar_t], src: &[wchar_t]) -> usize {
     n
 }
 
+/// Move wide characters within one buffer (overlapping safe)
+///
+/// Copies `buf[src]` to `buf[dest..]` as if through a temporary, so the two
+/// ranges may overlap in either direction. Returns the number of wide
+/// characters moved.
+///
+/// # Panics
+///
+/// Panics if `src` is decreasing or ends past the buffer, or if fewer than
+/// `src.len()` elements follow `dest`.
+pub fn wmemmove_within(buf: &mut [wchar_t], src: Range<usize>, dest: usize) -> usize {
+    let n = move_within_len(buf.len(), &src, dest);
+    // SAFETY: `move_within_len` checked that both ranges lie within `buf`;
+    // the byte kernel needs no alignment.
+    unsafe {
+        let base = buf.as_mut_ptr();
+        crate::memmove::optimized_memmove_unified(
+            base.add(dest) as *mut u8,
+            base.add(src.start) as *const u8,
+            n * core::mem::size_of::<wchar_t>(),
+        );
+    }
+    n
+}
+
 /// Fill wide character array with a constant
 ///
 /// Sets all wide characters in `dest` to `c`. Returns the count.
 pub fn wmemset(dest: &mut [wchar_t], c: wchar_t) -> usize {
-    dest.fill(c);
+    // SAFETY: `dest` is writable for its length, which the pattern fill
+    // takes in bytes.
+    unsafe {
+        crate::memset::optimized_memset_pattern(
+            dest.as_mut_ptr() as *mut u8,
+            &c.to_ne_bytes(),
+            core::mem::size_of_val(dest),
+        );
+    }
     dest.len()
 }
 
@@ -121,6 +197,56 @@ mod tests {
         assert_eq!(buf, [2, 3, 4, 5, 5]);
     }
 
+    #[test]
+    fn test_wmemmove_within_matches_copy_within() {
+        let mut init = [0 as wchar_t; 300];
+        for (i, c) in init.iter_mut().enumerate() {
+            *c = (i * 31 + 1) as wchar_t;
+        }
+
+        for n in [0usize, 1, 3, 15, 16, 17, 64, 200] {
+            for (start, dest) in [(0, 1), (1, 0), (0, 9), (9, 0), (20, 20)] {
+                let mut expect = init;
+                expect.copy_within(start..start + n, dest);
+                let mut buf = init;
+                assert_eq!(wmemmove_within(&mut buf, start..start + n, dest), n);
+                assert_eq!(buf, expect, "n={n} start={start} dest={dest}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_wmemset_sizes_and_offsets() {
+        let mut buf = [7 as wchar_t; 600];
+        for off in 0..4 {
+            for n in [0usize, 1, 3, 16, 17, 33, 64, 65, 300, 595] {
+                buf.fill(7);
+                let c = (0x1234_5600 + n) as wchar_t;
+                assert_eq!(wmemset(&mut buf[off..off + n], c), n);
+                assert!(buf[..off].iter().all(|&v| v == 7));
+                assert!(buf[off..off + n].iter().all(|&v| v == c), "n={n} off={off}");
+                assert!(buf[off + n..].iter().all(|&v| v == 7));
+            }
+        }
+    }
+
+    #[test]
+    fn test_wmemcpy_uninit_and_wvec_extend() {
+        let src: [wchar_t; 100] = core::array::from_fn(|i| (i * 7 + 1) as wchar_t);
+        let mut dest = [MaybeUninit::<wchar_t>::uninit(); 100];
+        for n in [0usize, 1, 15, 16, 17, 100] {
+            assert_eq!(wmemcpy_uninit(&mut dest[..n], &src), &src[..n]);
+        }
+
+        #[cfg(feature = "alloc")]
+        {
+            let mut v = Vec::new();
+            wvec_extend(&mut v, &src[..3]);
+            wvec_extend(&mut v, &src[3..]);
+            assert_eq!(v, src);
+        }
+    }
+
     #[test]
     fn test_wmempcpy_basic() {
         let src = [1 as wchar_t, 2, 3];
@@ -0,0 +1,194 @@
+//! Wiping secrets from memory.
+//!
+//! Clearing a buffer with `fill(0)` or [`memset`](crate::mem::memset) just
+//! before it goes out of scope is a dead store, and the optimizer is free to
+//! drop it, leaving the key in freed memory. [`Zeroize`] erases a value with
+//! [`memset_explicit`](crate::mem::memset_explicit), whose volatile stores
+//! and compiler fence are always kept, and [`Zeroizing`] does so when the
+//! value is dropped.
+//!
+//! ```
+//! use faststrings::zeroize::Zeroizing;
+//!
+//! let mut key = Zeroizing::new([0u8; 32]);
+//! key[..6].copy_from_slice(b"s3cr3t");
+//! assert_eq!(&key[..6], b"s3cr3t");
+//! // `key` is overwritten with zeros here.
+//! ```
+//!
+//! Only the memory the value occupies when it is wiped is cleared: copies
+//! left behind by moves, or by a `Vec` reallocating as it grows, are not.
+//! Reserve capacity up front and keep secrets in place (for example in a
+//! `Box`) when that matters.
+#![allow(unsafe_code)]
+
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{Ordering, compiler_fence};
+
+#[cfg(feature = "alloc")]
+use crate::memset::optimized_memset_explicit;
+
+/// A value that can be securely overwritten with zeros.
+pub trait Zeroize {
+    /// Overwrites the value with zeros using stores the optimizer keeps.
+    ///
+    /// Containers are left empty, with their whole allocation wiped.
+    fn zeroize(&mut self);
+}
+
+impl Zeroize for [u8] {
+    fn zeroize(&mut self) {
+        crate::mem::explicit_bzero(self);
+    }
+}
+
+impl<const N: usize> Zeroize for [u8; N] {
+    fn zeroize(&mut self) {
+        crate::mem::explicit_bzero(self);
+    }
+}
+
+macro_rules! zeroize_int {
+    ($($t:ty)*) => {$(
+        impl Zeroize for $t {
+            fn zeroize(&mut self) {
+                // SAFETY: `self` is a valid, aligned reference.
+                unsafe { core::ptr::write_volatile(self, 0) };
+                compiler_fence(Ordering::SeqCst);
+            }
+        }
+    )*};
+}
+
+zeroize_int!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+#[cfg(feature = "alloc")]
+impl Zeroize for Vec<u8> {
+    fn zeroize(&mut self) {
+        // SAFETY: The allocation is writable for its whole capacity, and
+        // bytes stay initialized however they are overwritten.
+        unsafe { optimized_memset_explicit(self.as_mut_ptr(), 0, self.capacity()) };
+        self.clear();
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Zeroize for String {
+    fn zeroize(&mut self) {
+        // SAFETY: The vector is left empty, which is valid UTF-8.
+        unsafe { self.as_mut_vec() }.zeroize();
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Zeroize + ?Sized> Zeroize for Box<T> {
+    fn zeroize(&mut self) {
+        (**self).zeroize();
+    }
+}
+
+/// Owns a value and [zeroizes](Zeroize) it when dropped.
+///
+/// It dereferences to the value, so it can be used in its place.
+#[derive(Default)]
+pub struct Zeroizing<T: Zeroize>(T);
+
+impl<T: Zeroize> Zeroizing<T> {
+    /// Wraps `value`, to be wiped when the wrapper is dropped.
+    pub const fn new(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Zeroize> From<T> for Zeroizing<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Zeroize> Deref for Zeroizing<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> DerefMut for Zeroizing<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Zeroize + Clone> Clone for Zeroizing<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.0.zeroize();
+        self.0.clone_from(&source.0);
+    }
+}
+
+/// Prints `Zeroizing(..)`, keeping the secret out of logs.
+impl<T: Zeroize> fmt::Debug for Zeroizing<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Zeroizing(..)")
+    }
+}
+
+impl<T: Zeroize> Drop for Zeroizing<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zeroize_values() {
+        let mut arr = *b"secret";
+        arr.zeroize();
+        assert_eq!(arr, [0; 6]);
+
+        let mut n = 0xdead_beef_u64;
+        n.zeroize();
+        assert_eq!(n, 0);
+
+        let mut v = Vec::with_capacity(64);
+        v.extend_from_slice(b"password");
+        v.truncate(4);
+        let (ptr, cap) = (v.as_ptr(), v.capacity());
+        v.zeroize();
+        assert!(v.is_empty());
+        // The truncated tail is wiped too.
+        let all = unsafe { core::slice::from_raw_parts(ptr, cap) };
+        assert!(all.iter().all(|&b| b == 0));
+
+        let mut s = String::from("token");
+        s.zeroize();
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn test_zeroizing_wraps_value() {
+        let mut key = Zeroizing::new(Box::new([7u8; 16]));
+        key[0] = 1;
+        assert_eq!(key[..2], [1, 7]);
+        assert_eq!(std::format!("{key:?}"), "Zeroizing(..)");
+        let copy = key.clone();
+        assert_eq!(**copy, **key);
+    }
+}
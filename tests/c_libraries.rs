@@ -0,0 +1,50 @@
+//! Builds the C libraries the way the `capi` module docs describe and calls
+//! into the shared one. This runs a nested `cargo rustc --release`, so it
+//! lives apart from the unit tests.
+
+#![cfg(all(feature = "capi", target_os = "linux"))]
+
+use std::ffi::{c_char, c_int, c_void};
+use std::path::Path;
+use std::process::Command;
+
+unsafe extern "C" {
+    fn dlopen(filename: *const c_char, flags: c_int) -> *mut c_void;
+    fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+}
+
+#[test]
+fn test_c_libraries_build_and_load() {
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("c-libs");
+
+    for crate_type in ["cdylib", "staticlib"] {
+        let build = Command::new(env!("CARGO"))
+            .args(["rustc", "--release", "--lib", "--features", "capi"])
+            .args(["--crate-type", crate_type])
+            .arg("--manifest-path")
+            .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"))
+            .arg("--target-dir")
+            .arg(&target_dir)
+            .output()
+            .unwrap();
+        assert!(
+            build.status.success(),
+            "cargo rustc --crate-type {crate_type}: {}",
+            String::from_utf8_lossy(&build.stderr)
+        );
+    }
+
+    let out = target_dir.join("release");
+    assert!(out.join("libfaststrings.a").is_file());
+    let so = format!("{}\0", out.join("libfaststrings.so").display());
+    unsafe {
+        // RTLD_NOW | RTLD_LOCAL
+        let lib = dlopen(so.as_ptr().cast(), 2);
+        assert!(!lib.is_null(), "dlopen {so}");
+        assert!(!dlsym(lib, c"faststrings_set_chk_fail_hook".as_ptr()).is_null());
+        let len = dlsym(lib, c"strlen".as_ptr());
+        assert!(!len.is_null());
+        let len: unsafe extern "C" fn(*const c_char) -> usize = std::mem::transmute(len);
+        assert_eq!(len(c"shared".as_ptr()), 6);
+    }
+}
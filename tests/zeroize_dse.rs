@@ -0,0 +1,96 @@
+//! Checks that the explicit wipes survive dead-store elimination.
+//!
+//! The global allocator of this test binary counts the nonzero bytes of one
+//! watched allocation as it is freed. A plain `fill(0)` right before the free
+//! is a dead store that the optimizer removes, so the count comes back
+//! nonzero; the explicit wipes must leave nothing behind.
+//!
+//! The check only means something under `cargo test --release`: the default
+//! test profile does not remove dead stores, so there even a plain `fill(0)`
+//! passes. It lives in its own binary so the inspecting allocator stays out
+//! of the unit tests.
+
+#![cfg(feature = "alloc")]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use faststrings::mem::explicit_bzero;
+use faststrings::zeroize::Zeroizing;
+
+/// Address of the allocation to inspect when it is freed.
+static WATCHED: AtomicUsize = AtomicUsize::new(0);
+/// Nonzero bytes the watched allocation held when freed.
+static LEFTOVER: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+struct Inspecting;
+
+// SAFETY: Every call is forwarded to the system allocator.
+unsafe impl GlobalAlloc for Inspecting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let addr = ptr as usize;
+        if WATCHED
+            .compare_exchange(addr, 0, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+        {
+            // SAFETY: The block is still allocated and initialized.
+            let bytes = unsafe { std::slice::from_raw_parts(ptr, layout.size()) };
+            let left = bytes.iter().filter(|&&b| b != 0).count();
+            LEFTOVER.store(left, Ordering::SeqCst);
+        }
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Inspecting = Inspecting;
+
+/// Nonzero bytes left in the allocation at `addr` by `run`, which must free
+/// it.
+fn leftover_after(addr: usize, run: impl FnOnce()) -> usize {
+    LEFTOVER.store(usize::MAX, Ordering::SeqCst);
+    WATCHED.store(addr, Ordering::SeqCst);
+    run();
+    LEFTOVER.load(Ordering::SeqCst)
+}
+
+/// Fills a key, reads it, wipes it and frees it, so the wipe is the last
+/// thing to touch the memory.
+#[inline(never)]
+fn wipe_then_free(mut key: Box<[u8; 256]>, secret: u8) -> u32 {
+    key.fill(secret);
+    let sum = key.iter().map(|&b| b as u32).sum();
+    explicit_bzero(&mut *key);
+    sum
+}
+
+/// Like [`wipe_then_free`], with the wipe left to [`Zeroizing`].
+#[inline(never)]
+fn drop_zeroizing(mut key: Zeroizing<Box<[u8; 256]>>, secret: u8) -> u32 {
+    key.fill(secret);
+    key.iter().map(|&b| b as u32).sum()
+}
+
+// One test, so no other test changes the statics meanwhile.
+#[test]
+fn test_wipes_survive_dead_store_elimination() {
+    let secret = std::hint::black_box(0xa5);
+
+    let key = Box::new([0u8; 256]);
+    let addr = key.as_ptr() as usize;
+    let left = leftover_after(addr, || {
+        assert_eq!(wipe_then_free(key, secret), 0xa5 * 256);
+    });
+    assert_eq!(left, 0, "explicit_bzero was optimized out");
+
+    let key = Zeroizing::new(Box::new([0u8; 256]));
+    let addr = key.as_ptr() as usize;
+    let left = leftover_after(addr, || {
+        assert_eq!(drop_zeroizing(key, secret), 0xa5 * 256);
+    });
+    assert_eq!(left, 0, "Zeroizing left its secret behind");
+}